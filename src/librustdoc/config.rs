@@ -25,6 +25,11 @@
 
 use crate::core::new_dcx;
 use crate::externalfiles::ExternalHtml;
+use crate::fuzz_target::api_graph::GraphTraverseAlgorithm;
+use crate::fuzz_target::cargo_fuzz::FuzzEngine;
+use crate::fuzz_target::fuzzable_type::InputLayout;
+use crate::fuzz_target::named_value::NamedValue;
+use crate::fuzz_target::unsafe_policy::UnsafePolicy;
 use crate::html;
 use crate::html::markdown::IdMap;
 use crate::html::render::StylePath;
@@ -292,6 +297,53 @@ pub(crate) struct RenderOptions {
     pub(crate) no_emit_shared: bool,
     /// If `true`, HTML source code pages won't be generated.
     pub(crate) html_no_source: bool,
+    /// File of user supplied instantiations for generic APIs, used by the fuzz target generator.
+    pub(crate) fuzz_hints: Option<PathBuf>,
+    /// Let the fuzz target generator instantiate generic APIs with misbehaving trait impls.
+    pub(crate) fuzz_evil_impls: bool,
+    /// Let the fuzz target generator instantiate `A: Allocator` with an allocator failing on fuzz
+    /// input.
+    pub(crate) fuzz_allocator: bool,
+    /// Install a global allocator failing on fuzz input in every generated fuzz target.
+    pub(crate) fuzz_global_allocator: bool,
+    /// Graph traversal building API sequences, `None` for the default search of the fuzz target
+    /// generator.
+    pub(crate) fuzz_strategy: Option<GraphTraverseAlgorithm>,
+    /// Seed of the random choices made by the fuzz target generator.
+    pub(crate) fuzz_seed: u64,
+    /// Coverage of a former fuzzing round, an llvm-cov export or a directory of AFL outputs.
+    pub(crate) fuzz_coverage: Option<PathBuf>,
+    /// Fuzz target, e.g. `test_url3`, whose crashes a minimizer is written for.
+    pub(crate) fuzz_minimize: Option<String>,
+    /// Whether fuzz targets decode their input with the runtime crate instead of pasted helpers.
+    pub(crate) fuzz_runtime_crate: bool,
+    /// Layout of the fuzz input, `Sequential` lifts the limit of one dynamic length dimension.
+    pub(crate) fuzz_layout: InputLayout,
+    /// Whether to also write fuzz targets applying fuzz-selected operations to a receiver in a
+    /// loop.
+    pub(crate) fuzz_op_loop: bool,
+    /// Whether unsafe APIs are called by the fuzz targets, by fuzz targets of their own, or only
+    /// when their `# Safety` section asks nothing of the arguments.
+    pub(crate) fuzz_unsafe: UnsafePolicy,
+    /// Whether to also write fuzz targets feeding one input to several instantiations of a
+    /// generic API and asserting that their results agree.
+    pub(crate) fuzz_differential: bool,
+    /// Whether to also write fuzz targets checking the laws of hand-written `PartialEq`, `Eq`,
+    /// `PartialOrd`, `Ord`, `Hash` and `Clone` impls of local types.
+    pub(crate) fuzz_trait_laws: bool,
+    /// Whether to also write fuzz targets asserting that values of local types survive
+    /// `Display` and `FromStr`, serde, or a pair of functions like `to_bytes` and `from_bytes`.
+    pub(crate) fuzz_round_trip: bool,
+    /// Whether panics raised by APIs whose doc has a `# Panics` section are expected instead of
+    /// findings.
+    pub(crate) fuzz_expect_documented_panics: bool,
+    /// Fuzzer the fuzz targets are written for, `Libfuzzer` writes a cargo-fuzz project.
+    pub(crate) fuzz_engine: FuzzEngine,
+    /// Whether to write one fuzz target running all sequences, picked by the first input bytes,
+    /// instead of a fuzz target per sequence.
+    pub(crate) fuzz_multiplex: bool,
+    /// Fuzz target, e.g. `test_url3`, whose crashes are turned into `#[test]` functions.
+    pub(crate) fuzz_regression: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -736,6 +788,69 @@ fn println_condition(condition: Condition) {
         let extern_html_root_takes_precedence =
             matches.opt_present("extern-html-root-takes-precedence");
         let html_no_source = matches.opt_present("html-no-source");
+        let fuzz_hints = matches.opt_str("fuzz-hints").map(PathBuf::from);
+        let fuzz_evil_impls = matches.opt_present("fuzz-evil-impls");
+        let fuzz_allocator = matches.opt_present("fuzz-allocator");
+        let fuzz_global_allocator = matches.opt_present("fuzz-global-allocator");
+        let fuzz_strategy = match matches.opt_str("fuzz-strategy").as_deref() {
+            Some("default") | None => None,
+            Some(s) => match GraphTraverseAlgorithm::from_name(s) {
+                Some(algorithm) => Some(algorithm),
+                None => dcx.fatal(format!(
+                    "unknown fuzz strategy: {s}, expected `default` or one of: {}",
+                    GraphTraverseAlgorithm::names()
+                )),
+            },
+        };
+        let fuzz_coverage = matches.opt_str("fuzz-coverage").map(PathBuf::from);
+        let fuzz_minimize = matches.opt_str("fuzz-minimize");
+        let fuzz_runtime_crate = matches.opt_present("fuzz-runtime-crate");
+        let fuzz_op_loop = matches.opt_present("fuzz-op-loop");
+        let fuzz_differential = matches.opt_present("fuzz-differential");
+        let fuzz_multiplex = matches.opt_present("fuzz-multiplex");
+        let fuzz_regression = matches.opt_str("fuzz-regression");
+        let fuzz_trait_laws = matches.opt_present("fuzz-trait-laws");
+        let fuzz_round_trip = matches.opt_present("fuzz-round-trip");
+        let fuzz_expect_documented_panics = matches.opt_present("fuzz-expect-documented-panics");
+        let fuzz_unsafe = match matches.opt_str("fuzz-unsafe") {
+            None => UnsafePolicy::Include,
+            Some(s) => match UnsafePolicy::from_name(&s) {
+                Some(policy) => policy,
+                None => dcx.fatal(format!(
+                    "unknown unsafe policy: {s}, expected one of: {}",
+                    UnsafePolicy::names()
+                )),
+            },
+        };
+        let fuzz_layout = match matches.opt_str("fuzz-layout") {
+            None => InputLayout::Split,
+            Some(s) => match InputLayout::from_name(&s) {
+                Some(layout) => layout,
+                None => dcx.fatal(format!(
+                    "unknown fuzz layout: {s}, expected one of: {}",
+                    InputLayout::names()
+                )),
+            },
+        };
+        let fuzz_engine = match matches.opt_str("fuzz-engine") {
+            None => FuzzEngine::Afl,
+            Some(s) => match FuzzEngine::from_name(&s) {
+                Some(engine) => engine,
+                None => dcx.fatal(format!(
+                    "unknown fuzz engine: {s}, expected one of: {}",
+                    FuzzEngine::names()
+                )),
+            },
+        };
+        let fuzz_seed = match matches.opt_str("fuzz-seed") {
+            Some(s) => match s.parse::<u64>() {
+                Ok(seed) => seed,
+                Err(_) => {
+                    dcx.fatal(format!("`--fuzz-seed` expects an unsigned integer, found `{s}`"))
+                }
+            },
+            None => 0,
+        };
 
         if generate_link_to_definition && (show_coverage || output_format != OutputFormat::Html) {
             dcx.fatal(
@@ -821,6 +936,25 @@ fn println_condition(condition: Condition) {
             call_locations,
             no_emit_shared: false,
             html_no_source,
+            fuzz_hints,
+            fuzz_evil_impls,
+            fuzz_allocator,
+            fuzz_global_allocator,
+            fuzz_strategy,
+            fuzz_seed,
+            fuzz_coverage,
+            fuzz_minimize,
+            fuzz_runtime_crate,
+            fuzz_layout,
+            fuzz_op_loop,
+            fuzz_unsafe,
+            fuzz_differential,
+            fuzz_trait_laws,
+            fuzz_round_trip,
+            fuzz_expect_documented_panics,
+            fuzz_engine,
+            fuzz_multiplex,
+            fuzz_regression,
         };
         Some((options, render_options))
     }
@@ -2,7 +2,7 @@
 
 use crate::clean::PrimitiveType;
 use crate::fuzz_target::api_util::get_type_name_from_did;
-use crate::fuzz_target::fuzzable_type::FuzzableType;
+use crate::fuzz_target::fuzzable_type::{FuzzableType, InputLayout};
 use rustc_data_structures::fx::FxHashSet;
 use rustc_hir::def::CtorKind;
 
@@ -110,32 +110,32 @@ pub(crate) fn _get_all_dependent_afl_helpers(&self) -> Vec<_AflHelpers> {
                     helpers.append(&mut u8_dependency);
                 }
                 _AflHelpers::_I16 => {
-                    let mut i8_dependency = _AflHelpers::_I8._get_all_dependent_afl_helpers();
-                    helpers.append(&mut i8_dependency);
+                    let mut u16_dependency = _AflHelpers::_U16._get_all_dependent_afl_helpers();
+                    helpers.append(&mut u16_dependency);
                 }
                 _AflHelpers::_U32 => {
                     let mut u16_dependency = _AflHelpers::_U16._get_all_dependent_afl_helpers();
                     helpers.append(&mut u16_dependency);
                 }
                 _AflHelpers::_I32 => {
-                    let mut i16_dependency = _AflHelpers::_I16._get_all_dependent_afl_helpers();
-                    helpers.append(&mut i16_dependency);
+                    let mut u32_dependency = _AflHelpers::_U32._get_all_dependent_afl_helpers();
+                    helpers.append(&mut u32_dependency);
                 }
                 _AflHelpers::_U64 => {
                     let mut u32_dependency = _AflHelpers::_U32._get_all_dependent_afl_helpers();
                     helpers.append(&mut u32_dependency);
                 }
                 _AflHelpers::_I64 => {
-                    let mut i32_dependency = _AflHelpers::_I32._get_all_dependent_afl_helpers();
-                    helpers.append(&mut i32_dependency);
+                    let mut u64_dependency = _AflHelpers::_U64._get_all_dependent_afl_helpers();
+                    helpers.append(&mut u64_dependency);
                 }
                 _AflHelpers::_U128 => {
                     let mut u64_dependency = _AflHelpers::_U64._get_all_dependent_afl_helpers();
                     helpers.append(&mut u64_dependency);
                 }
                 _AflHelpers::_I128 => {
-                    let mut i64_dependency = _AflHelpers::_I64._get_all_dependent_afl_helpers();
-                    helpers.append(&mut i64_dependency);
+                    let mut u128_dependency = _AflHelpers::_U128._get_all_dependent_afl_helpers();
+                    helpers.append(&mut u128_dependency);
                 }
                 _AflHelpers::_Usize => {
                     let mut u64_dependency = _AflHelpers::_U64._get_all_dependent_afl_helpers();
@@ -241,24 +241,9 @@ pub(crate) fn _to_function_name(&self) -> String {
 
     pub(crate) fn _print_all() {
         println!("afl helper functions: ");
-        println!("{}", _data_to_u8());
-        println!("{}", _data_to_i8());
-        println!("{}", _data_to_u16());
-        println!("{}", _data_to_i16());
-        println!("{}", _data_to_u32());
-        println!("{}", _data_to_i32());
-        println!("{}", _data_to_u64());
-        println!("{}", _data_to_i64());
-        println!("{}", _data_to_u128());
-        println!("{}", _data_to_i128());
-        println!("{}", _data_to_usize());
-        println!("{}", _data_to_isize());
-        println!("{}", _data_to_char());
-        println!("{}", _data_to_bool());
-        println!("{}", _data_to_str());
-        println!("{}", _data_to_slice());
-        println!("{}", _data_to_f32());
-        println!("{}", _data_to_f64());
+        for helper_function in _all_helper_functions() {
+            println!("{}", helper_function);
+        }
     }
 
     //may remove later
@@ -295,6 +280,7 @@ pub(crate) fn _generate_param_initial_statement(
         total_dynamic_param_numbers: usize,
         dynamic_param_length: &String,
         origin_fuzzable_type: &FuzzableType,
+        reader: Option<(&str, InputLayout)>,
     ) -> String {
         match self {
             _ => {
@@ -305,12 +291,15 @@ pub(crate) fn _generate_param_initial_statement(
                     total_dynamic_param_numbers,
                     dynamic_param_length,
                     origin_fuzzable_type,
+                    reader,
                 );
                 format!("let _param{param_index} = {rhs};", param_index = param_index, rhs = rhs)
             }
         }
     }
 
+    /// With a reader, values are taken from the `FuzzedDataProvider` of the runtime crate, in
+    /// the same layout as the indexes would give, or one after another in the sequential layout.
     pub(crate) fn _generate_param_initial_rhs(
         &self,
         fixed_start_index: usize,
@@ -319,6 +308,7 @@ pub(crate) fn _generate_param_initial_rhs(
         total_dynamic_param_numbers: usize,
         dynamic_param_length: &String,
         origin_fuzzable_type: &FuzzableType,
+        reader: Option<(&str, InputLayout)>,
     ) -> String {
         match self {
             _AflHelpers::_Bool
@@ -337,6 +327,9 @@ pub(crate) fn _generate_param_initial_rhs(
             | _AflHelpers::_Isize
             | _AflHelpers::_F32
             | _AflHelpers::_F64 => {
+                if let Some((reader, _)) = reader {
+                    return format!("{}.consume_{}()", reader, self._type_name());
+                }
                 format!(
                     "{afl_function_name}(data, {fixed_start_index})",
                     afl_function_name = self._to_function_name(),
@@ -344,6 +337,38 @@ pub(crate) fn _generate_param_initial_rhs(
                 )
             }
             _AflHelpers::_Str | _AflHelpers::_Slice(..) => {
+                if let Some((reader, layout)) = reader {
+                    return match (self, origin_fuzzable_type) {
+                        (
+                            _AflHelpers::_Slice(inner_afl_helper),
+                            FuzzableType::RefSlice(inner_fuzzable),
+                        ) if layout == InputLayout::Sequential
+                            && !inner_fuzzable._is_input_slice_element() =>
+                        {
+                            // the closure shadows the reader, elements read from the same input
+                            let inner_rhs = inner_afl_helper._generate_param_initial_rhs(
+                                0,
+                                0,
+                                0,
+                                0,
+                                dynamic_param_length,
+                                inner_fuzzable,
+                                Some((reader, layout)),
+                            );
+                            format!(
+                                "&{reader}.consume_vec(|{reader}| {inner_rhs}) as &[_]",
+                                reader = reader,
+                                inner_rhs = inner_rhs
+                            )
+                        }
+                        (_AflHelpers::_Slice(inner_afl_helper), _) => format!(
+                            "{}.consume_slice::<{}>()",
+                            reader,
+                            inner_afl_helper._type_name()
+                        ),
+                        _ => format!("{}.consume_str()", reader),
+                    };
+                }
                 let latter_index = if dynamic_param_index == total_dynamic_param_numbers - 1 {
                     format!("data.len()")
                 } else {
@@ -383,6 +408,7 @@ pub(crate) fn _generate_param_initial_rhs(
                             total_dynamic_param_numbers,
                             dynamic_param_length,
                             inner_origin_fuzzable_type,
+                            reader,
                         );
                         res.push_str(inner_rhs.as_str());
                         inner_fixed_start_index = inner_fixed_start_index
@@ -421,6 +447,7 @@ pub(crate) fn _generate_param_initial_rhs(
                                         total_dynamic_param_numbers,
                                         dynamic_param_length,
                                         inner_fuzzable,
+                                        reader,
                                     )
                                 ));
                                 inner_fixed_start_index =
@@ -455,6 +482,7 @@ pub(crate) fn _generate_param_initial_rhs(
                                         total_dynamic_param_numbers,
                                         dynamic_param_length,
                                         inner_fuzzable,
+                                        reader,
                                     )
                                 ));
                                 inner_fixed_start_index =
@@ -515,6 +543,7 @@ pub(crate) fn _get_afl_helpers_functions_of_sequence(
         }
         afl_helper_functions.push(afl_helper._to_full_function())
     }
+    afl_helper_functions.sort();
     Some(afl_helper_functions)
 }
 
@@ -538,9 +567,34 @@ pub(crate) fn _get_feature_gates_of_sequence(
     for feature_gate in feature_gates {
         features.push(feature_gate);
     }
+    features.sort();
     Some(features)
 }
 
+/// every helper function, each one defined once
+pub(crate) fn _all_helper_functions() -> Vec<&'static str> {
+    vec![
+        _data_to_u8(),
+        _data_to_i8(),
+        _data_to_u16(),
+        _data_to_i16(),
+        _data_to_u32(),
+        _data_to_i32(),
+        _data_to_u64(),
+        _data_to_i64(),
+        _data_to_u128(),
+        _data_to_i128(),
+        _data_to_usize(),
+        _data_to_isize(),
+        _data_to_char(),
+        _data_to_bool(),
+        _data_to_str(),
+        _data_to_slice(),
+        _data_to_f32(),
+        _data_to_f64(),
+    ]
+}
+
 pub(crate) fn _data_to_u8() -> &'static str {
     "fn _to_u8(data:&[u8], index:usize)->u8 {
     data[index]
@@ -563,9 +617,7 @@ pub(crate) fn _data_to_u16() -> &'static str {
 
 pub(crate) fn _data_to_i16() -> &'static str {
     "fn _to_i16(data:&[u8], index:usize)->i16 {
-    let data0 = _to_i8(data, index) as i16;
-    let data1 = _to_i8(data, index+1) as i16;
-    data0 << 8 | data1
+    _to_u16(data, index) as i16
 }\n"
 }
 
@@ -579,9 +631,7 @@ pub(crate) fn _data_to_u32() -> &'static str {
 
 pub(crate) fn _data_to_i32() -> &'static str {
     "fn _to_i32(data:&[u8], index:usize)->i32 {
-    let data0 = _to_i16(data, index) as i32;
-    let data1 = _to_i16(data, index+2) as i32;
-    data0 << 16 | data1
+    _to_u32(data, index) as i32
 }\n"
 }
 
@@ -604,9 +654,7 @@ pub(crate) fn _data_to_u64() -> &'static str {
 
 pub(crate) fn _data_to_i64() -> &'static str {
     "fn _to_i64(data:&[u8], index:usize)->i64 {
-    let data0 = _to_i32(data, index) as i64;
-    let data1 = _to_i32(data, index+4) as i64;
-    data0 << 32 | data1
+    _to_u64(data, index) as i64
 }\n"
 }
 
@@ -629,9 +677,7 @@ pub(crate) fn _data_to_u128() -> &'static str {
 
 pub(crate) fn _data_to_i128() -> &'static str {
     "fn _to_i128(data:&[u8], index:usize)->i128 {
-    let data0 = _to_i64(data, index) as i128;
-    let data1 = _to_i64(data, index+8) as i128;
-    data0 << 64 | data1
+    _to_u128(data, index) as i128
 }\n"
 }
 
@@ -0,0 +1,210 @@
+use crate::clean::{Path, PrimitiveType, Type};
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::fuzz_target::api_function::{ApiFunction, ApiUnsafety};
+use crate::fuzz_target::helper_type::{find_external_trait, simple_path};
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{DefId, DefIndex, LOCAL_CRATE};
+use rustc_hir::Mutability;
+use rustc_span::Symbol;
+
+pub(crate) static ALLOC_TYPE_NAME: &'static str = "FuzzAllocator";
+
+static ALLOCATOR_TRAIT: &'static str = "core::alloc::Allocator";
+
+/// Shared by the `Allocator` type and the global allocator. An injected failure is turned into
+/// a `FuzzAllocFailure` panic by the alloc error hook, so infallible APIs unwind instead of
+/// aborting and the driver can tell them from real bugs. Blocks are handed out aligned to
+/// exactly `layout.align()` and never more, which breaks code relying on malloc's alignment.
+static RUNTIME_DEFINITION: &'static str = r#"struct FuzzAllocFailure;
+
+static FUZZ_ALLOC_INJECTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+fn fuzz_alloc_init() {
+    static HOOK: std::sync::Once = std::sync::Once::new();
+    HOOK.call_once(|| {
+        std::alloc::set_alloc_error_hook(fuzz_alloc_error_hook);
+        let prev = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if !info.payload().is::<FuzzAllocFailure>() {
+                prev(info);
+            }
+        }));
+    });
+}
+
+fn fuzz_alloc_error_hook(layout: std::alloc::Layout) {
+    if FUZZ_ALLOC_INJECTED.swap(false, std::sync::atomic::Ordering::SeqCst) {
+        fuzz_alloc_arm(false);
+        std::panic::panic_any(FuzzAllocFailure);
+    }
+    eprintln!("memory allocation of {} bytes failed", layout.size());
+}
+
+fn fuzz_alloc_expected(err: &Box<dyn std::any::Any + Send>) -> bool {
+    err.is::<FuzzAllocFailure>()
+}
+
+fn fuzz_alloc_outer(layout: std::alloc::Layout) -> Option<std::alloc::Layout> {
+    let size = layout.size().checked_add(layout.align())?;
+    let align = layout.align().checked_mul(2)?;
+    std::alloc::Layout::from_size_align(size, align).ok()
+}
+"#;
+
+/// `FuzzAllocator::new(&[u8])` replays its bytes as the schedule, a control byte that is
+/// `15 mod 16` fails the allocation
+static ALLOCATOR_DEFINITION: &'static str = r#"#[derive(Debug)]
+struct FuzzAllocator {
+    schedule: Vec<u8>,
+    count: std::sync::atomic::AtomicUsize,
+}
+
+impl FuzzAllocator {
+    fn new(data: &[u8]) -> Self {
+        fuzz_alloc_init();
+        FuzzAllocator { schedule: data.to_vec(), count: std::sync::atomic::AtomicUsize::new(0) }
+    }
+}
+
+impl Clone for FuzzAllocator {
+    fn clone(&self) -> Self {
+        FuzzAllocator::new(&self.schedule)
+    }
+}
+
+unsafe impl std::alloc::Allocator for FuzzAllocator {
+    fn allocate(
+        &self,
+        layout: std::alloc::Layout,
+    ) -> Result<std::ptr::NonNull<[u8]>, std::alloc::AllocError> {
+        if !self.schedule.is_empty() {
+            let count = self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if self.schedule[count % self.schedule.len()] % 16 == 15 {
+                FUZZ_ALLOC_INJECTED.store(true, std::sync::atomic::Ordering::SeqCst);
+                return Err(std::alloc::AllocError);
+            }
+        }
+        let outer = fuzz_alloc_outer(layout).ok_or(std::alloc::AllocError)?;
+        let block = std::alloc::Global.allocate(outer)?;
+        let ptr = unsafe { block.cast::<u8>().add(layout.align()) };
+        Ok(std::ptr::NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: std::ptr::NonNull<u8>, layout: std::alloc::Layout) {
+        let outer = fuzz_alloc_outer(layout).unwrap();
+        std::alloc::Global.deallocate(ptr.sub(layout.align()), outer);
+    }
+}
+"#;
+
+/// The global allocator only fails inside the test function, the schedule is the first bytes
+/// of the input and a control byte that is `31 mod 32` fails the allocation
+static GLOBAL_DEFINITION: &'static str = r#"static FUZZ_ALLOC_ARMED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+static FUZZ_ALLOC_COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+static FUZZ_ALLOC_SCHEDULE: [std::sync::atomic::AtomicU8; 16] =
+    [const { std::sync::atomic::AtomicU8::new(0) }; 16];
+
+fn fuzz_alloc_schedule(data: &[u8]) {
+    fuzz_alloc_init();
+    for (i, slot) in FUZZ_ALLOC_SCHEDULE.iter().enumerate() {
+        slot.store(data.get(i).copied().unwrap_or(0), std::sync::atomic::Ordering::SeqCst);
+    }
+    FUZZ_ALLOC_COUNT.store(0, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn fuzz_alloc_arm(armed: bool) {
+    FUZZ_ALLOC_ARMED.store(armed, std::sync::atomic::Ordering::SeqCst);
+}
+
+struct FuzzGlobalAlloc;
+
+unsafe impl std::alloc::GlobalAlloc for FuzzGlobalAlloc {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        if FUZZ_ALLOC_ARMED.load(std::sync::atomic::Ordering::SeqCst) {
+            let count = FUZZ_ALLOC_COUNT.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let slot = &FUZZ_ALLOC_SCHEDULE[count % FUZZ_ALLOC_SCHEDULE.len()];
+            if slot.load(std::sync::atomic::Ordering::SeqCst) % 32 == 31 {
+                FUZZ_ALLOC_INJECTED.store(true, std::sync::atomic::Ordering::SeqCst);
+                return std::ptr::null_mut();
+            }
+        }
+        let Some(outer) = fuzz_alloc_outer(layout) else {
+            return std::ptr::null_mut();
+        };
+        let ptr = std::alloc::GlobalAlloc::alloc(&std::alloc::System, outer);
+        if ptr.is_null() { ptr } else { ptr.add(layout.align()) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        let outer = fuzz_alloc_outer(layout).unwrap();
+        std::alloc::GlobalAlloc::dealloc(&std::alloc::System, ptr.sub(layout.align()), outer);
+    }
+}
+
+#[global_allocator]
+static FUZZ_GLOBAL_ALLOC: FuzzGlobalAlloc = FuzzGlobalAlloc;
+"#;
+
+/// called by the alloc error hook, without the global allocator there is nothing to disarm
+static ARM_STUB: &'static str = "fn fuzz_alloc_arm(_armed: bool) {}\n";
+
+/// Fake def id of the allocator type. It is only known by `cache.paths`.
+pub(crate) fn alloc_did() -> DefId {
+    DefId { krate: LOCAL_CRATE, index: DefIndex::from_u32(DefIndex::MAX_AS_U32 - 2) }
+}
+
+/// make the allocator type nameable like other local types
+pub(crate) fn register_alloc_type(cache: &mut Cache) {
+    cache.paths.insert(alloc_did(), (vec![Symbol::intern(ALLOC_TYPE_NAME)], ItemType::Struct));
+}
+
+pub(crate) fn alloc_type() -> Type {
+    Type::Path {
+        path: simple_path(Res::Def(DefKind::Struct, alloc_did()), Symbol::intern(ALLOC_TYPE_NAME)),
+    }
+}
+
+/// `Allocator` is only in the cache when the crate mentions it
+pub(crate) fn allocator_trait(cache: &Cache) -> Option<Path> {
+    find_external_trait(ALLOCATOR_TRAIT, cache)
+}
+
+/// `FuzzAllocator::new(&[u8]) -> FuzzAllocator`, the bytes are its failure schedule
+pub(crate) fn constructor() -> ApiFunction {
+    ApiFunction {
+        name: "new".to_string(),
+        full_path: ALLOC_TYPE_NAME.to_string(),
+        inputs: vec![Type::BorrowedRef {
+            lifetime: None,
+            mutability: Mutability::Not,
+            type_: Box::new(Type::Slice(Box::new(Type::Primitive(PrimitiveType::U8)))),
+        }],
+        output: Some(alloc_type()),
+        self_: Some(alloc_type()),
+        trait_: None,
+        _unsafe_tag: ApiUnsafety::Normal,
+        trivial_safety: false,
+        panics_doc: None,
+        mono: false,
+        local: false,
+        rpg_local: false,
+    }
+}
+
+/// source of the allocators in driver, either of them may be absent
+pub(crate) fn definition(allocator: bool, global: bool) -> String {
+    let mut res = RUNTIME_DEFINITION.to_string();
+    res.push('\n');
+    if allocator {
+        res.push_str(ALLOCATOR_DEFINITION);
+        res.push('\n');
+    }
+    if global {
+        res.push_str(GLOBAL_DEFINITION);
+    } else {
+        res.push_str(ARM_STUB);
+    }
+    res.push('\n');
+    res
+}
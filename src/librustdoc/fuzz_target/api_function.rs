@@ -4,7 +4,8 @@
 use crate::fuzz_target::api_util::{is_unsupported_fuzzable,_type_name};
 use crate::fuzz_target::api_util::scan_type_with;
 use crate::fuzz_target::call_type::CallType;
-use crate::fuzz_target::fuzzable_type::{self, FuzzableType};
+use crate::fuzz_target::documented_panic::PanicsDoc;
+use crate::fuzz_target::fuzzable_type::{self, FuzzableType, InputLayout};
 use crate::fuzz_target::impl_util::FullNameMap;
 use rustc_data_structures::fx::FxHashSet;
 use rustc_hir::{self, Mutability};
@@ -26,6 +27,8 @@ pub(crate) struct ApiFunction {
     pub(crate) self_: Option<Type>,
     pub(crate) trait_: Option<Type>, //Trait的全限定路径,因为使用trait::fun来调用函数的时候，需要将trait的全路径引入
     pub(crate) _unsafe_tag: ApiUnsafety,
+    pub(crate) trivial_safety: bool, // unsafe, but the `# Safety` contract holds for any arguments
+    pub(crate) panics_doc: Option<PanicsDoc>, // the `# Panics` section of the doc
     pub(crate) local: bool,
     pub(crate) mono: bool,
     pub(crate) rpg_local: bool, // for comparison with RPG, only trait and struct is local the API will be viewed as local
@@ -197,9 +200,10 @@ pub(crate) fn contains_unsupported_fuzzable_type(
         &self,
         full_name_map: &FullNameMap,
         cache: &Cache,
+        layout: InputLayout,
     ) -> bool {
         for input_ty_ in &self.inputs {
-            if is_unsupported_fuzzable(input_ty_,full_name_map,cache){
+            if is_unsupported_fuzzable(input_ty_,full_name_map,cache,layout){
                 return true;
             }
         }
@@ -17,25 +17,39 @@
 use crate::fuzz_target::api_sequence::{ApiCall, ApiSequence, ParamType};
 use crate::fuzz_target::api_util;
 use crate::fuzz_target::api_util::{_type_name, get_type_name_from_did, replace_lifetime, _same_type};
+use crate::fuzz_target::api_util::{type_bucket, TypeBucket};
 use crate::fuzz_target::call_type::CallType;
+use crate::fuzz_target::alloc_type::{self, ALLOC_TYPE_NAME};
+use crate::fuzz_target::arbitrary_producer;
+use crate::fuzz_target::coverage_feedback::CoverageFeedback;
+use crate::fuzz_target::evil_type::{self, EVIL_TYPE_NAME};
 use crate::fuzz_target::fuzz_target_renderer::FuzzTargetContext;
 use crate::fuzz_target::fuzzable_type;
-use crate::fuzz_target::fuzzable_type::FuzzableType;
+use crate::fuzz_target::fuzzable_type::{FuzzableType, InputLayout};
+use crate::fuzz_target::unsafe_policy::UnsafePolicy;
+use crate::fuzz_target::cargo_fuzz::FuzzEngine;
+use crate::fuzz_target::differential;
 use crate::fuzz_target::generic_function;
 use crate::fuzz_target::generic_function::GenericFunction;
+use crate::fuzz_target::generic_hint::GenericHints;
 use crate::fuzz_target::generic_param_map::GenericParamMap;
 use crate::fuzz_target::generic_solution::take_type_from_path;
 use crate::fuzz_target::generic_solver;
 use crate::fuzz_target::generic_solver::GenericSolver;
+use crate::fuzz_target::helper_type::{self, HelperType, HELPER_TYPE_NAME};
 use crate::fuzz_target::impl_util::FullNameMap;
 use crate::fuzz_target::mod_visibility::ModVisibity;
+use crate::fuzz_target::named_value::NamedValue;
 use crate::fuzz_target::prelude_type;
+use crate::fuzz_target::seed_corpus::CrateLiterals;
 use crate::fuzz_target::statistic;
 use crate::fuzz_target::trait_impl::{TraitImpl, TypeTraitCache};
+use crate::fuzz_target::unsolvable_report::{UnsolvableEntry, UnsolvableReason, UnsolvableReport};
 use crate::html::format::join_with_double_colon;
 use crate::TyCtxt;
 use lazy_static::lazy_static;
-use rand::{self, Rng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::ty::Visibility;
 use rustc_hir::def_id::DefId;
@@ -236,7 +250,8 @@ pub(crate) fn is_callable(
 
     pub(crate) fn update_sorted_type(&mut self) {
         let mut vec = self.type_candidates.iter().collect::<Vec<_>>();
-        vec.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        // ties are ordered by name, not by the iteration order of the map
+        vec.sort_by_cached_key(|a| (std::cmp::Reverse(*a.1), _type_name(a.0, None)));
         self.type_sorted = vec.into_iter().map(|a| a.0.clone()).collect();
     }
 }
@@ -250,6 +265,7 @@ pub(crate) struct ApiGraph<'tcx> {
     pub(crate) reserve: Vec<bool>,
     pub(crate) api_functions_visited: Vec<bool>,
     pub(crate) api_dependencies: Vec<ApiDependency>,
+    pub(crate) dependency_index: FxHashMap<DependencyKey, usize>, // index of api_dependencies
     pub(crate) api_sequences: Vec<ApiSequence>,
     pub(crate) trait_impl_map: TraitImplMap, // type defid => {(trait path, generics, impl id)}
     pub(crate) full_name_map: FullNameMap,   //did to full_name
@@ -257,6 +273,30 @@ pub(crate) struct ApiGraph<'tcx> {
     pub(crate) generic_functions: Vec<GenericFunction>,
     pub(crate) functions_with_unsupported_fuzzable_types: FxHashSet<String>,
     pub(crate) type_context: Rc<RefCell<TypeContext>>,
+    pub(crate) unsolvable_report: UnsolvableReport, // generic functions without reserved mono function
+    pub(crate) generic_hints: GenericHints,         // user supplied instantiation of generic params
+    pub(crate) helper_type: HelperType,             // driver-local type for bounds without candidate
+    pub(crate) evil_type: bool,                     // instantiate generic params with misbehaving impls
+    pub(crate) alloc_type: bool,                    // instantiate `A: Allocator` with a failing allocator
+    pub(crate) global_allocator: bool,              // drivers install a failing global allocator
+    pub(crate) strategy: Option<GraphTraverseAlgorithm>, // None: bfs end point + backward search
+    pub(crate) seed: u64,                                // seed of every random choice
+    pub(crate) coverage_feedback: CoverageFeedback,      // coverage of the former fuzzing round
+    pub(crate) minimize: Option<String>,                 // driver to write a crash minimizer for
+    pub(crate) runtime_crate: bool,                      // drivers decode with the runtime crate
+    pub(crate) input_layout: InputLayout,                // how drivers lay out the fuzz input
+    pub(crate) op_loop: bool,                            // also write op loop drivers
+    pub(crate) unsafe_policy: UnsafePolicy,              // how unsafe apis enter sequences
+    pub(crate) mono_variants: Vec<Vec<usize>>,           // comparable mono functions of one generic function
+    pub(crate) differential: bool,                       // also write differential drivers
+    pub(crate) trait_laws: bool,                         // also write trait law check drivers
+    pub(crate) round_trip: bool,                         // also write round trip drivers
+    pub(crate) expect_documented_panics: bool,           // documented panics are no findings
+    pub(crate) engine: FuzzEngine,                       // fuzzer the drivers are written for
+    pub(crate) features: Vec<String>,                    // crate features rustdoc saw enabled
+    pub(crate) multiplex: bool,                          // write all sequences into one driver
+    pub(crate) regression: Option<String>,               // driver to write a test converter for
+    pub(crate) literals: CrateLiterals,                  // literals of the crate tried by seeds
     pub(crate) cx: Rc<FuzzTargetContext<'tcx>>, 
 }
 
@@ -272,6 +312,30 @@ pub(crate) enum GraphTraverseAlgorithm {
     _DirectBackwardSearch,
 }
 
+/// the algorithms selectable by `--fuzz-strategy`
+impl NamedValue for GraphTraverseAlgorithm {
+    const NAMES: &'static [(&'static str, GraphTraverseAlgorithm)] = &[
+        ("bfs", GraphTraverseAlgorithm::_Bfs),
+        ("fast-bfs", GraphTraverseAlgorithm::_FastBfs),
+        ("bfs-end-point", GraphTraverseAlgorithm::_BfsEndPoint),
+        ("fast-bfs-end-point", GraphTraverseAlgorithm::_FastBfsEndPoint),
+        ("random-walk", GraphTraverseAlgorithm::_RandomWalk),
+        ("random-walk-end-point", GraphTraverseAlgorithm::_RandomWalkEndPoint),
+        ("try-deep-bfs", GraphTraverseAlgorithm::_TryDeepBfs),
+        ("backward-search", GraphTraverseAlgorithm::_DirectBackwardSearch),
+    ];
+}
+
+impl GraphTraverseAlgorithm {
+    /// random walks only produce a pool of sequences, drivers are picked from it in order
+    pub(crate) fn is_random(&self) -> bool {
+        matches!(
+            self,
+            GraphTraverseAlgorithm::_RandomWalk | GraphTraverseAlgorithm::_RandomWalkEndPoint
+        )
+    }
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Copy)]
 pub(crate) enum ApiType {
     BareFunction,
@@ -279,6 +343,8 @@ pub(crate) enum ApiType {
 }
 
 type Solution = FxHashMap<String, Vec<Type>>; // TODO: Type or DefId?
+/// output function, input function, input param index
+type DependencyKey = ((ApiType, usize), (ApiType, usize), usize);
 //函数的依赖关系
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub(crate) struct ApiDependency {
@@ -294,6 +360,7 @@ pub(crate) fn new(_crate_name: String, cx: Rc<FuzzTargetContext<'tcx>>) -> Self
             api_functions: Vec::new(),
             api_functions_visited: Vec::new(),
             api_dependencies: Vec::new(),
+            dependency_index: FxHashMap::default(),
             api_sequences: Vec::new(),
             reachable: Vec::new(),
             reachable_input: Vec::new(),
@@ -305,6 +372,30 @@ pub(crate) fn new(_crate_name: String, cx: Rc<FuzzTargetContext<'tcx>>) -> Self
             mod_visibility: ModVisibity::new(&_crate_name),
             generic_functions: Vec::new(),
             functions_with_unsupported_fuzzable_types: FxHashSet::default(),
+            unsolvable_report: UnsolvableReport::new(),
+            generic_hints: GenericHints::new(),
+            helper_type: HelperType::new(),
+            evil_type: false,
+            alloc_type: false,
+            global_allocator: false,
+            strategy: None,
+            seed: 0,
+            coverage_feedback: CoverageFeedback::new(),
+            minimize: None,
+            runtime_crate: false,
+            input_layout: InputLayout::Split,
+            op_loop: false,
+            unsafe_policy: UnsafePolicy::Include,
+            mono_variants: Vec::new(),
+            differential: false,
+            trait_laws: false,
+            round_trip: false,
+            expect_documented_panics: false,
+            engine: FuzzEngine::Afl,
+            features: Vec::new(),
+            multiplex: false,
+            regression: None,
+            literals: CrateLiterals::default(),
             _crate_name,
             cx,
         }
@@ -639,7 +730,7 @@ pub fn search_reachable_solutions(&mut self, solvers: &mut Vec<GenericSolver>, t
             }
 
             num_iter += 1;
-            if !update {
+            if !update && !self.add_helper_type(solvers) {
                 break;
             }
         }
@@ -660,6 +751,115 @@ pub fn search_reachable_solutions(&mut self, solvers: &mut Vec<GenericSolver>, t
     }
 
 
+    /// Generic functions that are still unsolved may lack a type implementing their bounds.
+    /// Register the helper type for every param whose bounds it can implement, and add its
+    /// constructor so that it becomes a type candidate. Return true if the helper is added.
+    pub(crate) fn add_helper_type(&mut self, solvers: &Vec<GenericSolver>) -> bool {
+        if self.helper_type.is_enabled() {
+            return false;
+        }
+        let cx = Rc::clone(&self.cx);
+        let mut traits = Vec::<Path>::new();
+        for (i, solver) in solvers.iter().enumerate() {
+            if solver.num_solution() > 0 || !solver.is_solvable() {
+                continue;
+            }
+            let generic_map = &self.generic_functions[i].generic_map;
+            let mut param_bounds: Vec<&Vec<Path>> =
+                generic_map.generic_defs.iter().map(|param| generic_map.get_bounds(param)).collect();
+            for (type_, bounds) in generic_map.type_pred.iter() {
+                if let Type::Generic(sym) = type_ {
+                    if generic_map.generic_defs.iter().any(|param| param == sym.as_str()) {
+                        param_bounds.push(bounds);
+                    }
+                }
+            }
+            for bounds in param_bounds {
+                if !bounds.iter().all(|bound| self.helper_type.support(bound, &cx.cache, cx.tcx)) {
+                    continue;
+                }
+                for bound in bounds.iter() {
+                    if !self.helper_type.is_marker(bound, &cx.cache)
+                        && !traits.iter().any(|trait_| trait_.def_id() == bound.def_id())
+                    {
+                        traits.push(bound.clone());
+                    }
+                }
+            }
+        }
+        if traits.is_empty() {
+            return false;
+        }
+
+        let helper_did = helper_type::helper_did();
+        for trait_ in traits {
+            println!(
+                "[Helper] impl {} for {}",
+                api_util::print_path(&trait_, Some(&cx.cache)),
+                HELPER_TYPE_NAME
+            );
+            self.trait_impl_map.add_type_trait_impl(
+                helper_did,
+                TraitImpl::new(
+                    trait_.clone(),
+                    helper_type::helper_type(),
+                    None,
+                    GenericParamMap::new(),
+                    helper_did,
+                    false,
+                ),
+            );
+            self.helper_type.traits.push(trait_);
+        }
+        self.helper_type.enable();
+        let constructor = self.helper_type.constructor();
+        self.add_api_function(constructor);
+        true
+    }
+
+    /// Make the evil type a candidate of every param bounded by the traits it implements.
+    pub(crate) fn add_evil_type(&mut self) {
+        let evil_did = evil_type::evil_did();
+        for (trait_, assoc_items) in evil_type::evil_traits(self.cache()) {
+            println!(
+                "[Evil] impl {} for {}",
+                api_util::print_path(&trait_, Some(self.cache())),
+                EVIL_TYPE_NAME
+            );
+            let mut trait_impl = TraitImpl::new(
+                trait_,
+                evil_type::evil_type(),
+                None,
+                GenericParamMap::new(),
+                evil_did,
+                false,
+            );
+            trait_impl.assoc_items = assoc_items;
+            self.trait_impl_map.add_type_trait_impl(evil_did, trait_impl);
+        }
+        self.add_api_function(evil_type::constructor());
+    }
+
+    /// Make the fuzz-controlled allocator a candidate of `A: Allocator` params besides `Global`.
+    pub(crate) fn add_alloc_type(&mut self) {
+        let Some(trait_) = alloc_type::allocator_trait(self.cache()) else {
+            println!("[Alloc] Allocator is never used");
+            return;
+        };
+        let alloc_did = alloc_type::alloc_did();
+        println!("[Alloc] impl Allocator for {}", ALLOC_TYPE_NAME);
+        let trait_impl = TraitImpl::new(
+            trait_,
+            alloc_type::alloc_type(),
+            None,
+            GenericParamMap::new(),
+            alloc_did,
+            false,
+        );
+        self.trait_impl_map.add_type_trait_impl(alloc_did, trait_impl);
+        self.add_api_function(alloc_type::constructor());
+    }
+
     pub fn prune_by_similarity(&mut self, solvers: &mut Vec<GenericSolver>) {
         let mut diverse_types = FxHashMap::<Type, bool>::default();
 
@@ -692,6 +892,18 @@ pub(crate) fn resolve_generic_functions(&mut self) {
         let num_function = self.generic_functions.len();
         let mut type_trait_cache = TypeTraitCache::new();
         self.trait_impl_map.init_concrete();
+        self.trait_impl_map.init_marker_traits(&self.cx.cache);
+        if self.evil_type {
+            self.add_evil_type();
+        }
+        if self.alloc_type {
+            self.add_alloc_type();
+        }
+        // the crate's own generators build the types they cover from bytes
+        for producer in arbitrary_producer::producers(self) {
+            println!("[Arbitrary] {}", producer.full_path);
+            self.add_api_function(producer);
+        }
 
         // init solvers and do statistic
         for function in &self.api_functions {
@@ -717,8 +929,10 @@ pub(crate) fn resolve_generic_functions(&mut self) {
         for function in self.generic_functions.iter() {
             println!("[ApiGraph] Resolve this function");
             function.pretty_print(&self.cx.cache);
-            solvers.push(GenericSolver::new(Rc::clone(&self.type_context), function.clone()));
+            let hint = self.generic_hints.seed_for(function, &self.cx.cache);
+            solvers.push(GenericSolver::new(Rc::clone(&self.type_context), function.clone(), hint));
         }
+        self.generic_hints.print_unused();
 
         // 1. find all reachable API
         self.search_reachable_solutions(&mut solvers, &mut type_trait_cache);
@@ -745,6 +959,35 @@ pub(crate) fn resolve_generic_functions(&mut self) {
                 }
                 self.generic_functions[i].pretty_print(&self.cx.cache);
             }
+            let function = &self.generic_functions[i];
+            let entry = UnsolvableEntry {
+                function: function.api_function.full_name(&self.cx.cache),
+                signature: function.get_full_signature(&self.cx.cache),
+                local: function.api_function.is_local(),
+                reasons: solvers[i].reasons().clone(),
+            };
+            // a rejected hint is reported even if the function is solved without it
+            let rejected_hints = entry
+                .reasons
+                .iter()
+                .filter(|reason| matches!(reason, UnsolvableReason::RejectedHint { .. }))
+                .cloned()
+                .collect::<Vec<_>>();
+            if !rejected_hints.is_empty() {
+                let hints_entry = UnsolvableEntry { reasons: rejected_hints, ..entry.clone() };
+                self.unsolvable_report.add_rejected_hints(hints_entry);
+            }
+            if solvers[i].reserve_solutions().is_empty() {
+                self.unsolvable_report.add_entry(entry);
+            } else if let Some(cap) = entry
+                .reasons
+                .iter()
+                .find(|reason| matches!(reason, UnsolvableReason::MonoCap { .. }))
+            {
+                // a solved function can only be incomplete, the cap is all there is to report
+                let reasons = vec![cap.clone()];
+                self.unsolvable_report.add_capped(UnsolvableEntry { reasons, ..entry });
+            }
         }
 
         println!("all mono function:");
@@ -753,6 +996,7 @@ pub(crate) fn resolve_generic_functions(&mut self) {
             if self.generic_functions[i].api_function.is_local() {
                 statistic::add("MONO_FUNS", solvers[i].num_solution());
             }
+            let mut variants = Vec::new();
             for (api_fun, is_reserved, impl_set) in solvers[i].take_solutions() {
                 print!("",);
                 count += 1;
@@ -768,15 +1012,30 @@ pub(crate) fn resolve_generic_functions(&mut self) {
                     if self.generic_functions[i].api_function.is_local() {
                         statistic::inc("RESERVE");
                     }
+                    let index = self.api_functions.len();
                     self.add_api_function(api_fun);
+                    if self.api_functions.len() > index {
+                        variants.push(index);
+                    }
                 }
             }
+            if variants.len() > 1
+                && differential::is_comparable_output(
+                    self.generic_functions[i].api_function.output.as_ref(),
+                    self.cache(),
+                )
+            {
+                self.mono_variants.push(variants);
+            }
         }
     }
 
     pub(crate) fn add_api_function(&mut self, mut api_fun: ApiFunction) {
-        if api_fun.contains_unsupported_fuzzable_type(&self.full_name_map, self.cache())
-            || !api_fun.is_unsupported()
+        if api_fun.contains_unsupported_fuzzable_type(
+            &self.full_name_map,
+            self.cache(),
+            self.input_layout,
+        ) || !api_fun.is_unsupported()
         {
             println!("{} contain unsupported fuzzable type", api_fun._pretty_print(self.cache()));
             // self.functions_with_unsupported_fuzzable_types.insert(api_fun.full_name.clone());
@@ -921,9 +1180,25 @@ pub(crate) fn set_full_name_map(&mut self, full_name_map: &FullNameMap) {
     pub(crate) fn find_all_dependencies(&mut self) {
         //println!("find_dependencies");
         self.api_dependencies.clear();
+        self.dependency_index.clear();
         //两个api_function之间的dependency
         let api_num = self.api_functions.len();
 
+        // only outputs and inputs in the same bucket can be compatible
+        let mut consumers: FxHashMap<TypeBucket, Vec<(usize, usize)>> = FxHashMap::default();
+        for j in 0..api_num {
+            //TODO:是否要把i=j的情况去掉？
+            let second_fun = &self.api_functions[j];
+            if second_fun._is_start_function(&self.full_name_map, self.cache()) {
+                //如果第二个节点是开始节点，那么直接跳过
+                continue;
+            }
+            for (k, input_param) in second_fun.inputs.iter().enumerate() {
+                let bucket = type_bucket(input_param, &self.full_name_map, self.cache());
+                consumers.entry(bucket).or_default().push((j, k));
+            }
+        }
+
         for i in 0..api_num {
             let first_fun = &self.api_functions[i];
             if first_fun._is_end_function(&self.full_name_map, self.cache()) {
@@ -932,45 +1207,63 @@ pub(crate) fn find_all_dependencies(&mut self) {
             }
             if let Some(ty_) = &first_fun.output {
                 let output_type = ty_;
-                for j in 0..api_num {
-                    //TODO:是否要把i=j的情况去掉？
-                    let second_fun = &self.api_functions[j];
-                    if second_fun._is_start_function(&self.full_name_map, self.cache()) {
-                        //如果第二个节点是开始节点，那么直接跳过
-                        continue;
-                    }
-                    let input_params = &second_fun.inputs;
-                    let input_params_num = input_params.len();
-
-                    for k in 0..input_params_num {
-                        let input_param = &input_params[k];
-
-                        let call_type = api_util::_same_type(
-                            output_type,
-                            input_param,
-                            true,
-                            &self.full_name_map,
-                            self.cache(),
-                        );
-                        match &call_type {
-                            CallType::_NotCompatible => {
-                                continue;
-                            }
-                            _ => {
-                                let one_dependency = ApiDependency {
-                                    output_fun: (ApiType::BareFunction, i),
-                                    input_fun: (ApiType::BareFunction, j),
-                                    input_param_index: k,
-                                    call_type: call_type.clone(),
-                                };
-
-                                self.api_dependencies.push(one_dependency);
-                            }
+                let bucket = type_bucket(output_type, &self.full_name_map, self.cache());
+                let Some(candidates) = consumers.get(&bucket) else {
+                    continue;
+                };
+                for &(j, k) in candidates {
+                    let input_param = &self.api_functions[j].inputs[k];
+                    let call_type = api_util::_same_type(
+                        output_type,
+                        input_param,
+                        true,
+                        &self.full_name_map,
+                        self.cache(),
+                    );
+                    match &call_type {
+                        CallType::_NotCompatible => {
+                            continue;
+                        }
+                        _ => {
+                            let one_dependency = ApiDependency {
+                                output_fun: (ApiType::BareFunction, i),
+                                input_fun: (ApiType::BareFunction, j),
+                                input_param_index: k,
+                                call_type: call_type.clone(),
+                            };
+
+                            self.dependency_index.insert(
+                                (one_dependency.output_fun, one_dependency.input_fun, k),
+                                self.api_dependencies.len(),
+                            );
+                            self.api_dependencies.push(one_dependency);
                         }
                     }
                 }
             }
         }
+        println!(
+            "[Dependency] {} dependencies in {} type buckets",
+            self.api_dependencies.len(),
+            consumers.len()
+        );
+    }
+
+    /// run the traversal selected on command line
+    pub(crate) fn generate_sequences(&mut self) {
+        match self.strategy {
+            Some(algorithm) => self.generate_all_possoble_sequences(algorithm),
+            None => self.default_generate_sequences(),
+        }
+    }
+
+    pub(crate) fn is_random_strategy(&self) -> bool {
+        self.strategy.map_or(false, |algorithm| algorithm.is_random())
+    }
+
+    /// every random choice restarts from the seed so that output only depends on the input
+    pub(crate) fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
     }
 
     pub(crate) fn default_generate_sequences(&mut self) {
@@ -1221,7 +1514,7 @@ pub(crate) fn random_walk(
 
         //start random work
         let function_len = self.api_functions.len();
-        let mut rng = rand::thread_rng();
+        let mut rng = self.rng();
         for i in 0..max_size {
             let current_sequence_len = self.api_sequences.len();
             let chosen_sequence_index = rng.gen_range(0..current_sequence_len);
@@ -1442,7 +1735,7 @@ pub(crate) fn _naive_choose_sequence(&self, max_sequence_size: usize) -> Vec<Api
             }
         }
 
-        let mut rng = rand::thread_rng();
+        let mut rng = self.rng();
         for _ in 0..max_sequence_size {
             if to_cover_nodes.len() == 0 {
                 println!("all {} nodes need to be covered is covered", to_cover_nodes_number);
@@ -1484,7 +1777,7 @@ pub(crate) fn _random_choose(&self, max_size: usize) -> Vec<ApiSequence> {
             sequence_indexes.push(i);
         }
 
-        let mut rng = rand::thread_rng();
+        let mut rng = self.rng();
         for _ in 0..max_size {
             let rest_sequences_number = sequence_indexes.len();
             if rest_sequences_number <= 0 {
@@ -1552,6 +1845,15 @@ pub(crate) fn _first_choose(&self, max_size: usize) -> Vec<ApiSequence> {
         res
     }
 
+    /// full names of the called apis, in call order
+    pub(crate) fn sequence_api_names(&self, sequence: &ApiSequence) -> Vec<String> {
+        sequence
+            .functions
+            .iter()
+            .map(|api_call| self.api_functions[api_call.func.1].full_name(self.cache()))
+            .collect()
+    }
+
     pub(crate) fn _heuristic_choose(
         &self,
         max_size: usize,
@@ -1601,6 +1903,28 @@ pub(crate) fn _heuristic_choose(
             return res;
         }
 
+        // feedback of the former fuzzing round
+        let uncovered_apis = self
+            .api_functions
+            .iter()
+            .map(|api_function| {
+                self.coverage_feedback.is_uncovered(&api_function.full_name(self.cache()))
+            })
+            .collect::<Vec<_>>();
+        let mut plateaued_sequences = FxHashSet::default();
+        if !self.coverage_feedback.is_empty() {
+            for (j, api_sequence) in self.api_sequences.iter().enumerate() {
+                if self.coverage_feedback.is_plateaued(&self.sequence_api_names(api_sequence)) {
+                    plateaued_sequences.insert(j);
+                }
+            }
+            println!(
+                "{} apis stayed uncovered, {} sequences plateaued",
+                uncovered_apis.iter().filter(|uncovered| **uncovered).count(),
+                plateaued_sequences.len()
+            );
+        }
+
         //let mut already_covered_monos = FxHashSet::default();
         let mut already_covered_nodes = FxHashSet::default();
         let mut already_covered_edges = FxHashSet::default();
@@ -1612,6 +1936,7 @@ pub(crate) fn _heuristic_choose(
         let mut try_to_find_dynamic_length_flag = true;
         for _ in 0..max_size + 1 {
             let mut current_chosen_sequence_index = 0;
+            let mut current_max_uncovered_apis = 0;
             let mut current_max_covered_nodes = 0;
             let mut current_max_covered_edges = 0;
             let mut current_chosen_sequence_len = 0;
@@ -1637,6 +1962,11 @@ pub(crate) fn _heuristic_choose(
                     continue;
                 }
 
+                if plateaued_sequences.contains(&j) {
+                    // the same driver already saturated in the former round
+                    continue;
+                }
+
                 if try_to_find_dynamic_length_flag && api_sequence._is_fuzzables_fixed_length() {
                     //优先寻找fuzzable部分具有动态长度的情况
                     // println!("try_to_find_dynamic_length_flag1, {}",j);
@@ -1658,7 +1988,19 @@ pub(crate) fn _heuristic_choose(
                     }
                 }
 
-                if uncovered_nodes_by_former_sequence_count < current_max_covered_nodes {
+                // apis whose bodies stayed uncovered in the former round come first
+                let uncovered_apis_count = covered_nodes
+                    .iter()
+                    .filter(|node| uncovered_apis[**node] && !already_covered_nodes.contains(*node))
+                    .count();
+                if uncovered_apis_count < current_max_uncovered_apis {
+                    continue;
+                }
+                let more_uncovered_apis = uncovered_apis_count > current_max_uncovered_apis;
+
+                if !more_uncovered_apis
+                    && uncovered_nodes_by_former_sequence_count < current_max_covered_nodes
+                {
                     // println!("smaller than max1, {}", j);
                     continue;
                 }
@@ -1670,14 +2012,16 @@ pub(crate) fn _heuristic_choose(
                             uncovered_edges_by_former_sequence_count + 1;
                     }
                 }
-                if uncovered_nodes_by_former_sequence_count == current_max_covered_nodes
+                if !more_uncovered_apis
+                    && uncovered_nodes_by_former_sequence_count == current_max_covered_nodes
                     && uncovered_edges_by_former_sequence_count < current_max_covered_edges
                 {
                     // println!("smaller than max2, {}",j);
                     continue;
                 }
                 let sequence_len = api_sequence.len();
-                if (uncovered_nodes_by_former_sequence_count > current_max_covered_nodes)
+                if more_uncovered_apis
+                    || (uncovered_nodes_by_former_sequence_count > current_max_covered_nodes)
                     || (uncovered_nodes_by_former_sequence_count == current_max_covered_nodes
                         && uncovered_edges_by_former_sequence_count > current_max_covered_edges)
                     || (uncovered_nodes_by_former_sequence_count == current_max_covered_nodes
@@ -1685,6 +2029,7 @@ pub(crate) fn _heuristic_choose(
                         && sequence_len < current_chosen_sequence_len)
                 {
                     current_chosen_sequence_index = j;
+                    current_max_uncovered_apis = uncovered_apis_count;
                     current_max_covered_nodes = uncovered_nodes_by_former_sequence_count;
                     current_max_covered_edges = uncovered_edges_by_former_sequence_count;
                     current_chosen_sequence_len = sequence_len;
@@ -1755,8 +2100,11 @@ pub(crate) fn _heuristic_choose(
 
         let mut valid_api_number = 0;
         for api_function_ in &self.api_functions {
-            if !api_function_.contains_unsupported_fuzzable_type(&self.full_name_map, self.cache())
-            {
+            if !api_function_.contains_unsupported_fuzzable_type(
+                &self.full_name_map,
+                self.cache(),
+                self.input_layout,
+            ) {
                 valid_api_number = valid_api_number + 1;
             }
         }
@@ -1844,6 +2192,9 @@ pub(crate) fn is_fun_satisfied(
                 let is_mono = input_function.is_mono();
                 //如果是个unsafe函数，给sequence添加unsafe标记
                 if input_function._unsafe_tag._is_unsafe() {
+                    if !self.unsafe_policy.admits_function(input_function) {
+                        return None;
+                    }
                     new_sequence.set_unsafe();
                 }
                 if let Some(ref trait_) = input_function.trait_ {
@@ -1918,6 +2269,12 @@ pub(crate) fn is_fun_satisfied(
                             self.check_dependency(api_type, *index, input_type, input_fun_index, i)
                         {
                             let dependency_ = self.api_dependencies[dependency_index].clone();
+                            //unsafe policy可能不允许解引用裸指针
+                            if dependency_.call_type.unsafe_call_type()._is_unsafe()
+                                && !self.unsafe_policy.admits_unsafe_deref()
+                            {
+                                continue;
+                            }
                             //将覆盖到的边加入到新的sequence中去
                             new_sequence.add_dependency(dependency_index);
                             //找到了依赖，当前参数是可以被满足的，设置flag并退出循环
@@ -1986,7 +2343,7 @@ pub(crate) fn is_fun_satisfied(
                 for move_index in _moved_indexes {
                     new_sequence.insert_move_index(move_index);
                 }
-                if new_sequence._contains_multi_dynamic_length_fuzzable() {
+                if !new_sequence._is_decodable(self.input_layout) {
                     //如果新生成的序列包含多维可变的参数，就不把这个序列加进去
                     // println!("func {} fail by Dynamic", input_fun_index);
                     return None;
@@ -2005,23 +2362,9 @@ pub(crate) fn check_dependency(
         input_index: usize,
         input_param_index_: usize,
     ) -> Option<usize> {
-        let dependency_num = self.api_dependencies.len();
-        for index in 0..dependency_num {
-            let dependency = &self.api_dependencies[index];
-            //TODO:直接比较每一项内容是否可以节省点时间？
-            let tmp_dependency = ApiDependency {
-                output_fun: (*output_type, output_index),
-                input_fun: (*input_type, input_index),
-                input_param_index: input_param_index_,
-                call_type: dependency.call_type.clone(),
-            };
-            if tmp_dependency == *dependency {
-                //存在依赖
-                return Some(index);
-            }
-        }
-        //没找到依赖
-        return None;
+        self.dependency_index
+            .get(&((*output_type, output_index), (*input_type, input_index), input_param_index_))
+            .copied()
     }
 
     //判断一个调用序列是否已经到达终止端点
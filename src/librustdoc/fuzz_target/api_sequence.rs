@@ -2,14 +2,23 @@
 use crate::clean::Type;
 use crate::formats::cache::Cache;
 use crate::fuzz_target::afl_util::{self, _AflHelpers};
+use crate::fuzz_target::alloc_type;
+use crate::fuzz_target::arbitrary_producer;
 use crate::fuzz_target::api_graph::{ApiGraph, ApiType};
 use crate::fuzz_target::api_util;
 use crate::fuzz_target::api_util::_type_name;
 use crate::fuzz_target::api_util::get_type_name_from_did;
 use crate::fuzz_target::call_type::CallType;
-use crate::fuzz_target::fuzzable_type::FuzzableType;
+use crate::fuzz_target::documented_panic;
+use crate::fuzz_target::evil_type;
+use crate::fuzz_target::helper_type;
+use crate::fuzz_target::fuzzable_type::{FuzzableType, InputLayout};
+use crate::fuzz_target::op_loop;
 use crate::fuzz_target::prelude_type;
 use crate::fuzz_target::replay_util;
+use crate::fuzz_target::round_trip::{self, RoundTripCheck};
+use crate::fuzz_target::runtime_crate;
+use crate::fuzz_target::trait_law::{self, LawCheck};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_span::def_id::DefId;
 
@@ -65,6 +74,10 @@ pub(crate) struct ApiSequence {
     pub(crate) _function_mut_tag: FxHashSet<usize>, //表示哪些function的返回值需要带上mut标记
     pub(crate) _covered_dependencies: FxHashSet<usize>, //表示用到了哪些dependency,即边覆盖率
     pub(crate) mono: bool,                          // have any mono function in sequence
+    pub(crate) op_loop: Option<usize>, // calls building the receiver, the rest run in a loop
+    pub(crate) observed: bool,         // the result of the last call is recorded for comparison
+    pub(crate) law_check: Option<LawCheck>, // trait laws checked on the results of three calls
+    pub(crate) round_trip: Option<RoundTripCheck>, // a result written and read back
                                                     // pub(crate) dependencies: FxHashSet<String>,  // third party dependencies
 }
 
@@ -89,6 +102,10 @@ pub(crate) fn new() -> Self {
             _function_mut_tag,
             _covered_dependencies,
             mono: false,
+            op_loop: None,
+            observed: false,
+            law_check: None,
+            round_trip: None,
             // return_mark
         }
     }
@@ -253,13 +270,9 @@ pub(crate) fn _fuzzables_min_length(&self) -> usize {
         total_length
     }
 
-    pub(crate) fn _contains_multi_dynamic_length_fuzzable(&self) -> bool {
-        for fuzzable_param in &self.fuzzable_params {
-            if fuzzable_param._is_multiple_dynamic_length() {
-                return true;
-            }
-        }
-        false
+    /// whether a driver can decode every fuzzable param from an input of the layout
+    pub(crate) fn _is_decodable(&self, layout: InputLayout) -> bool {
+        self.fuzzable_params.iter().all(|fuzzable_param| fuzzable_param._is_decodable(layout))
     }
 
     pub(crate) fn _fuzzable_fixed_part_length(&self) -> usize {
@@ -350,16 +363,16 @@ pub(crate) fn _to_replay_crash_file(
         test_index: usize,
     ) -> String {
         let mut res = self.to_afl_except_main(_api_graph, test_index);
-        res = res.replace("#[macro_use]\nuse afl;\n", "");
+        res = res.replace(_api_graph.engine.header(), "");
         res.push_str(replay_util::_read_crash_file_data());
         res.push('\n');
-        res.push_str(self.reproduce_main_function(test_index).as_str());
+        res.push_str(self.reproduce_main_function(_api_graph, test_index).as_str());
         res
     }
 
     pub(crate) fn to_afl_test_file(&self, _api_graph: &ApiGraph<'_>, test_index: usize) -> String {
         let mut res = self.to_afl_except_main(_api_graph, test_index);
-        res.push_str(self.afl_main_function(test_index).as_str());
+        res.push_str(self.afl_main_function(_api_graph, test_index).as_str());
         res
     }
 
@@ -369,18 +382,18 @@ pub(crate) fn to_libfuzzer_test_file(
         test_index: usize,
     ) -> String {
         let mut res = self.to_afl_except_main(_api_graph, test_index);
-        res = res.replace(
-            "#[macro_use]\nextern crate afl;\n",
-            format!("#![no_main]\n#[macro_use]\nextern crate libfuzzer_sys;\n").as_str(),
-        );
-        res.push_str(self.libfuzzer_fuzz_main(test_index).as_str());
+        res.push_str(self.libfuzzer_fuzz_main(_api_graph, test_index).as_str());
         res
     }
 
-    pub(crate) fn libfuzzer_fuzz_main(&self, test_index: usize) -> String {
+    pub(crate) fn libfuzzer_fuzz_main(
+        &self,
+        _api_graph: &ApiGraph<'_>,
+        test_index: usize,
+    ) -> String {
         let mut res = String::new();
         res.push_str("fuzz_target!(|data: &[u8]| {\n");
-        res.push_str(self.afl_closure_body(0, test_index).as_str());
+        res.push_str(self.afl_closure_body(_api_graph, 0, test_index).as_str());
         res.push_str("});\n");
         res
     }
@@ -390,43 +403,143 @@ pub(crate) fn to_afl_except_main(
         _api_graph: &ApiGraph<'_>,
         test_index: usize,
     ) -> String {
+        let mut res = ApiSequence::driver_prelude(_api_graph, &[self]);
+        res.push_str(self.to_well_written_function(_api_graph, test_index, 0).as_str());
+        res.push('\n');
+        res
+    }
+
+    /// Everything before the test functions of a driver: feature gates, afl, helper functions and
+    /// driver-local types, merged over all sequences tested by the driver.
+    pub(crate) fn driver_prelude(_api_graph: &ApiGraph<'_>, sequences: &[&ApiSequence]) -> String {
         let mut res = String::new();
         //加入可能需要开启的feature gate
-        let feature_gates = afl_util::_get_feature_gates_of_sequence(&self.fuzzable_params);
-
-        if feature_gates.is_some() {
-            for feature_gate in &feature_gates.unwrap() {
-                let feature_gate_line = format!("{feature_gate}\n", feature_gate = feature_gate);
-                res.push_str(feature_gate_line.as_str());
-            }
+        let mut feature_gates = sequences
+            .iter()
+            .filter_map(|sequence| {
+                afl_util::_get_feature_gates_of_sequence(&sequence.fuzzable_params)
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        feature_gates.sort();
+        feature_gates.dedup();
+        // only needed by the helper functions pasted into the driver
+        if _api_graph.runtime_crate {
+            feature_gates.clear();
+        }
+        for feature_gate in &feature_gates {
+            let feature_gate_line = format!("{feature_gate}\n", feature_gate = feature_gate);
+            res.push_str(feature_gate_line.as_str());
         }
         // add std nightly feature 
         res.push_str("#![feature(int_log)]\n");
-        res.push_str("#![feature(allocator_api)]\n\n");
+        res.push_str("#![feature(allocator_api)]\n");
+        let uses_type = |did| sequences.iter().any(|sequence| sequence.uses_type(_api_graph, did));
+        let uses_alloc = uses_type(alloc_type::alloc_did());
+        if uses_alloc || _api_graph.global_allocator {
+            res.push_str("#![feature(alloc_error_hook)]\n");
+        }
+        res.push('\n');
 
-        // add afl macro
-        res.push_str("#[macro_use]\n");
-        res.push_str("extern crate afl;\n");
+        // add afl or libfuzzer macro
+        res.push_str(_api_graph.engine.header());
         // add dependency
 
         // add target library crate
         // res.push_str(format!("extern crate {};\n", _api_graph._crate_name).as_str());
 
-        let prelude_helper_functions = self.prelude_helper_functions();
-        if let Some(prelude_functions) = prelude_helper_functions {
-            res.push_str(prelude_functions.as_str());
+        if _api_graph.runtime_crate {
+            // helper functions come from the runtime crate instead
+            res.push_str(&format!("use {}::*;\n", runtime_crate::RUNTIME_CRATE_NAME));
+        } else {
+            let mut prelude_helpers = sequences
+                .iter()
+                .flat_map(|sequence| sequence.prelude_helpers())
+                .collect::<Vec<_>>();
+            prelude_helpers.sort();
+            prelude_helpers.dedup();
+            for prelude_helper in prelude_helpers {
+                res.push_str(prelude_helper);
+                res.push('\n');
+            }
+
+            let mut afl_helpers = sequences
+                .iter()
+                .filter_map(|sequence| {
+                    afl_util::_get_afl_helpers_functions_of_sequence(&sequence.fuzzable_params)
+                })
+                .flatten()
+                .collect::<Vec<_>>();
+            afl_helpers.sort();
+            afl_helpers.dedup();
+            for afl_helper in &afl_helpers {
+                res.push_str(format!("{}\n", afl_helper).as_str());
+            }
+        }
+
+        res.push_str(&ApiSequence::driver_definitions(_api_graph, sequences));
+        res
+    }
+
+    /// Driver-local types and the helper functions they come with, for every sequence that uses
+    /// them
+    pub(crate) fn driver_definitions(
+        _api_graph: &ApiGraph<'_>,
+        sequences: &[&ApiSequence],
+    ) -> String {
+        let mut res = String::new();
+        let uses_type = |did| sequences.iter().any(|sequence| sequence.uses_type(_api_graph, did));
+        let uses_alloc = uses_type(alloc_type::alloc_did());
+        // driver-local types that instantiate generic params
+        if uses_type(helper_type::helper_did()) {
+            res.push_str(&_api_graph.helper_type.definition());
+        }
+        if uses_type(evil_type::evil_did()) {
+            res.push_str(&evil_type::definition());
+        }
+        if sequences
+            .iter()
+            .any(|sequence| arbitrary_producer::uses_arbitrary(_api_graph, sequence))
+        {
+            res.push_str(&arbitrary_producer::definition());
         }
+        if uses_alloc || _api_graph.global_allocator {
+            res.push_str(&alloc_type::definition(uses_alloc, _api_graph.global_allocator));
+        }
+        if sequences
+            .iter()
+            .any(|sequence| documented_panic::expects_documented_panics(_api_graph, sequence))
+        {
+            res.push_str(&documented_panic::definition());
+        }
+        res
+    }
 
-        let afl_helper_functions = self.afl_helper_functions();
-        if let Some(afl_functions) = afl_helper_functions {
-            res.push_str(afl_functions.as_str());
+    /// names of the driver functions recognizing panics raised on purpose
+    pub(crate) fn expected_panics(&self, api_graph: &ApiGraph<'_>) -> Vec<&'static str> {
+        let mut res = Vec::new();
+        // first, it also forgets the documented call once the panic is caught
+        if documented_panic::expects_documented_panics(api_graph, self) {
+            res.push("fuzz_documented_expected");
+        }
+        if self.uses_type(api_graph, evil_type::evil_did()) {
+            res.push("fuzz_evil_expected");
+        }
+        if api_graph.global_allocator || self.uses_type(api_graph, alloc_type::alloc_did()) {
+            res.push("fuzz_alloc_expected");
         }
-        res.push_str(self.to_well_written_function(_api_graph, test_index, 0).as_str());
-        res.push('\n');
         res
     }
 
-    pub(crate) fn prelude_helper_functions(&self) -> Option<String> {
+    /// whether any called function mentions the type of did
+    pub(crate) fn uses_type(&self, _api_graph: &ApiGraph<'_>, did: DefId) -> bool {
+        self.functions.iter().any(|api_call| {
+            helper_type::mentions_type(&_api_graph.api_functions[api_call.func.1], did)
+        })
+    }
+
+    /// helper functions unwrapping the results of called apis
+    pub(crate) fn prelude_helpers(&self) -> Vec<&'static str> {
         let mut prelude_helpers = FxHashSet::default();
         for api_call in &self.functions {
             let params = &api_call.params;
@@ -437,46 +550,27 @@ pub(crate) fn prelude_helper_functions(&self) -> Option<String> {
                 }
             }
         }
-        if prelude_helpers.len() == 0 {
-            return None;
-        }
-        let mut res = String::new();
-        for helper in prelude_helpers {
-            res.push_str(helper._to_helper_function());
-            res.push('\n');
-        }
-        Some(res)
-    }
-
-    pub(crate) fn afl_helper_functions(&self) -> Option<String> {
-        let afl_helper_functions =
-            afl_util::_get_afl_helpers_functions_of_sequence(&self.fuzzable_params);
-        match afl_helper_functions {
-            None => None,
-            Some(afl_helpers) => {
-                let mut res = String::new();
-                for afl_helper in &afl_helpers {
-                    res.push_str(format!("{}\n", afl_helper).as_str());
-                }
-                Some(res)
-            }
-        }
+        prelude_helpers.iter().map(|helper| helper._to_helper_function()).collect()
     }
 
-    pub(crate) fn afl_main_function(&self, test_index: usize) -> String {
+    pub(crate) fn afl_main_function(&self, _api_graph: &ApiGraph<'_>, test_index: usize) -> String {
         let mut res = String::new();
         let indent = _generate_indent(4);
         res.push_str("fn main() {\n");
         res.push_str(indent.as_str());
         res.push_str("fuzz!(|data: &[u8]| {\n");
-        res.push_str(self.afl_closure_body(4, test_index).as_str());
+        res.push_str(self.afl_closure_body(_api_graph, 4, test_index).as_str());
         res.push_str(indent.as_str());
         res.push_str("});\n");
         res.push_str("}\n");
         res
     }
 
-    pub(crate) fn reproduce_main_function(&self, test_index: usize) -> String {
+    pub(crate) fn reproduce_main_function(
+        &self,
+        _api_graph: &ApiGraph<'_>,
+        test_index: usize,
+    ) -> String {
         format!(
             "fn main() {{
     let _content = _read_data();
@@ -485,27 +579,77 @@ pub(crate) fn reproduce_main_function(&self, test_index: usize) -> String {
     println!(\"data len = {{:?}}\", data.len());
 {}
 }}",
-            self.afl_closure_body(0, test_index)
+            self.afl_closure_body(_api_graph, 0, test_index)
         )
     }
 
-    pub(crate) fn afl_closure_body(&self, outer_indent: usize, test_index: usize) -> String {
+    pub(crate) fn afl_closure_body(
+        &self,
+        _api_graph: &ApiGraph<'_>,
+        outer_indent: usize,
+        test_index: usize,
+    ) -> String {
         let extra_indent = 4;
         let mut res = String::new();
         let indent = _generate_indent(outer_indent + extra_indent);
         res.push_str(format!("{indent}//actual body emit\n", indent = indent).as_str());
+        // the global allocator fails on a schedule picked by the whole input
+        if _api_graph.global_allocator {
+            res.push_str(format!("{indent}fuzz_alloc_schedule(data);\n", indent = indent).as_str());
+        }
+        // op loops read the input while running, in the sequential layout
+        if self.op_loop.is_some() {
+            res.push_str(
+                format!(
+                    "{indent}let mut _fdp = FuzzedDataProvider::sequential(data);\n{indent}test_function{test_index}(&mut _fdp);\n",
+                    indent = indent,
+                    test_index = test_index
+                )
+                .as_str(),
+            );
+            return res;
+        }
 
-        let op = if self._is_fuzzables_fixed_length() { "!=" } else { "<" };
-        let min_len = self._fuzzables_min_length();
-        res.push_str(
-            format!(
-                "{indent}if data.len() {op} {min_len} {{return;}}\n",
-                indent = indent,
-                op = op,
-                min_len = min_len
-            )
-            .as_str(),
-        );
+        res.push_str(&self.decode_statements(_api_graph, outer_indent + extra_indent));
+
+        let fuzzable_param_number = self.fuzzable_params.len();
+        let mut test_function_call =
+            format!("{indent}test_function{test_index}(", indent = indent, test_index = test_index);
+        for i in 0..fuzzable_param_number {
+            if i != 0 {
+                test_function_call.push_str(" ,");
+            }
+            test_function_call.push_str(format!("_param{}", i).as_str());
+        }
+        test_function_call.push_str(");\n");
+        res.push_str(test_function_call.as_str());
+
+        res
+    }
+
+    /// Statements binding `_param0`, `_param1`, ... to the fuzzable params decoded from `data`,
+    /// returning early from the enclosing function on inputs of the wrong length
+    pub(crate) fn decode_statements(
+        &self,
+        _api_graph: &ApiGraph<'_>,
+        indent_size: usize,
+    ) -> String {
+        let mut res = String::new();
+        let indent = _generate_indent(indent_size);
+        // the sequential layout decodes any input, short ones are padded with zeroes
+        if _api_graph.input_layout == InputLayout::Split {
+            let op = if self._is_fuzzables_fixed_length() { "!=" } else { "<" };
+            let min_len = self._fuzzables_min_length();
+            res.push_str(
+                format!(
+                    "{indent}if data.len() {op} {min_len} {{return;}}\n",
+                    indent = indent,
+                    op = op,
+                    min_len = min_len
+                )
+                .as_str(),
+            );
+        }
 
         let dynamic_param_start_index = self._fuzzable_fixed_part_length();
         let dynamic_param_number = self._dynamic_length_param_number();
@@ -516,15 +660,37 @@ pub(crate) fn afl_closure_body(&self, outer_indent: usize, test_index: usize) ->
             dynamic_param_start_index = dynamic_param_start_index,
             dynamic_param_number = dynamic_param_number
         );
-        if !self._is_fuzzables_fixed_length() {
-            res.push_str(
+        // the reader of the runtime crate splits the dynamic part itself
+        let reader =
+            if _api_graph.runtime_crate { Some(("_fdp", _api_graph.input_layout)) } else { None };
+        match reader {
+            Some((reader, InputLayout::Sequential)) => res.push_str(
+                format!(
+                    "{indent}let mut {reader} = FuzzedDataProvider::sequential(data);\n",
+                    indent = indent,
+                    reader = reader
+                )
+                .as_str(),
+            ),
+            Some((reader, InputLayout::Split)) => res.push_str(
+                format!(
+                    "{indent}let mut {reader} = FuzzedDataProvider::new(data, {fixed}, {dynamic});\n",
+                    indent = indent,
+                    reader = reader,
+                    fixed = dynamic_param_start_index,
+                    dynamic = dynamic_param_number
+                )
+                .as_str(),
+            ),
+            None if !self._is_fuzzables_fixed_length() => res.push_str(
                 format!(
                     "{indent}{every_dynamic_length};\n",
                     indent = indent,
                     every_dynamic_length = every_dynamic_length
                 )
                 .as_str(),
-            );
+            ),
+            None => {}
         }
 
         let mut fixed_start_index = 0; //当前固定长度的变量开始分配的位置
@@ -542,6 +708,7 @@ pub(crate) fn afl_closure_body(&self, outer_indent: usize, test_index: usize) ->
                 dynamic_param_number,
                 &dynamic_length_name.to_string(),
                 fuzzable_param,
+                reader,
             );
             res.push_str(
                 format!(
@@ -556,17 +723,6 @@ pub(crate) fn afl_closure_body(&self, outer_indent: usize, test_index: usize) ->
                 dynamic_param_index + fuzzable_param._dynamic_length_param_number();
         }
 
-        let mut test_function_call =
-            format!("{indent}test_function{test_index}(", indent = indent, test_index = test_index);
-        for i in 0..fuzzable_param_number {
-            if i != 0 {
-                test_function_call.push_str(" ,");
-            }
-            test_function_call.push_str(format!("_param{}", i).as_str());
-        }
-        test_function_call.push_str(");\n");
-        res.push_str(test_function_call.as_str());
-
         res
     }
 
@@ -613,47 +769,99 @@ pub(crate) fn to_well_written_function(
         let using_traits = self.generate_using_traits_string(indent_size, api_graph.cache());
         //res.push_str(using_traits.as_str()); // As we use full qualified path, we don't need to import trait
         //生成函数头
-        let function_header = self.generate_function_header_string(
-            api_graph,
-            test_index,
-            indent_size,
-            0,
-            test_function_title,
-            param_prefix,
-        );
+        let function_header = if self.op_loop.is_some() {
+            // op loops decode their params while running
+            format!(
+                "{indent}{test_function_title}{test_index}(_fdp: &mut FuzzedDataProvider) ",
+                indent = _generate_indent(indent_size),
+                test_function_title = test_function_title,
+                test_index = test_index
+            )
+        } else {
+            self.generate_function_header_string(
+                api_graph,
+                test_index,
+                indent_size,
+                0,
+                test_function_title,
+                param_prefix,
+            )
+        };
         res.push_str(function_header.as_str());
 
         //加入函数体开头的大括号
         res.push_str("{\n");
 
-        //加入函数体
-        if self._unsafe_tag {
-            let unsafe_indent = _generate_indent(indent_size + 4);
-            res.push_str(unsafe_indent.as_str());
-            res.push_str("unsafe {\n");
-            let unsafe_function_body = self._generate_function_body_string(
-                api_graph,
-                api_graph.cache(),
-                indent_size + 4,
-                param_prefix,
+        // panics raised on purpose by driver-local types are expected, only other panics reach
+        // the fuzzer
+        let expected_panics = self.expected_panics(api_graph);
+        let catch_panics = !expected_panics.is_empty();
+        let outer_indent_size = indent_size;
+        let indent_size = if catch_panics { indent_size + 4 } else { indent_size };
+        if catch_panics {
+            res.push_str(&_generate_indent(indent_size));
+            res.push_str("let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {\n");
+            // the global allocator only fails inside the tested calls
+            if api_graph.global_allocator {
+                res.push_str(&_generate_indent(indent_size + 4));
+                res.push_str("fuzz_alloc_arm(true);\n");
+            }
+        }
+
+        //加入函数体，unsafe块只包住需要它的调用
+        let function_body = self._generate_function_body_string(
+            api_graph,
+            api_graph.cache(),
+            indent_size,
+            param_prefix,
+            local_param_prefix,
+        );
+        res.push_str(function_body.as_str());
+        // differential drivers compare what the variants return
+        if self.observed {
+            res.push_str(&format!(
+                "{indent}fuzz_record({test_index}, (&FuzzObserve(&{local_param_prefix}{last})).fuzz_observe());\n",
+                indent = _generate_indent(indent_size + 4),
+                test_index = test_index,
+                local_param_prefix = local_param_prefix,
+                last = self.len() - 1
+            ));
+        }
+        if let Some(ref law_check) = self.law_check {
+            res.push_str(&trait_law::law_check_statements(
+                law_check,
                 local_param_prefix,
-            );
-            res.push_str(unsafe_function_body.as_str());
-            res.push_str(unsafe_indent.as_str());
-            res.push_str("}\n");
-        } else {
-            let function_body = self._generate_function_body_string(
-                api_graph,
-                api_graph.cache(),
-                indent_size,
-                param_prefix,
+                indent_size + 4,
+            ));
+        }
+        if let Some(ref check) = self.round_trip {
+            res.push_str(&round_trip::round_trip_statements(
+                check,
+                self.len() - 1,
                 local_param_prefix,
-            );
-            res.push_str(function_body.as_str());
+                indent_size + 4,
+            ));
+        }
+        if catch_panics {
+            let catch_indent = _generate_indent(indent_size);
+            res.push_str(&format!("{indent}}}));\n", indent = catch_indent));
+            if api_graph.global_allocator {
+                res.push_str(&format!("{indent}fuzz_alloc_arm(false);\n", indent = catch_indent));
+            }
+            let checks = expected_panics
+                .iter()
+                .map(|check| format!("{check}(&err)"))
+                .collect::<Vec<_>>()
+                .join(" || ");
+            res.push_str(&format!(
+                "{indent}if let Err(err) = res {{\n{indent}    if !({checks}) {{\n{indent}        std::panic::resume_unwind(err);\n{indent}    }}\n{indent}}}\n",
+                indent = catch_indent,
+                checks = checks
+            ));
         }
 
         //加入函数体结尾的大括号
-        let braket_indent = _generate_indent(indent_size);
+        let braket_indent = _generate_indent(outer_indent_size);
         res.push_str(braket_indent.as_str());
         res.push_str("}\n");
 
@@ -736,124 +944,196 @@ pub(crate) fn _generate_function_body_string(
         param_prefix: &str,
         local_param_prefix: &str,
     ) -> String {
+        if let Some(construction_len) = self.op_loop {
+            return op_loop::op_loop_body(
+                self,
+                api_graph,
+                cache,
+                outer_indent,
+                param_prefix,
+                local_param_prefix,
+                construction_len,
+            );
+        }
         let extra_indent = 4;
         let mut res = String::new();
         let body_indent = _generate_indent(outer_indent + extra_indent);
 
-        let dead_code = self._dead_code(api_graph);
+        let mut dead_code = self._dead_code(api_graph);
+        if self.observed {
+            // the result of the last call is still to be recorded
+            *dead_code.last_mut().unwrap() = false;
+        }
+        if let Some(ref law_check) = self.law_check {
+            for value in law_check.values {
+                dead_code[value] = false;
+            }
+        }
+        if let Some(ref check) = self.round_trip {
+            // the value written and, when functions read it back, the value read
+            dead_code[check.value] = false;
+            *dead_code.last_mut().unwrap() = false;
+        }
 
         //api_calls
         let api_calls_num = self.functions.len();
-        let full_name_map = &api_graph.full_name_map;
         for i in 0..api_calls_num {
-            let api_call = &self.functions[i];
-
-            //准备参数
-            let param_size = api_call.params.len();
-            let mut param_strings = Vec::new();
-            for j in 0..param_size {
-                let (param_type, index, call_type) = &api_call.params[j];
-                let call_type_array = call_type._split_at_unwrap_call_type();
-                //println!("call_type_array = {:?}",call_type_array);
-                let param_name = match param_type {
-                    ParamType::_FuzzableType => {
-                        let mut s1 = param_prefix.to_string();
-                        s1 += &(index.to_string());
-                        s1
-                    }
-                    ParamType::_FunctionReturn => {
-                        let mut s1 = local_param_prefix.to_string();
-                        s1 += &(index.to_string());
-                        s1
-                    }
-                };
-                let call_type_array_len = call_type_array.len();
-                if call_type_array_len == 0 {
-                    let call_type = &call_type_array[0];
-                    let param_string = call_type._to_call_string(&param_name, full_name_map, cache);
-                    // println!("#0#param string: {}", param_string);
-                    param_strings.push(param_string);
-                } else {
-                    let mut former_param_name = param_name.clone();
-                    let mut helper_index = 1;
-                    let mut former_helper_line = String::new();
-                    for k in 0..call_type_array_len - 1 {
-                        let call_type = &call_type_array[k];
-                        let helper_name = format!(
-                            "{}{}_param{}_helper{}",
-                            local_param_prefix, i, j, helper_index
-                        );
-                        let helper_line = format!(
-                            "{}let mut {} = {};\n",
-                            body_indent,
-                            helper_name,
-                            call_type._to_call_string(&former_param_name, full_name_map, cache)
-                        );
-                        if helper_index > 1 {
-                            if !api_util::_need_mut_tag(call_type) {
-                                former_helper_line = former_helper_line.replace("let mut ", "let ");
-                            }
-                            res.push_str(former_helper_line.as_str());
-                        }
-                        helper_index = helper_index + 1;
-                        former_param_name = helper_name;
-                        former_helper_line = helper_line;
-                    }
-                    let last_call_type = call_type_array.last().unwrap();
-                    if !api_util::_need_mut_tag(last_call_type) {
-                        former_helper_line = former_helper_line.replace("let mut ", "let ");
-                    }
-                    res.push_str(former_helper_line.as_str());
-                    let param_string =
-                        last_call_type._to_call_string(&former_param_name, full_name_map, cache);
-                    // println!("#1#param string: {}", param_string);
+            res.push_str(&self._generate_call_string(
+                api_graph,
+                cache,
+                i,
+                &dead_code,
+                &body_indent,
+                param_prefix,
+                local_param_prefix,
+            ));
+        }
+        res
+    }
 
-                    param_strings.push(param_string);
+    /// the statements of the `i`th call, helpers unwrapping its arguments included
+    pub(crate) fn _generate_call_string(
+        &self,
+        api_graph: &ApiGraph<'_>,
+        cache: &Cache,
+        i: usize,
+        dead_code: &[bool],
+        body_indent: &str,
+        param_prefix: &str,
+        local_param_prefix: &str,
+    ) -> String {
+        let mut res = String::new();
+        let full_name_map = &api_graph.full_name_map;
+        let api_call = &self.functions[i];
+
+        let api_function = &api_graph.api_functions[api_call.func.1];
+        let mut needs_unsafe = api_function._unsafe_tag._is_unsafe();
+
+        //准备参数
+        let param_size = api_call.params.len();
+        let mut param_strings = Vec::new();
+        for j in 0..param_size {
+            let (param_type, index, call_type) = &api_call.params[j];
+            let call_type_array = call_type._split_at_unwrap_call_type();
+            //println!("call_type_array = {:?}",call_type_array);
+            let param_name = match param_type {
+                ParamType::_FuzzableType => {
+                    let mut s1 = param_prefix.to_string();
+                    s1 += &(index.to_string());
+                    s1
+                }
+                ParamType::_FunctionReturn => {
+                    let mut s1 = local_param_prefix.to_string();
+                    s1 += &(index.to_string());
+                    s1
                 }
-            }
-            res.push_str(body_indent.as_str());
-            //如果不是最后一个调用
-            let api_function_index = api_call.func.1;
-            let api_function = &api_graph.api_functions[api_function_index];
-
-            let variable_name = if dead_code[i] || api_function._has_no_output() {
-                "_".to_string()
-            } else {
-                format!("{}{}", local_param_prefix, i)
             };
+            let call_type_array_len = call_type_array.len();
+            if call_type_array_len == 0 {
+                let call_type = &call_type_array[0];
+                let param_string = call_type._to_call_string(&param_name, full_name_map, cache);
+                // println!("#0#param string: {}", param_string);
+                needs_unsafe |= call_type._contains_unsafe_call_type();
+                param_strings.push(param_string);
+            } else {
+                let mut former_param_name = param_name.clone();
+                let mut helper_index = 1;
+                let mut former_helper_line = String::new();
+                for k in 0..call_type_array_len - 1 {
+                    let call_type = &call_type_array[k];
+                    let helper_name = format!(
+                        "{}{}_param{}_helper{}",
+                        local_param_prefix, i, j, helper_index
+                    );
+                    let helper_line = format!(
+                        "{}let mut {} = {};\n",
+                        body_indent,
+                        helper_name,
+                        unsafe_scoped(
+                            call_type._to_call_string(&former_param_name, full_name_map, cache),
+                            call_type._contains_unsafe_call_type()
+                        )
+                    );
+                    if helper_index > 1 {
+                        if !api_util::_need_mut_tag(call_type) {
+                            former_helper_line = former_helper_line.replace("let mut ", "let ");
+                        }
+                        res.push_str(former_helper_line.as_str());
+                    }
+                    helper_index = helper_index + 1;
+                    former_param_name = helper_name;
+                    former_helper_line = helper_line;
+                }
+                let last_call_type = call_type_array.last().unwrap();
+                if !api_util::_need_mut_tag(last_call_type) {
+                    former_helper_line = former_helper_line.replace("let mut ", "let ");
+                }
+                res.push_str(former_helper_line.as_str());
+                let param_string =
+                    last_call_type._to_call_string(&former_param_name, full_name_map, cache);
+                // println!("#1#param string: {}", param_string);
 
-            let mut_tag = if self._is_function_need_mut_tag(i) { "mut " } else { "" };
-            res.push_str(&format!("let {}{}", mut_tag, variable_name));
-            // if function is a generic API, we need annotate return type.
-            if api_function.is_mono() && api_function.output.is_some() {
-                res.push_str(&format!(
-                    ": {}",
-                    api_function
-                        .output
-                        .as_ref()
-                        .map(|output| api_util::_type_name(output, Some(api_graph.cache())))
-                        .map(|output| map_std_output_name(&output))
-                        .unwrap()
-                ));
+                needs_unsafe |= last_call_type._contains_unsafe_call_type();
+                param_strings.push(param_string);
             }
-            res.push_str(" = ");
-
-            let (api_type, function_index) = &api_call.func;
-
-            let api_function_full_name = map_std_output_name(
-                &api_graph.api_functions[*function_index].full_name(api_graph.cache()),
-            );
-            res.push_str(api_function_full_name.as_str());
-
-            res.push('(');
+        }
+        // arguments the doc says the call panics on are not tried
+        res.push_str(&documented_panic::guard_statements(
+            api_graph,
+            self,
+            i,
+            body_indent,
+            param_prefix,
+        ));
+        // a panic raised by the call itself is documented
+        let location = documented_panic::documented_location(api_graph, self, i);
+        if let Some((file, first_line, last_line)) = location {
+            res.push_str(&format!(
+                "{}fuzz_documented_enter({:?}, {}, {});\n",
+                body_indent, file, first_line, last_line
+            ));
+        }
+        res.push_str(body_indent);
+        //如果不是最后一个调用
 
-            res.push_str(&param_strings.join(", "));
-            res.push_str(");\n");
+        let variable_name = if dead_code[i] || api_function._has_no_output() {
+            "_".to_string()
+        } else {
+            format!("{}{}", local_param_prefix, i)
+        };
+
+        let mut_tag = if self._is_function_need_mut_tag(i) { "mut " } else { "" };
+        res.push_str(&format!("let {}{}", mut_tag, variable_name));
+        // if function is a generic API, we need annotate return type.
+        if api_function.is_mono() && api_function.output.is_some() {
+            res.push_str(&format!(
+                ": {}",
+                api_function
+                    .output
+                    .as_ref()
+                    .map(|output| api_util::_type_name(output, Some(api_graph.cache())))
+                    .map(|output| map_std_output_name(&output))
+                    .unwrap()
+            ));
+        }
+        res.push_str(" = ");
+
+        let api_function_full_name = map_std_output_name(&api_function.full_name(api_graph.cache()));
+        let call = format!("{}({})", api_function_full_name, param_strings.join(", "));
+        res.push_str(&unsafe_scoped(call, needs_unsafe));
+        res.push_str(";\n");
+        if location.is_some() {
+            res.push_str(&format!("{}fuzz_documented_exit();\n", body_indent));
         }
         res
     }
 }
 
+/// the expression in an `unsafe` block of its own if it needs one
+fn unsafe_scoped(expression: String, needs_unsafe: bool) -> String {
+    if needs_unsafe { format!("unsafe {{ {} }}", expression) } else { expression }
+}
+
 pub(crate) fn _generate_indent(indent_size: usize) -> String {
     " ".repeat(indent_size).to_string()
 }
@@ -6,10 +6,13 @@
     AssocItemConstraint,
 };
 use crate::formats::cache::Cache;
+use crate::fuzz_target::alloc_type;
 use crate::fuzz_target::api_function::ApiFunction;
 use crate::fuzz_target::call_type::CallType;
-use crate::fuzz_target::fuzzable_type::{self, FuzzableCallType};
+use crate::fuzz_target::evil_type;
+use crate::fuzz_target::fuzzable_type::{self, FuzzableCallType, InputLayout};
 use crate::fuzz_target::generic_param_map::GenericParamMap;
+use crate::fuzz_target::helper_type;
 use crate::fuzz_target::impl_util::FullNameMap;
 use crate::fuzz_target::prelude_type::{self, PreludeType};
 use crate::html::format::join_with_double_colon;
@@ -28,6 +31,9 @@
 use super::statistic::add;
 use super::{api_function, generic_function};
 
+#[cfg(test)]
+mod tests;
+
 pub(crate) fn extract_input_types(inputs: &clean::Arguments) -> Vec<clean::Type> {
     inputs.values.iter().map(|arg| arg.type_.clone()).collect()
 }
@@ -39,6 +45,17 @@ pub(crate) fn extract_input_types(inputs: &clean::Arguments) -> Vec<clean::Type>
 //     }
 // }
 
+/// Whether `did` is one of the types written into the drivers. They live in the local crate under
+/// made up indexes, so `tcx` must never be asked about them.
+pub(crate) fn is_driver_type(did: DefId) -> bool {
+    [helper_type::helper_did(), evil_type::evil_did(), alloc_type::alloc_did()].contains(&did)
+}
+
+/// whether `did` is a type defined by the crate itself
+pub(crate) fn is_crate_type(did: DefId) -> bool {
+    did.is_local() && !is_driver_type(did)
+}
+
 pub(crate) fn is_external_type(did: DefId, cache: &Cache) -> bool {
     if let Some(&(ref syms, item_type)) = cache.external_paths.get(&did) {
         return true;
@@ -121,6 +138,10 @@ fn map_std_type_name(name: &str) -> String {
     let name = replace_start_string(&name, "std::num::nonzero::", "std::num::");
     let name = replace_start_string(&name, "std::ffi::os_str::", "std::ffi::");
     let name = replace_start_string(&name, "std::ffi::c_str::", "std::ffi::");
+    let name = replace_start_string(&name, "std::sync::mutex::", "std::sync::");
+    let name = replace_start_string(&name, "std::sync::rwlock::", "std::sync::");
+    let name = replace_start_string(&name, "std::sync::once_lock::", "std::sync::");
+    let name = replace_start_string(&name, "std::cell::once::", "std::cell::");
     
     // private module name workaround for experiment library
     let name = replace_start_string(&name, "prost::error::", "prost::");
@@ -361,6 +382,54 @@ pub(crate) fn _same_type(
         CallType::_NotCompatible
     }
 }
+
+/// Types that `_same_type` may find compatible fall into the same bucket: references, raw
+/// pointers, `Option` and `Result` are looked through, paths compare by def id, and numbers
+/// share a bucket as they convert into each other with `as`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum TypeBucket {
+    Path(DefId),
+    Number,
+    Primitive(PrimitiveType),
+    Other(clean::Type),
+}
+
+pub(crate) fn type_bucket(
+    type_: &clean::Type,
+    full_name_map: &FullNameMap,
+    cache: &Cache,
+) -> TypeBucket {
+    if let Some(prelude_type) = PreludeType::from_type(type_, full_name_map, cache) {
+        return type_bucket(&prelude_type._get_final_type(), full_name_map, cache);
+    }
+    match type_ {
+        clean::Type::BorrowedRef { type_, .. } | clean::Type::RawPointer(_, type_) => {
+            type_bucket(type_, full_name_map, cache)
+        }
+        clean::Type::Path { path } => TypeBucket::Path(path.def_id()),
+        clean::Type::Primitive(primitive_type) => match primitive_type {
+            PrimitiveType::Isize
+            | PrimitiveType::I8
+            | PrimitiveType::I16
+            | PrimitiveType::I32
+            | PrimitiveType::I64
+            | PrimitiveType::I128
+            | PrimitiveType::Usize
+            | PrimitiveType::U8
+            | PrimitiveType::U16
+            | PrimitiveType::U32
+            | PrimitiveType::U64
+            | PrimitiveType::U128
+            | PrimitiveType::F16
+            | PrimitiveType::F32
+            | PrimitiveType::F64
+            | PrimitiveType::F128
+            | PrimitiveType::Char => TypeBucket::Number,
+            _ => TypeBucket::Primitive(*primitive_type),
+        },
+        _ => TypeBucket::Other(type_.clone()),
+    }
+}
 /* 
 pub(crate) fn _same_type_new(
     output_type: &clean::Type,
@@ -1280,6 +1349,7 @@ pub(crate) fn is_unsupported_fuzzable(
     type_: &Type,
     full_name_map: &FullNameMap,
     cache: &Cache,
+    layout: InputLayout,
 ) -> bool {
     let fuzzable_call_type = fuzzable_type::fuzzable_call_type(type_, full_name_map, cache);
 
@@ -1294,7 +1364,7 @@ pub(crate) fn is_unsupported_fuzzable(
         return true;
     }
 
-    if fuzzable_type._is_multiple_dynamic_length() {
+    if !fuzzable_type._is_decodable(layout) {
         println!("input fail#1");
         return true;
     }
@@ -1317,3 +1387,32 @@ pub(crate) fn has_type_parameter(generics: &Generics) -> bool {
     }
     return false;
 }
+
+/// The text of the `# name` section of a doc comment, up to the next heading of the same or a
+/// higher level. Lines in code blocks are not headings.
+pub(crate) fn doc_section(doc: &str, name: &str) -> Option<String> {
+    let mut in_code = false;
+    let mut section: Option<(usize, Vec<&str>)> = None;
+    for line in doc.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let heading = !in_code && level > 0 && trimmed[level..].starts_with(' ');
+        if heading {
+            if let Some((section_level, ref lines)) = section {
+                if level <= section_level {
+                    return Some(lines.join("\n"));
+                }
+            } else if trimmed[level..].trim().eq_ignore_ascii_case(name) {
+                section = Some((level, Vec::new()));
+                continue;
+            }
+        }
+        if let Some((_, ref mut lines)) = section {
+            lines.push(line);
+        }
+    }
+    section.map(|(_, lines)| lines.join("\n"))
+}
@@ -0,0 +1,46 @@
+use super::doc_section;
+
+#[test]
+fn test_doc_section() {
+    let doc = "Gets the element.
+
+# Panics
+
+Panics if `idx >= len`.
+
+## Example of a panic
+
+Still part of it.
+
+# Safety
+
+Nothing to uphold.";
+    assert_eq!(
+        doc_section(doc, "Panics").as_deref(),
+        Some("\nPanics if `idx >= len`.\n\n## Example of a panic\n\nStill part of it.\n")
+    );
+    assert_eq!(doc_section(doc, "safety").as_deref(), Some("\nNothing to uphold."));
+    assert_eq!(doc_section(doc, "Errors"), None);
+}
+
+#[test]
+fn test_doc_section_code_block() {
+    let doc = "# Panics
+
+```
+# fn hidden() {}
+#Not a heading either
+```
+Panics on empty input.
+#no space, no heading
+## Deeper";
+    assert_eq!(
+        doc_section(doc, "Panics").as_deref(),
+        Some(concat!(
+            "\n```\n# fn hidden() {}\n#Not a heading either\n```\n",
+            "Panics on empty input.\n#no space, no heading\n## Deeper"
+        ))
+    );
+    // a heading in a code block does not start the section
+    assert_eq!(doc_section("```\n# Panics\n```\ntext", "Panics"), None);
+}
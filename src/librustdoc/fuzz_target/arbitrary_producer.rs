@@ -0,0 +1,82 @@
+use crate::clean::{PrimitiveType, Type};
+use crate::fuzz_target::api_function::{ApiFunction, ApiUnsafety};
+use crate::fuzz_target::api_graph::ApiGraph;
+use crate::fuzz_target::api_sequence::ApiSequence;
+use crate::fuzz_target::api_util::{self, _type_name};
+use rustc_hir::Mutability;
+
+static ARBITRARY_TRAIT: &'static str = "arbitrary::Arbitrary";
+static ARBITRARY_PRODUCER_NAME: &'static str = "fuzz_arbitrary";
+
+/// Builds a value from its own part of the input with the `Arbitrary` impl of the crate. Input
+/// the impl rejects ends the run, like `_unwrap_result` does.
+static ARBITRARY_DEFINITION: &'static str = r#"fn fuzz_arbitrary<T: for<'a> arbitrary::Arbitrary<'a>>(data: &[u8]) -> T {
+    let unstructured = arbitrary::Unstructured::new(data);
+    match <T as arbitrary::Arbitrary>::arbitrary_take_rest(unstructured) {
+        Ok(value) => value,
+        Err(_) => {
+            use std::process;
+            process::exit(0);
+        }
+    }
+}
+"#;
+
+/// `fuzz_arbitrary::<T>(&[u8]) -> T` for every local type without generics implementing
+/// `arbitrary::Arbitrary`, ordered by the name of the type. Crates deriving `Arbitrary` know
+/// better than field by field construction which values are worth trying.
+pub(crate) fn producers(api_graph: &ApiGraph<'_>) -> Vec<ApiFunction> {
+    let cache = api_graph.cache();
+    let mut res = Vec::new();
+    for (did, trait_impls) in &api_graph.trait_impl_map.inner {
+        if !api_util::is_crate_type(*did) {
+            continue;
+        }
+        let Some(trait_impl) = trait_impls.iter().find(|trait_impl| {
+            !trait_impl.negative
+                && matches!(trait_impl.for_, Type::Path { .. })
+                && trait_impl.for_.generics().map_or(true, |generics| generics.is_empty())
+                && api_graph
+                    .full_name_map
+                    .get_full_name(trait_impl.trait_.def_id())
+                    .map_or(false, |name| name == ARBITRARY_TRAIT)
+        }) else {
+            continue;
+        };
+        let type_name = _type_name(&trait_impl.for_, Some(cache));
+        res.push(ApiFunction {
+            name: ARBITRARY_PRODUCER_NAME.to_string(),
+            full_path: format!("{}::<{}>", ARBITRARY_PRODUCER_NAME, type_name),
+            inputs: vec![Type::BorrowedRef {
+                lifetime: None,
+                mutability: Mutability::Not,
+                type_: Box::new(Type::Slice(Box::new(Type::Primitive(PrimitiveType::U8)))),
+            }],
+            output: Some(trait_impl.for_.clone()),
+            self_: None,
+            trait_: None,
+            _unsafe_tag: ApiUnsafety::Normal,
+            trivial_safety: false,
+            panics_doc: None,
+            mono: false,
+            local: false,
+            rpg_local: false,
+        });
+    }
+    res.sort_by(|a, b| a.full_path.cmp(&b.full_path));
+    res
+}
+
+/// whether a call of the sequence builds its value with `Arbitrary`
+pub(crate) fn uses_arbitrary(api_graph: &ApiGraph<'_>, sequence: &ApiSequence) -> bool {
+    sequence.functions.iter().any(|api_call| {
+        api_graph.api_functions[api_call.func.1].name == ARBITRARY_PRODUCER_NAME
+    })
+}
+
+/// source of `fuzz_arbitrary` in driver
+pub(crate) fn definition() -> String {
+    let mut res = ARBITRARY_DEFINITION.to_string();
+    res.push('\n');
+    res
+}
@@ -120,6 +120,11 @@ pub(crate) fn unsafe_call_type(&self) -> ApiUnsafety {
         }
     }
 
+    /// whether any layer of the call type needs an `unsafe` block
+    pub(crate) fn _contains_unsafe_call_type(&self) -> bool {
+        self._call_type_to_array().iter().any(|call_type| call_type.unsafe_call_type()._is_unsafe())
+    }
+
     pub(crate) fn _contains_move_call_type(&self) -> bool {
         self._contains_unwrap_call_type()
     }
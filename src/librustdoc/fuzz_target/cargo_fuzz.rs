@@ -0,0 +1,101 @@
+use crate::fuzz_target::driver_workspace;
+use crate::fuzz_target::named_value::NamedValue;
+use crate::fuzz_target::runtime_crate::RUNTIME_CRATE_NAME;
+use std::fs;
+use std::path::PathBuf;
+
+/// directory of the cargo-fuzz project, next to the other generated files
+static CARGO_FUZZ_DIR: &'static str = "fuzz";
+static FUZZ_TARGETS_DIR: &'static str = "fuzz_targets";
+static LIBFUZZER_SYS_VERSION: &'static str = "0.4";
+
+/// The fuzzer the drivers are written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FuzzEngine {
+    /// `fuzz!` of afl.rs, one file per driver in `test_files`
+    Afl,
+    /// `fuzz_target!` of libfuzzer-sys, a cargo-fuzz project in `fuzz`
+    Libfuzzer,
+}
+
+impl NamedValue for FuzzEngine {
+    const NAMES: &'static [(&'static str, FuzzEngine)] =
+        &[("afl", FuzzEngine::Afl), ("libfuzzer", FuzzEngine::Libfuzzer)];
+}
+
+impl FuzzEngine {
+    /// the lines of a driver making the fuzzing macro of the engine available, replay files
+    /// drop them
+    pub(crate) fn header(&self) -> &'static str {
+        match self {
+            FuzzEngine::Afl => "#[macro_use]\nextern crate afl;\n",
+            FuzzEngine::Libfuzzer => "#![no_main]\n#[macro_use]\nextern crate libfuzzer_sys;\n",
+        }
+    }
+}
+
+/// `Cargo.toml` of the cargo-fuzz project with a `[[bin]]` for every driver
+fn manifest(
+    crate_name: &str,
+    features: &[String],
+    drivers: &[String],
+    dependencies: &str,
+    runtime_crate: bool,
+) -> String {
+    let package = std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| crate_name.to_string());
+    let mut res = format!(
+        "[package]\nname = \"{package}-fuzz\"\nversion = \"0.0.0\"\npublish = false\nedition = \"2021\"\n\n[package.metadata]\ncargo-fuzz = true\n\n",
+        package = package
+    );
+    res.push_str("[dependencies]\n");
+    res.push_str(&format!("libfuzzer-sys = \"{}\"\n", LIBFUZZER_SYS_VERSION));
+    res.push_str(dependencies);
+    res.push_str(&driver_workspace::crate_dependency(crate_name, features));
+    if runtime_crate {
+        res.push_str(&format!(
+            "{name} = {{ path = \"../{name}\" }}\n",
+            name = RUNTIME_CRATE_NAME
+        ));
+    }
+    // not a member of the workspace of the tested crate
+    res.push_str("\n[workspace]\nmembers = [\".\"]\n");
+    for driver in drivers {
+        res.push_str(&format!(
+            "\n[[bin]]\nname = \"{driver}\"\npath = \"{dir}/{driver}.rs\"\ntest = false\ndoc = false\nbench = false\n",
+            driver = driver,
+            dir = FUZZ_TARGETS_DIR
+        ));
+    }
+    res
+}
+
+/// directory of the corpus of every driver, `cargo fuzz run` starts from it
+pub(crate) fn corpus_dir(dir: &PathBuf) -> PathBuf {
+    dir.join(CARGO_FUZZ_DIR).join("corpus")
+}
+
+/// write the drivers, named and given as `(name, content)`, as the fuzz targets of a cargo-fuzz
+/// project in `dir/fuzz`
+pub(crate) fn write_cargo_fuzz(
+    dir: &PathBuf,
+    crate_name: &str,
+    features: &[String],
+    drivers: &[(String, &String)],
+    runtime_crate: bool,
+) {
+    let project_dir = dir.join(CARGO_FUZZ_DIR);
+    let targets_dir = project_dir.join(FUZZ_TARGETS_DIR);
+    println!("cargo_fuzz_path: {project_dir:?}");
+    fs::create_dir_all(&targets_dir).unwrap();
+    let names = drivers.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+    let contents = drivers.iter().map(|(_, content)| *content).collect::<Vec<_>>();
+    let dependencies = driver_workspace::driver_dependencies(crate_name, &contents);
+    fs::write(
+        project_dir.join("Cargo.toml"),
+        manifest(crate_name, features, &names, &dependencies, runtime_crate),
+    )
+    .unwrap();
+    for (name, content) in drivers {
+        fs::write(targets_dir.join(format!("{}.rs", name)), content).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+/// api sequence of every driver written in the last round, one `driver: api -> api` per line
+static DRIVER_MANIFEST_FILE: &'static str = "drivers.txt";
+/// a driver that found no new path for this long is considered saturated
+static PLATEAU_SECONDS: u64 = 600;
+
+/// Coverage of a former fuzzing round, read from `--fuzz-coverage`.
+///
+/// A json file is taken as `llvm-cov export` output and tells which api bodies were never
+/// executed. A directory is searched for AFL `fuzzer_stats`, one sub-directory per driver named
+/// after its file (e.g. `test_url3`), and tells which drivers stopped finding new paths.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CoverageFeedback {
+    /// `Owner::function` => execution count summed over all instances
+    function_counts: FxHashMap<String, u64>,
+    /// api sequences of the drivers that plateaued
+    plateaued: FxHashSet<Vec<String>>,
+}
+
+impl CoverageFeedback {
+    pub(crate) fn new() -> Self {
+        CoverageFeedback { function_counts: FxHashMap::default(), plateaued: FxHashSet::default() }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.function_counts.is_empty() && self.plateaued.is_empty()
+    }
+
+    /// `output_dir` holds the manifest of the round that produced the coverage
+    pub(crate) fn load(path: &PathBuf, output_dir: &PathBuf) -> Self {
+        let mut res = CoverageFeedback::new();
+        if path.is_dir() {
+            let manifest = read_manifest(&output_dir.join(DRIVER_MANIFEST_FILE));
+            res.load_afl_stats(path, &manifest);
+        } else {
+            res.load_llvm_cov(path);
+        }
+        println!(
+            "[Coverage] {} functions, {} uncovered, {} plateaued drivers",
+            res.function_counts.len(),
+            res.function_counts.values().filter(|count| **count == 0).count(),
+            res.plateaued.len()
+        );
+        res
+    }
+
+    fn load_llvm_cov(&mut self, file: &PathBuf) {
+        let json = match fs::read_to_string(file).map_err(|e| e.to_string()).and_then(|content| {
+            serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string())
+        }) {
+            Ok(json) => json,
+            Err(e) => {
+                println!("[Coverage] cannot read llvm-cov export {}: {}", file.display(), e);
+                return;
+            }
+        };
+        let Some(data) = json["data"].as_array() else {
+            println!("[Coverage] {} is not an llvm-cov export", file.display());
+            return;
+        };
+        for functions in data.iter().filter_map(|export| export["functions"].as_array()) {
+            for function in functions {
+                let (Some(name), Some(count)) =
+                    (function["name"].as_str(), function["count"].as_u64())
+                else {
+                    continue;
+                };
+                let Some(key) = coverage_key(&demangle(name)) else {
+                    continue;
+                };
+                *self.function_counts.entry(key).or_insert(0) += count;
+            }
+        }
+    }
+
+    fn load_afl_stats(&mut self, dir: &Path, manifest: &FxHashMap<String, Vec<String>>) {
+        let mut stats_files = Vec::new();
+        find_files(dir, "fuzzer_stats", &mut stats_files);
+        for stats_file in stats_files {
+            // `out/test_url3/default/fuzzer_stats` belongs to the nearest known driver
+            let Some(apis) = stats_file
+                .ancestors()
+                .filter_map(|ancestor| ancestor.file_name()?.to_str())
+                .find_map(|name| manifest.get(name))
+            else {
+                println!("[Coverage] {} matches no driver", stats_file.display());
+                continue;
+            };
+            let Ok(content) = fs::read_to_string(&stats_file) else {
+                continue;
+            };
+            if is_plateaued(&content) {
+                println!("[Coverage] {} plateaued", stats_file.display());
+                self.plateaued.insert(apis.clone());
+            }
+        }
+    }
+
+    /// whether the body of the api was never executed. Unknown apis are not uncovered.
+    pub(crate) fn is_uncovered(&self, full_name: &str) -> bool {
+        coverage_key(full_name).and_then(|key| self.function_counts.get(&key)) == Some(&0)
+    }
+
+    /// whether a driver calling exactly these apis plateaued
+    pub(crate) fn is_plateaued(&self, apis: &Vec<String>) -> bool {
+        self.plateaued.contains(apis)
+    }
+}
+
+/// write the api sequence of every driver, read back by the next round
+pub(crate) fn write_manifest(dir: &PathBuf, drivers: &Vec<(String, Vec<String>)>) {
+    let mut content = String::new();
+    for (driver, apis) in drivers {
+        content.push_str(&format!("{}: {}\n", driver, apis.join(" -> ")));
+    }
+    fs::create_dir_all(dir).unwrap();
+    fs::write(dir.join(DRIVER_MANIFEST_FILE), content).unwrap();
+}
+
+fn read_manifest(file: &PathBuf) -> FxHashMap<String, Vec<String>> {
+    let mut res = FxHashMap::default();
+    let Ok(content) = fs::read_to_string(file) else {
+        println!("[Coverage] no driver manifest at {}", file.display());
+        return res;
+    };
+    for line in content.lines() {
+        if let Some((driver, apis)) = line.split_once(": ") {
+            res.insert(driver.to_string(), apis.split(" -> ").map(|api| api.to_string()).collect());
+        }
+    }
+    res
+}
+
+fn find_files(dir: &Path, name: &str, res: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths =
+        entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            find_files(&path, name, res);
+        } else if path.file_name().map_or(false, |file_name| file_name == name) {
+            res.push(path);
+        }
+    }
+}
+
+/// no new path in the last `PLATEAU_SECONDS` of the run.
+/// AFL++ names the field `last_find`, older AFL `last_path`.
+fn is_plateaued(stats: &str) -> bool {
+    let mut fields = FxHashMap::default();
+    for line in stats.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            if let Ok(value) = value.trim().parse::<u64>() {
+                fields.insert(key.trim(), value);
+            }
+        }
+    }
+    let (Some(start), Some(update)) = (fields.get("start_time"), fields.get("last_update")) else {
+        return false;
+    };
+    let last_find = fields.get("last_find").or(fields.get("last_path")).copied().unwrap_or(0);
+    update.saturating_sub(last_find.max(*start)) >= PLATEAU_SECONDS
+}
+
+/// Key shared by a demangled symbol and an api full name: owner type (or module) and function
+/// name without generic args, e.g. `Url::parse`. Trait impls use their self type, so
+/// `<url::Url as core::fmt::Display>::fmt` gives `Url::fmt`. Closures and shims have no key.
+pub(crate) fn coverage_key(name: &str) -> Option<String> {
+    if name.contains('{') {
+        return None;
+    }
+    let name = name.trim();
+    let (owner, function) = if name.starts_with('<') {
+        let close = matching_bracket(name, 0)?;
+        let inner = &name[1..close];
+        let self_type = match find_top_level(inner, " as ") {
+            Some(pos) => &inner[..pos],
+            None => inner,
+        };
+        let function = name[close + 1..].trim_start_matches("::");
+        (last_segment(self_type)?, last_segment(function)?)
+    } else {
+        let segments = split_path(&strip_generics(name));
+        if segments.len() < 2 {
+            return None;
+        }
+        (segments[segments.len() - 2].clone(), segments[segments.len() - 1].clone())
+    };
+    Some(format!("{}::{}", owner, function))
+}
+
+/// last path segment of a type without references and generic args
+fn last_segment(path: &str) -> Option<String> {
+    let path = path.trim().trim_start_matches(['&', '*']);
+    let path = path.trim_start_matches("mut ").trim_start_matches("const ");
+    split_path(&strip_generics(path)).pop()
+}
+
+fn matching_bracket(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices().skip(open) {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_top_level(s: &str, pat: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if depth == 0 && s[i..].starts_with(pat) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn strip_generics(s: &str) -> String {
+    let mut res = String::new();
+    let mut depth = 0;
+    for c in s.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if depth == 0 => res.push(c),
+            _ => {}
+        }
+    }
+    res
+}
+
+fn split_path(s: &str) -> Vec<String> {
+    s.split("::")
+        .map(|segment| segment.trim().to_string())
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Demangle legacy `_ZN..E` and v0 `_R` symbols without their hash, other names are returned
+/// as is. v0 prints inherent methods as `<url::Url>::parse`, which `coverage_key` accepts.
+pub(crate) fn demangle(symbol: &str) -> String {
+    match rustc_demangle::try_demangle(symbol) {
+        Ok(demangled) => format!("{:#}", demangled),
+        Err(_) => symbol.to_string(),
+    }
+}
@@ -0,0 +1,85 @@
+use super::{coverage_key, demangle, is_plateaued};
+
+#[test]
+fn test_coverage_key() {
+    let cases = [
+        ("url::Url::parse", Some("Url::parse")),
+        ("<url::Url as core::fmt::Display>::fmt", Some("Url::fmt")),
+        ("<&mut url::Url as core::fmt::Display>::fmt", Some("Url::fmt")),
+        ("<url::Url>::parse", Some("Url::parse")),
+        ("<alloc::vec::Vec<u8> as url::Encode>::encode::<u16>", Some("Vec::encode")),
+        ("url::decode::<u16>", Some("url::decode")),
+        ("url::parser::Parser<'a>::parse_host", Some("Parser::parse_host")),
+        ("url::Url::parse::{closure#0}", None),
+        ("url::Url::parse::{{closure}}", None),
+        ("parse", None),
+    ];
+    for (name, key) in cases {
+        assert_eq!(coverage_key(name).as_deref(), key, "{}", name);
+    }
+}
+
+#[test]
+fn test_demangle() {
+    let cases = [
+        // legacy
+        ("_ZN3url3Url5parse17h8a5c3a3e80d1ab1fE", "url::Url::parse"),
+        ("_ZN3url6decode17h00f8e2daca5d8588E", "url::decode"),
+        (
+            "_ZN47_$LT$url..Url$u20$as$u20$core..fmt..Display$GT$3fmt17h26f173068f0bb4c1E",
+            "<url::Url as core::fmt::Display>::fmt",
+        ),
+        // v0
+        ("_RNvMCsbTceOviSN5W_3urlNtB2_3Url5parse", "<url::Url>::parse"),
+        ("_RINvCsbTceOviSN5W_3url6decodetEB2_", "url::decode::<u16>"),
+        (
+            "_RNvXs_CsbTceOviSN5W_3urlNtB4_3UrlNtNtCsgEmfK2I1SDS_4core3fmt7Display3fmt",
+            "<url::Url as core::fmt::Display>::fmt",
+        ),
+        // already demangled by llvm-cov
+        ("url::Url::parse", "url::Url::parse"),
+    ];
+    for (symbol, name) in cases {
+        assert_eq!(demangle(symbol), name, "{}", symbol);
+    }
+    // legacy and v0 symbols of the same api share a key
+    assert_eq!(
+        coverage_key(&demangle("_ZN3url3Url5parse17h8a5c3a3e80d1ab1fE")),
+        coverage_key(&demangle("_RNvMCsbTceOviSN5W_3urlNtB2_3Url5parse"))
+    );
+}
+
+fn fuzzer_stats(fields: &[(&str, u64)]) -> String {
+    fields.iter().map(|(key, value)| format!("{:<18}: {}\n", key, value)).collect()
+}
+
+#[test]
+fn test_is_plateaued() {
+    // AFL++ `last_find`
+    assert!(is_plateaued(&fuzzer_stats(&[
+        ("start_time", 1000),
+        ("last_update", 2000),
+        ("last_find", 1300)
+    ])));
+    assert!(!is_plateaued(&fuzzer_stats(&[
+        ("start_time", 1000),
+        ("last_update", 2000),
+        ("last_find", 1500)
+    ])));
+    // older AFL `last_path`
+    assert!(is_plateaued(&fuzzer_stats(&[
+        ("start_time", 1000),
+        ("last_update", 2000),
+        ("last_path", 1300)
+    ])));
+    // nothing found yet counts from the start of the run
+    assert!(is_plateaued(&fuzzer_stats(&[
+        ("start_time", 1000),
+        ("last_update", 2000),
+        ("last_find", 0)
+    ])));
+    assert!(!is_plateaued(&fuzzer_stats(&[("start_time", 1000), ("last_update", 1300)])));
+    // a run without timestamps is not judged
+    assert!(!is_plateaued(&fuzzer_stats(&[("last_find", 1300)])));
+    assert!(!is_plateaued("not afl stats"));
+}
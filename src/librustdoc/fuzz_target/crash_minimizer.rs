@@ -0,0 +1,440 @@
+use crate::fuzz_target::api_graph::{ApiGraph, ApiType};
+use crate::fuzz_target::api_sequence::{ApiSequence, ParamType};
+use crate::fuzz_target::fuzzable_type::{FuzzableType, InputLayout};
+use rustc_data_structures::fx::FxHashSet;
+
+/// sequences longer than this only try dropping one call or a suffix, not every subset
+static MAX_SUBSET_CALLS: usize = 8;
+/// simpler producers tried for one argument
+static MAX_PRODUCER_SWAPS: usize = 4;
+/// variants compiled into one minimizer, the original included
+static MAX_VARIANTS: usize = 64;
+
+/// Reads the crashing input of the original driver as laid out by `afl_closure_body` and lays
+/// the bytes out again for a variant: every fixed part and dynamic slot is copied from the
+/// original param it stands for, params new to the variant get zeros. The last slot of the
+/// original runs to the end of the input, its extra bytes stay in the last slot of the variant
+/// as far as they do not make the slots of the variant longer.
+static REENCODE_DEFINITION: &'static str = r#"fn fuzz_reencode(
+    orig: &[u8],
+    fixed_len: usize,
+    dynamic_num: usize,
+    fixed: &[(Option<usize>, usize)],
+    dynamic: &[Option<usize>],
+) -> Option<Vec<u8>> {
+    // the driver itself skips inputs of a different length
+    if orig.len() < fixed_len || (dynamic_num == 0 && orig.len() != fixed_len) {
+        return None;
+    }
+    let dynamic_length = if dynamic_num == 0 { 1 } else { (orig.len() - fixed_len) / dynamic_num };
+    let extra = if dynamic_num == 0 { 0 } else { (orig.len() - fixed_len) % dynamic_num };
+    let extra = if dynamic.len() == 1 { extra } else { extra.min(dynamic.len().saturating_sub(1)) };
+    let mut data = Vec::new();
+    for (offset, len) in fixed {
+        match offset {
+            Some(offset) => data.extend_from_slice(&orig[*offset..*offset + *len]),
+            None => data.resize(data.len() + *len, 0),
+        }
+    }
+    for (index, slot) in dynamic.iter().enumerate() {
+        match slot {
+            Some(slot) => {
+                let start = fixed_len + *slot * dynamic_length;
+                let mut end = start + dynamic_length;
+                if *slot == dynamic_num - 1 && index == dynamic.len() - 1 {
+                    end += extra;
+                }
+                data.extend_from_slice(&orig[start..end]);
+            }
+            None => data.resize(data.len() + dynamic_length, 0),
+        }
+    }
+    Some(data)
+}
+"#;
+
+/// Reads the crashing input of the original driver as the sequential reader does and lays the
+/// values read for every param out again for a variant. The shapes tell what the reader does for
+/// each param: a fixed number of bytes, a length byte and as many elements of some bytes, a
+/// length byte and as many elements of another shape, or several shapes one after another.
+/// Slices cut at the end of the input get the length they were read with, params new to the
+/// variant read zeros, which the reader pads short inputs with anyway.
+static SEQUENTIAL_REENCODE_DEFINITION: &'static str = r#"enum FuzzShape {
+    Fixed(usize),
+    Bytes(usize),
+    Vec(&'static FuzzShape),
+    All(&'static [FuzzShape]),
+}
+
+/// append the bytes the value of `shape` at `pos` reads to `data`, return the position after it
+fn fuzz_sequential_read(orig: &[u8], pos: usize, shape: &FuzzShape, data: &mut Vec<u8>) -> usize {
+    let byte = |pos: usize| orig.get(pos).copied().unwrap_or(0);
+    match shape {
+        FuzzShape::Fixed(size) => {
+            data.extend((pos..pos + size).map(byte));
+            pos + size
+        }
+        FuzzShape::Bytes(size) => {
+            // the reader cuts slices at the end of the input
+            let start = (pos + 1).min(orig.len());
+            let len = (byte(pos) as usize).min((orig.len() - start) / size);
+            data.push(len as u8);
+            data.extend_from_slice(&orig[start..start + len * size]);
+            (pos + 1 + byte(pos) as usize * size).min(orig.len().max(pos + 1))
+        }
+        FuzzShape::Vec(inner) => {
+            data.push(byte(pos));
+            (0..byte(pos)).fold(pos + 1, |pos, _| fuzz_sequential_read(orig, pos, inner, data))
+        }
+        FuzzShape::All(inners) => {
+            inners.iter().fold(pos, |pos, inner| fuzz_sequential_read(orig, pos, inner, data))
+        }
+    }
+}
+
+fn fuzz_sequential_reencode(
+    orig: &[u8],
+    original: &[FuzzShape],
+    kept: &[(Option<usize>, FuzzShape)],
+) -> Option<Vec<u8>> {
+    let mut values = Vec::new();
+    let mut pos = 0;
+    for shape in original {
+        let mut value = Vec::new();
+        pos = fuzz_sequential_read(orig, pos, shape, &mut value);
+        values.push(value);
+    }
+    let mut data = Vec::new();
+    for (origin, shape) in kept {
+        match origin {
+            Some(origin) => data.extend_from_slice(&values[*origin]),
+            None => {
+                fuzz_sequential_read(&[], 0, shape, &mut data);
+            }
+        }
+    }
+    Some(data)
+}
+"#;
+
+/// Every variant runs in a child process of the minimizer itself, so panics, aborts and
+/// sanitizer reports can be told apart. A crash is the same when it panics at the same location
+/// or ends with the same sanitizer summary.
+static MAIN_DEFINITION: &'static str = r#"fn fuzz_crash_signature(exe: &str, variant: usize, file: &str) -> Option<String> {
+    let output = std::process::Command::new(exe)
+        .arg("--variant")
+        .arg(variant.to_string())
+        .arg(file)
+        .output()
+        .ok()?;
+    if output.status.success() {
+        return None;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if let Some(line) = stderr.lines().find(|line| line.contains("SUMMARY: ")) {
+        return Some(line[line.find("SUMMARY: ").unwrap()..].to_string());
+    }
+    if let Some(line) = stderr.lines().find(|line| line.contains("panicked at ")) {
+        // `panicked at src/lib.rs:1:2:` as well as the older `panicked at 'msg', src/lib.rs:1:2`
+        let rest = &line[line.find("panicked at ").unwrap() + "panicked at ".len()..];
+        let location = rest.trim_end_matches(':').rsplit(' ').next().unwrap_or(rest);
+        return Some(format!("panicked at {}", location));
+    }
+    Some(output.status.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() == 4 && args[1] == "--variant" {
+        let orig = std::fs::read(&args[3]).unwrap();
+        let (input, run, _) = FUZZ_VARIANTS[args[2].parse::<usize>().unwrap()];
+        if let Some(data) = input(&orig) {
+            run(&data);
+        }
+        return;
+    }
+    if args.len() != 2 {
+        eprintln!("usage: {} <crash input>", args[0]);
+        std::process::exit(2);
+    }
+    let Some(signature) = fuzz_crash_signature(&args[0], 0, &args[1]) else {
+        println!("{} does not crash the driver", args[1]);
+        std::process::exit(1);
+    };
+    println!("crash: {}", signature);
+    // variants are sorted by size, the original comes first
+    for variant in 1..FUZZ_VARIANTS.len() {
+        if fuzz_crash_signature(&args[0], variant, &args[1]).as_ref() != Some(&signature) {
+            continue;
+        }
+        let (input, _, source) = FUZZ_VARIANTS[variant];
+        let orig = std::fs::read(&args[1]).unwrap();
+        let min_file = format!("{}.min", args[1]);
+        std::fs::write(&min_file, input(&orig).unwrap()).unwrap();
+        println!("smallest crashing variant {}, input written to {}", variant, min_file);
+        println!("{}", source);
+        return;
+    }
+    println!("no smaller variant crashes the same way");
+}
+"#;
+
+/// A variant of the crashing sequence. `origins` tells for every call which call of the
+/// original sequence it stands for, swapped producers stand for none.
+struct Variant {
+    sequence: ApiSequence,
+    origins: Vec<Option<usize>>,
+}
+
+/// Source of a program that replays a crash input of the driver of `sequence` against smaller
+/// variants of the sequence: calls removed, producers swapped for ones taking fewer inputs. The
+/// smallest variant that still crashes the same way is reported with its re-encoded input.
+pub(crate) fn minimizer_file(api_graph: &ApiGraph<'_>, sequence: &ApiSequence) -> String {
+    let variants = variants(api_graph, sequence);
+    println!("[Minimize] {} variants of a sequence of {} calls", variants.len(), sequence.len());
+    let sequences = variants.iter().map(|variant| &variant.sequence).collect::<Vec<_>>();
+    let prelude = ApiSequence::driver_prelude(api_graph, &sequences);
+    // replayed in plain processes, afl is not needed
+    let mut res = prelude.replace(api_graph.engine.header(), "");
+    if api_graph.input_layout == InputLayout::Split {
+        res.push_str(REENCODE_DEFINITION);
+    } else {
+        res.push_str(SEQUENTIAL_REENCODE_DEFINITION);
+    }
+    res.push('\n');
+    let mut table = String::new();
+    for (index, variant) in variants.iter().enumerate() {
+        let test_function = variant.sequence.to_well_written_function(api_graph, index, 0);
+        res.push_str(&test_function);
+        res.push('\n');
+        res.push_str(&input_function(api_graph, sequence, variant, index));
+        res.push('\n');
+        res.push_str(&format!(
+            "fn fuzz_variant{}(data: &[u8]) {{\n{}}}\n\n",
+            index,
+            variant.sequence.afl_closure_body(api_graph, 0, index)
+        ));
+        table.push_str(&format!(
+            "    (fuzz_variant_input{index}, fuzz_variant{index}, {source:?}),\n",
+            index = index,
+            source = test_function
+        ));
+    }
+    res.push_str("static FUZZ_VARIANTS: &[(fn(&[u8]) -> Option<Vec<u8>>, fn(&[u8]), &str)] = &[\n");
+    res.push_str(&table);
+    res.push_str("];\n\n");
+    res.push_str(MAIN_DEFINITION);
+    res
+}
+
+/// the original sequence first, then the other variants from the smallest
+fn variants(api_graph: &ApiGraph<'_>, sequence: &ApiSequence) -> Vec<Variant> {
+    let original = sequence
+        .functions
+        .iter()
+        .enumerate()
+        .map(|(index, api_call)| (api_call.func.1, Some(index)))
+        .collect::<Vec<_>>();
+    let call_number = original.len();
+    let mut subsets: Vec<Vec<usize>> = Vec::new();
+    if call_number <= MAX_SUBSET_CALLS {
+        for mask in 1..(1usize << call_number) - 1 {
+            subsets.push((0..call_number).filter(|index| mask & (1 << index) != 0).collect());
+        }
+    } else {
+        for removed in 0..call_number {
+            subsets.push((0..call_number).filter(|index| *index != removed).collect());
+        }
+        for end in 1..call_number - 1 {
+            subsets.push((0..end).collect());
+        }
+    }
+    let mut candidates = subsets
+        .iter()
+        .map(|subset| subset.iter().map(|index| original[*index]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    // swaps are tried on the whole sequence as well as on the subsets keeping the producer
+    let mut swapped = Vec::new();
+    for candidate in std::iter::once(&original).chain(candidates.iter()) {
+        for (position, (_, origin)) in candidate.iter().enumerate() {
+            for simpler in simpler_producers(api_graph, sequence, origin.unwrap()) {
+                let mut swap = candidate.clone();
+                swap[position] = (simpler, None);
+                swapped.push(swap);
+            }
+        }
+    }
+    candidates.extend(swapped);
+
+    let mut res = vec![Variant {
+        sequence: sequence.clone(),
+        origins: original.iter().map(|call| call.1).collect(),
+    }];
+    let mut seen = FxHashSet::default();
+    seen.insert(original.iter().map(|call| call.0).collect::<Vec<_>>());
+    let mut others = Vec::new();
+    for candidate in candidates {
+        if !seen.insert(candidate.iter().map(|call| call.0).collect::<Vec<_>>()) {
+            continue;
+        }
+        if let Some(variant) = rebuild(api_graph, &candidate) {
+            others.push(variant);
+        }
+    }
+    others.sort_by_key(|variant| {
+        (
+            variant.sequence.len(),
+            variant.sequence.fuzzable_params.len(),
+            variant.sequence._fuzzable_fixed_part_length(),
+            variant.origins.iter().filter(|origin| origin.is_none()).count(),
+        )
+    });
+    res.extend(others.into_iter().take(MAX_VARIANTS - 1));
+    res
+}
+
+/// Functions that can feed an argument of some consumer of `call` in place of it, taking fewer
+/// inputs than the function of `call`.
+fn simpler_producers(api_graph: &ApiGraph<'_>, sequence: &ApiSequence, call: usize) -> Vec<usize> {
+    let producer = sequence.functions[call].func.1;
+    let input_number = api_graph.api_functions[producer].inputs.len();
+    let mut res = Vec::new();
+    for consumer in &sequence.functions[call + 1..] {
+        for (param_index, (param_type, index, _)) in consumer.params.iter().enumerate() {
+            if *param_type != ParamType::_FunctionReturn || *index != call {
+                continue;
+            }
+            for dependency in &api_graph.api_dependencies {
+                let simpler = dependency.output_fun.1;
+                if dependency.input_fun == consumer.func
+                    && dependency.input_param_index == param_index
+                    && api_graph.api_functions[simpler].inputs.len() < input_number
+                    && !res.contains(&simpler)
+                {
+                    res.push(simpler);
+                }
+            }
+        }
+    }
+    res.sort_by_key(|simpler| (api_graph.api_functions[*simpler].inputs.len(), *simpler));
+    res.truncate(MAX_PRODUCER_SWAPS);
+    res
+}
+
+/// add the calls one by one like the sequence generation does, arguments are taken from the
+/// first fitting producer
+fn rebuild(api_graph: &ApiGraph<'_>, calls: &Vec<(usize, Option<usize>)>) -> Option<Variant> {
+    let mut sequence = ApiSequence::new();
+    for (function, _) in calls {
+        sequence = api_graph.is_fun_satisfied(&ApiType::BareFunction, *function, &sequence)?;
+    }
+    Some(Variant { sequence, origins: calls.iter().map(|call| call.1).collect() })
+}
+
+/// the fuzzable param of the original sequence a variant param stands for: same argument of
+/// the same call with the same type
+fn original_fuzzable(sequence: &ApiSequence, variant: &Variant, fuzzable: usize) -> Option<usize> {
+    for (api_call, origin) in variant.sequence.functions.iter().zip(variant.origins.iter()) {
+        let Some(position) = api_call.params.iter().position(|(param_type, index, _)| {
+            *param_type == ParamType::_FuzzableType && *index == fuzzable
+        }) else {
+            continue;
+        };
+        let (param_type, index, _) = sequence.functions[(*origin)?].params.get(position)?;
+        if *param_type == ParamType::_FuzzableType
+            && sequence.fuzzable_params[*index] == variant.sequence.fuzzable_params[fuzzable]
+        {
+            return Some(*index);
+        }
+        return None;
+    }
+    None
+}
+
+/// `FuzzShape` of the sequential reader for a fuzzable param
+fn sequential_shape(fuzzable: &FuzzableType) -> String {
+    match fuzzable {
+        FuzzableType::Primitive(_) => {
+            format!("FuzzShape::Fixed({})", fuzzable._fixed_part_length())
+        }
+        FuzzableType::RefStr => "FuzzShape::Bytes(1)".to_string(),
+        FuzzableType::RefSlice(inner) if inner._is_input_slice_element() => {
+            format!("FuzzShape::Bytes({})", inner._fixed_part_length())
+        }
+        FuzzableType::RefSlice(inner) => format!("FuzzShape::Vec(&{})", sequential_shape(inner)),
+        FuzzableType::Tuple(inners) => format!(
+            "FuzzShape::All(&[{}])",
+            inners.iter().map(sequential_shape).collect::<Vec<_>>().join(", ")
+        ),
+        FuzzableType::Struct(_, kind, fields) if *kind != 1 => format!(
+            "FuzzShape::All(&[{}])",
+            fields.iter().map(|(_, inner)| sequential_shape(inner)).collect::<Vec<_>>().join(", ")
+        ),
+        // unit structs are built without reading the input
+        FuzzableType::Struct(..) | FuzzableType::NoFuzzable => "FuzzShape::All(&[])".to_string(),
+    }
+}
+
+/// `fuzz_variant_input{index}`, laying out the original input for the variant. Sequential
+/// inputs are cut into the bytes every param read, the ones of the params kept are joined.
+fn input_function(
+    api_graph: &ApiGraph<'_>,
+    sequence: &ApiSequence,
+    variant: &Variant,
+    index: usize,
+) -> String {
+    if api_graph.input_layout == InputLayout::Sequential {
+        let original = sequence.fuzzable_params.iter().map(sequential_shape).collect::<Vec<_>>();
+        let kept = (0..variant.sequence.fuzzable_params.len())
+            .map(|fuzzable| {
+                format!(
+                    "({:?}, {})",
+                    original_fuzzable(sequence, variant, fuzzable),
+                    sequential_shape(&variant.sequence.fuzzable_params[fuzzable])
+                )
+            })
+            .collect::<Vec<_>>();
+        return format!(
+            "fn fuzz_variant_input{}(orig: &[u8]) -> Option<Vec<u8>> {{
+    fuzz_sequential_reencode(orig, &[{}], &[{}])
+}}
+",
+            index,
+            original.join(", "),
+            kept.join(", ")
+        );
+    }
+    let mut fixed_offsets = Vec::new();
+    let mut dynamic_slots = Vec::new();
+    let (mut fixed_offset, mut dynamic_slot) = (0, 0);
+    for fuzzable_param in &sequence.fuzzable_params {
+        fixed_offsets.push(fixed_offset);
+        dynamic_slots.push(dynamic_slot);
+        fixed_offset += fuzzable_param._fixed_part_length();
+        dynamic_slot += fuzzable_param._dynamic_length_param_number();
+    }
+    let mut fixed = Vec::new();
+    let mut dynamic = Vec::new();
+    for (fuzzable, fuzzable_param) in variant.sequence.fuzzable_params.iter().enumerate() {
+        let origin = original_fuzzable(sequence, variant, fuzzable);
+        let fixed_length = fuzzable_param._fixed_part_length();
+        if fixed_length > 0 {
+            let offset = origin.map(|origin| fixed_offsets[origin]);
+            fixed.push(format!("({:?}, {})", offset, fixed_length));
+        }
+        for slot in 0..fuzzable_param._dynamic_length_param_number() {
+            dynamic.push(format!("{:?}", origin.map(|origin| dynamic_slots[origin] + slot)));
+        }
+    }
+    format!(
+        "fn fuzz_variant_input{}(orig: &[u8]) -> Option<Vec<u8>> {{
+    fuzz_reencode(orig, {}, {}, &[{}], &[{}])
+}}
+",
+        index,
+        sequence._fuzzable_fixed_part_length(),
+        sequence._dynamic_length_param_number(),
+        fixed.join(", "),
+        dynamic.join(", ")
+    )
+}
@@ -0,0 +1,277 @@
+use crate::clean::{PrimitiveType, Type};
+use crate::formats::cache::Cache;
+use crate::fuzz_target::api_graph::{ApiGraph, ApiType};
+use crate::fuzz_target::api_sequence::ApiSequence;
+use crate::fuzz_target::api_util::try_type_name_from_did;
+use crate::fuzz_target::cargo_fuzz::FuzzEngine;
+use crate::fuzz_target::replay_util;
+use crate::fuzz_target::trait_impl::path_type_args;
+use rustc_data_structures::fx::FxHashMap;
+
+#[cfg(test)]
+mod tests;
+
+/// variants one driver compares
+static MAX_VARIANTS: usize = 4;
+/// differential drivers written next to the chosen sequences
+static MAX_DIFFERENTIAL_DRIVERS: usize = 20;
+
+/// Records the Debug output of a value when its type has one, autoref picks
+/// `FuzzObserveDebug` over `FuzzObserveOpaque` for such types. Once every variant ran, their
+/// results must agree unless one of them rejected the input with a `None` or `Err(..)`
+/// anywhere in its result, e.g. `Some(Err(..))` or `(None, 0)`.
+static DIFFERENTIAL_DEFINITION: &'static str = r#"struct FuzzObserve<'a, T>(&'a T);
+
+trait FuzzObserveDebug {
+    fn fuzz_observe(&self) -> Option<String>;
+}
+
+impl<T: std::fmt::Debug> FuzzObserveDebug for FuzzObserve<'_, T> {
+    fn fuzz_observe(&self) -> Option<String> {
+        Some(format!("{:?}", self.0))
+    }
+}
+
+trait FuzzObserveOpaque {
+    fn fuzz_observe(&self) -> Option<String>;
+}
+
+impl<T> FuzzObserveOpaque for &FuzzObserve<'_, T> {
+    fn fuzz_observe(&self) -> Option<String> {
+        None
+    }
+}
+
+thread_local! {
+    static FUZZ_OBSERVED: std::cell::RefCell<Vec<(usize, String)>> = Default::default();
+}
+
+fn fuzz_record(variant: usize, observed: Option<String>) {
+    if let Some(observed) = observed {
+        FUZZ_OBSERVED.with(|o| o.borrow_mut().push((variant, observed)));
+    }
+}
+
+fn fuzz_rejected(observed: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    ["None", "Err("].iter().any(|word| {
+        observed.match_indices(word).any(|(i, _)| {
+            !observed[..i].ends_with(is_ident)
+                && (word.ends_with('(') || !observed[i + word.len()..].starts_with(is_ident))
+        })
+    })
+}
+
+fn fuzz_check_agreement(variants: usize) {
+    let observed = FUZZ_OBSERVED.with(|o| std::mem::take(&mut *o.borrow_mut()));
+    if observed.len() != variants || observed.iter().any(|(_, o)| fuzz_rejected(o)) {
+        return;
+    }
+    for (variant, o) in &observed[1..] {
+        if *o != observed[0].1 {
+            let (first, expected) = &observed[0];
+            panic!("variants {} and {} disagree:\n{}\n{}", first, variant, expected, o);
+        }
+    }
+}
+"#;
+
+/// Groups of sequences, each ending in another mono function of the same generic function and
+/// reading the same fuzzable params, so one input decodes to the same arguments for all of them.
+/// The functions of a group return the same type, up to the width of its integers. Only generic
+/// functions with a comparable output are in `mono_variants`, see `is_comparable_output`.
+pub(crate) fn differential_groups(api_graph: &ApiGraph<'_>) -> Vec<Vec<ApiSequence>> {
+    // the shortest generated sequence ending in every function
+    let mut shortest: FxHashMap<usize, &ApiSequence> = FxHashMap::default();
+    for sequence in &api_graph.api_sequences {
+        let Some(last) = sequence.last_api_func_index() else {
+            continue;
+        };
+        if shortest.get(&last).map_or(true, |former| former.len() > sequence.len()) {
+            shortest.insert(last, sequence);
+        }
+    }
+    let mut res = Vec::new();
+    for variants in &api_graph.mono_variants {
+        if res.len() >= MAX_DIFFERENTIAL_DRIVERS {
+            break;
+        }
+        let mut groups: Vec<Vec<ApiSequence>> = Vec::new();
+        for variant in variants {
+            // only a return value is compared
+            if api_graph.api_functions[*variant]._has_no_output() {
+                continue;
+            }
+            let sequence = match shortest.get(variant) {
+                Some(sequence) => (*sequence).clone(),
+                None => match api_graph.is_fun_satisfied(
+                    &ApiType::BareFunction,
+                    *variant,
+                    &ApiSequence::new(),
+                ) {
+                    Some(sequence) => sequence,
+                    None => continue,
+                },
+            };
+            let output = api_graph.api_functions[*variant].output.as_ref();
+            match groups.iter_mut().find(|group| {
+                let former = group[0].last_api_func_index().unwrap();
+                group[0].fuzzable_params == sequence.fuzzable_params
+                    && same_up_to_int_width(
+                        api_graph.api_functions[former].output.as_ref().unwrap(),
+                        output.unwrap(),
+                    )
+            }) {
+                Some(group) => group.push(sequence),
+                None => groups.push(vec![sequence]),
+            }
+        }
+        let Some(mut group) = groups.into_iter().max_by_key(|group| group.len()) else {
+            continue;
+        };
+        if group.len() < 2 {
+            continue;
+        }
+        group.truncate(MAX_VARIANTS);
+        for sequence in group.iter_mut() {
+            sequence.observed = true;
+        }
+        res.push(group);
+    }
+    res
+}
+
+/// Whether the results of the mono functions of a generic function with this output can be
+/// compared: the output mentions a type param, and only inside an `Option` or `Result`, like
+/// `fn parse<F: FromStr>(&self) -> Result<F, F::Err>`. An input a narrower type cannot hold then
+/// gives `None` or `Err(..)`, which is skipped. Outputs without a type param, like the one of
+/// `size_of::<T>()`, differ for good reason, and bare ones, like the one of `Vec::<T>::new()`,
+/// wrap, truncate or agree for no good reason.
+pub(crate) fn is_comparable_output(output: Option<&Type>, cache: &Cache) -> bool {
+    let mut has_param = false;
+    output.map_or(false, |output| params_only_wrapped(output, false, &mut has_param, cache))
+        && has_param
+}
+
+fn params_only_wrapped(type_: &Type, wrapped: bool, has_param: &mut bool, cache: &Cache) -> bool {
+    match type_ {
+        Type::Generic(_) => {
+            *has_param = true;
+            wrapped
+        }
+        Type::Path { path } => {
+            let name = try_type_name_from_did(path.def_id(), cache).unwrap_or_default();
+            let wrapped =
+                wrapped || name == "std::option::Option" || name == "std::result::Result";
+            path_type_args(path).all(|arg| params_only_wrapped(arg, wrapped, has_param, cache))
+        }
+        Type::BorrowedRef { type_, .. } | Type::RawPointer(_, type_) => {
+            params_only_wrapped(type_, wrapped, has_param, cache)
+        }
+        Type::Slice(type_) | Type::Array(type_, _) => {
+            params_only_wrapped(type_, wrapped, has_param, cache)
+        }
+        Type::Tuple(types) => {
+            types.iter().all(|type_| params_only_wrapped(type_, wrapped, has_param, cache))
+        }
+        // `F::Err`
+        Type::QPath(qpath) => params_only_wrapped(&qpath.self_type, wrapped, has_param, cache),
+        _ => true,
+    }
+}
+
+fn int_signedness(primitive: &PrimitiveType) -> Option<bool> {
+    match primitive {
+        PrimitiveType::U8
+        | PrimitiveType::U16
+        | PrimitiveType::U32
+        | PrimitiveType::U64
+        | PrimitiveType::U128
+        | PrimitiveType::Usize => Some(false),
+        PrimitiveType::I8
+        | PrimitiveType::I16
+        | PrimitiveType::I32
+        | PrimitiveType::I64
+        | PrimitiveType::I128
+        | PrimitiveType::Isize => Some(true),
+        _ => None,
+    }
+}
+
+/// whether the types are equal once integers of the same signedness are taken as one, their
+/// Debug output agrees for the values all of them hold
+fn same_up_to_int_width(a: &Type, b: &Type) -> bool {
+    match (a, b) {
+        (Type::Primitive(a), Type::Primitive(b)) => {
+            a == b || int_signedness(a).is_some() && int_signedness(a) == int_signedness(b)
+        }
+        (
+            Type::BorrowedRef { mutability: a_mut, type_: a, .. },
+            Type::BorrowedRef { mutability: b_mut, type_: b, .. },
+        ) => a_mut == b_mut && same_up_to_int_width(a, b),
+        (Type::Slice(a), Type::Slice(b)) => same_up_to_int_width(a, b),
+        (Type::Array(a, a_len), Type::Array(b, b_len)) => {
+            a_len == b_len && same_up_to_int_width(a, b)
+        }
+        (Type::Tuple(a), Type::Tuple(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_up_to_int_width(a, b))
+        }
+        (Type::Path { path: a }, Type::Path { path: b }) => {
+            let a_args = path_type_args(a).collect::<Vec<_>>();
+            let b_args = path_type_args(b).collect::<Vec<_>>();
+            a.def_id() == b.def_id()
+                && a_args.len() == b_args.len()
+                && a_args.iter().zip(b_args).all(|(a, b)| same_up_to_int_width(a, b))
+        }
+        _ => a == b,
+    }
+}
+
+/// Driver feeding the input to every variant and asserting that their results agree. The
+/// replay form reads the input from a crash file instead.
+pub(crate) fn differential_file(
+    api_graph: &ApiGraph<'_>,
+    group: &[ApiSequence],
+    replay: bool,
+) -> String {
+    let sequences = group.iter().collect::<Vec<_>>();
+    let mut res = ApiSequence::driver_prelude(api_graph, &sequences);
+    if replay {
+        res = res.replace(api_graph.engine.header(), "");
+    }
+    res.push_str(DIFFERENTIAL_DEFINITION);
+    res.push('\n');
+    for (variant, sequence) in group.iter().enumerate() {
+        res.push_str(&sequence.to_well_written_function(api_graph, variant, 0));
+        res.push('\n');
+        res.push_str(&format!("fn fuzz_variant{}(data: &[u8]) {{\n", variant));
+        res.push_str(&sequence.afl_closure_body(api_graph, 0, variant));
+        res.push_str("}\n\n");
+    }
+    let checks = (0..group.len())
+        .map(|variant| format!("fuzz_variant{}(data);\n", variant))
+        .chain(std::iter::once(format!("fuzz_check_agreement({});\n", group.len())))
+        .collect::<Vec<_>>();
+    if replay {
+        res.push_str(replay_util::_read_crash_file_data());
+        res.push('\n');
+        res.push_str("fn main() {\n    let _content = _read_data();\n    let data = &_content;\n");
+        for check in checks {
+            res.push_str(&format!("    {}", check));
+        }
+        res.push_str("}\n");
+    } else if api_graph.engine == FuzzEngine::Libfuzzer {
+        res.push_str("fuzz_target!(|data: &[u8]| {\n");
+        for check in checks {
+            res.push_str(&format!("    {}", check));
+        }
+        res.push_str("});\n");
+    } else {
+        res.push_str("fn main() {\n    fuzz!(|data: &[u8]| {\n");
+        for check in checks {
+            res.push_str(&format!("        {}", check));
+        }
+        res.push_str("    });\n}\n");
+    }
+    res
+}
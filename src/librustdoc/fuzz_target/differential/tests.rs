@@ -0,0 +1,64 @@
+use super::is_comparable_output;
+use crate::clean::{GenericArg, GenericArgs, Path, PathSegment, PrimitiveType, QPathData, Type};
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex};
+use rustc_span::create_default_session_globals_then;
+use rustc_span::Symbol;
+use thin_vec::{thin_vec, ThinVec};
+
+fn external_path(cache: &mut Cache, index: u32, name: &str, args: Vec<Type>) -> Type {
+    let did = DefId { krate: CrateNum::from_u32(1), index: DefIndex::from_u32(index) };
+    let syms = name.split("::").map(Symbol::intern).collect::<Vec<_>>();
+    cache.external_paths.insert(did, (syms, ItemType::Enum));
+    let args = args.into_iter().map(GenericArg::Type).collect::<Vec<_>>().into_boxed_slice();
+    Type::Path {
+        path: Path {
+            res: Res::Def(DefKind::Enum, did),
+            segments: thin_vec![PathSegment {
+                name: Symbol::intern(name.rsplit("::").next().unwrap()),
+                args: GenericArgs::AngleBracketed { args, constraints: ThinVec::new() },
+            }],
+        },
+    }
+}
+
+#[test]
+fn test_comparable_outputs() {
+    create_default_session_globals_then(|| {
+        let mut cache = Cache::new(false, false);
+        let f = Type::Generic(Symbol::intern("F"));
+        // `F::Err`
+        let f_err = Type::QPath(Box::new(QPathData {
+            assoc: PathSegment {
+                name: Symbol::intern("Err"),
+                args: GenericArgs::AngleBracketed {
+                    args: Vec::new().into_boxed_slice(),
+                    constraints: ThinVec::new(),
+                },
+            },
+            self_type: f.clone(),
+            should_show_cast: false,
+            trait_: None,
+        }));
+        let usize_ = Type::Primitive(PrimitiveType::Usize);
+        let result = external_path(&mut cache, 0, "core::result::Result", vec![f.clone(), f_err]);
+        let option = external_path(&mut cache, 1, "core::option::Option", vec![f.clone()]);
+        let tuple = Type::Tuple(vec![option.clone(), usize_.clone()]);
+        let vec = external_path(&mut cache, 2, "alloc::vec::Vec", vec![f.clone()]);
+        let option_vec = external_path(&mut cache, 1, "core::option::Option", vec![vec.clone()]);
+
+        // str::parse::<F>
+        assert!(is_comparable_output(Some(&result), &cache));
+        assert!(is_comparable_output(Some(&option), &cache));
+        assert!(is_comparable_output(Some(&tuple), &cache));
+        assert!(is_comparable_output(Some(&option_vec), &cache));
+        // size_of::<F>
+        assert!(!is_comparable_output(Some(&usize_), &cache));
+        assert!(!is_comparable_output(None, &cache));
+        // Vec::<F>::new
+        assert!(!is_comparable_output(Some(&vec), &cache));
+        assert!(!is_comparable_output(Some(&f), &cache));
+    });
+}
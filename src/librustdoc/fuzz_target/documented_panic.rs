@@ -0,0 +1,219 @@
+use crate::clean;
+use crate::clean::PrimitiveType;
+use crate::fuzz_target::api_graph::ApiGraph;
+use crate::fuzz_target::api_sequence::{ApiSequence, ParamType};
+use crate::fuzz_target::api_util;
+use crate::fuzz_target::fuzzable_type::FuzzableType;
+use crate::TyCtxt;
+
+/// comparisons a guard can be written with, the two character ones first
+static GUARD_OPERATORS: &'static [&'static str] = &[">=", "<=", "==", "!=", ">", "<"];
+
+/// A call whose doc has a `# Panics` section runs between `fuzz_documented_enter` and
+/// `fuzz_documented_exit`. A panic raised in between is the documented one if it is located in
+/// the body of the called function: the panic hook stays quiet about it, and
+/// `fuzz_documented_expected` tells it from other panics once caught, so it has to be the first
+/// check run on a caught panic. Panics of the functions it calls are still findings.
+static DOCUMENTED_PANIC_DEFINITION: &'static str = r#"thread_local! {
+    static FUZZ_DOCUMENTED_CALL: std::cell::Cell<Option<(&'static str, u32, u32)>> =
+        std::cell::Cell::new(None);
+    static FUZZ_DOCUMENTED_PANIC: std::cell::Cell<bool> = std::cell::Cell::new(false);
+}
+
+/// whether two paths name the same file, one may be relative to a directory of the other
+fn fuzz_same_file(a: &str, b: &str) -> bool {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    long.ends_with(short)
+        && (long.len() == short.len() || long[..long.len() - short.len()].ends_with('/'))
+}
+
+fn fuzz_documented_enter(file: &'static str, first_line: u32, last_line: u32) {
+    static HOOK: std::sync::Once = std::sync::Once::new();
+    HOOK.call_once(|| {
+        let prev = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let documented = match (FUZZ_DOCUMENTED_CALL.with(|call| call.get()), info.location()) {
+                (Some((file, first_line, last_line)), Some(location)) => {
+                    fuzz_same_file(&location.file().replace('\\', "/"), file)
+                        && location.line() >= first_line
+                        && location.line() <= last_line
+                }
+                _ => false,
+            };
+            FUZZ_DOCUMENTED_PANIC.with(|panic| panic.set(documented));
+            if !documented {
+                prev(info);
+            }
+        }));
+    });
+    FUZZ_DOCUMENTED_CALL.with(|call| call.set(Some((file, first_line, last_line))));
+    FUZZ_DOCUMENTED_PANIC.with(|panic| panic.set(false));
+}
+
+fn fuzz_documented_exit() {
+    FUZZ_DOCUMENTED_CALL.with(|call| call.set(None));
+}
+
+fn fuzz_documented_expected(_err: &Box<dyn std::any::Any + Send>) -> bool {
+    FUZZ_DOCUMENTED_CALL.with(|call| call.set(None));
+    FUZZ_DOCUMENTED_PANIC.with(|panic| panic.replace(false))
+}
+"#;
+
+/// The `# Panics` section of the doc of a function
+#[derive(Clone, Debug)]
+pub(crate) struct PanicsDoc {
+    pub(crate) section: String,
+    /// file, first and last line of the function, where the documented panics are raised
+    pub(crate) location: Option<(String, usize, usize)>,
+    /// conditions on the arguments the section says the function panics on
+    pub(crate) guards: Vec<PanicGuard>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum GuardOperand {
+    Input(usize), // index of the argument
+    Literal(i128),
+}
+
+/// `lhs op rhs`, written in a code span of the section, e.g. "Panics if `index >= len`"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PanicGuard {
+    pub(crate) lhs: GuardOperand,
+    pub(crate) op: &'static str,
+    pub(crate) rhs: GuardOperand,
+}
+
+/// The `# Panics` section of the doc of `item`, a function taking `inputs`. Functions of other
+/// crates have no location, their panics are not told apart from others.
+pub(crate) fn panics_doc(
+    tcx: TyCtxt<'_>,
+    item: &clean::Item,
+    inputs: &clean::Arguments,
+) -> Option<PanicsDoc> {
+    let section = api_util::doc_section(&item.doc_value(), "Panics")?;
+    let location = item.item_id.as_def_id().and_then(|did| did.as_local()).map(|did| {
+        let span = tcx.hir().span_with_body(tcx.local_def_id_to_hir_id(did));
+        let source_map = tcx.sess.source_map();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        (lo.file.name.prefer_local().to_string().replace('\\', "/"), lo.line, hi.line)
+    });
+    let names = inputs.values.iter().map(|input| input.name.to_string()).collect::<Vec<_>>();
+    let guards = guards(&section, &names);
+    Some(PanicsDoc { section, location, guards })
+}
+
+/// the conditions in code spans of the section comparing arguments with each other or with
+/// integer literals
+pub(crate) fn guards(section: &str, names: &[String]) -> Vec<PanicGuard> {
+    section
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .filter_map(|code| {
+            let op = GUARD_OPERATORS.iter().find(|op| code.contains(**op))?;
+            let (lhs, rhs) = code.split_once(op)?;
+            let lhs = guard_operand(lhs.trim(), names)?;
+            let rhs = guard_operand(rhs.trim(), names)?;
+            // literals alone say nothing about the arguments
+            if !matches!(lhs, GuardOperand::Input(_)) && !matches!(rhs, GuardOperand::Input(_)) {
+                return None;
+            }
+            Some(PanicGuard { lhs, op, rhs })
+        })
+        .collect()
+}
+
+fn guard_operand(operand: &str, names: &[String]) -> Option<GuardOperand> {
+    if let Some(index) = names.iter().position(|name| name == operand) {
+        return Some(GuardOperand::Input(index));
+    }
+    operand.replace('_', "").parse::<i128>().ok().map(GuardOperand::Literal)
+}
+
+pub(crate) fn definition() -> String {
+    let mut res = DOCUMENTED_PANIC_DEFINITION.to_string();
+    res.push('\n');
+    res
+}
+
+/// where the `i`th call of the sequence raises the panics its doc documents, if they are to be
+/// no findings
+pub(crate) fn documented_location<'a>(
+    api_graph: &'a ApiGraph<'_>,
+    sequence: &ApiSequence,
+    i: usize,
+) -> Option<&'a (String, usize, usize)> {
+    if !api_graph.expect_documented_panics {
+        return None;
+    }
+    let panics_doc = api_graph.api_functions[sequence.functions[i].func.1].panics_doc.as_ref()?;
+    panics_doc.location.as_ref()
+}
+
+/// whether the `i`th call of the sequence may panic as its doc says, without a finding
+pub(crate) fn is_documented_call(
+    api_graph: &ApiGraph<'_>,
+    sequence: &ApiSequence,
+    i: usize,
+) -> bool {
+    documented_location(api_graph, sequence, i).is_some()
+}
+
+/// Statements skipping the rest of the run before the `i`th call when its arguments meet a
+/// condition its doc says it panics on. Only conditions on integer arguments read from the input
+/// are checked, compared as `i128`.
+pub(crate) fn guard_statements(
+    api_graph: &ApiGraph<'_>,
+    sequence: &ApiSequence,
+    i: usize,
+    indent: &str,
+    param_prefix: &str,
+) -> String {
+    let api_call = &sequence.functions[i];
+    let Some(panics_doc) = api_graph.api_functions[api_call.func.1].panics_doc.as_ref() else {
+        return String::new();
+    };
+    if !api_graph.expect_documented_panics {
+        return String::new();
+    }
+    let operand = |operand: &GuardOperand| match *operand {
+        GuardOperand::Literal(value) => Some(format!("{}i128", value)),
+        GuardOperand::Input(input) => match api_call.params.get(input)? {
+            (ParamType::_FuzzableType, index, _) => match sequence.fuzzable_params[*index] {
+                // `u128` values do not fit
+                FuzzableType::Primitive(
+                    PrimitiveType::U8
+                    | PrimitiveType::I8
+                    | PrimitiveType::U16
+                    | PrimitiveType::I16
+                    | PrimitiveType::U32
+                    | PrimitiveType::I32
+                    | PrimitiveType::U64
+                    | PrimitiveType::I64
+                    | PrimitiveType::Usize
+                    | PrimitiveType::Isize
+                    | PrimitiveType::I128,
+                ) => Some(format!("({}{} as i128)", param_prefix, index)),
+                _ => None,
+            },
+            _ => None,
+        },
+    };
+    let mut res = String::new();
+    for guard in &panics_doc.guards {
+        if let (Some(lhs), Some(rhs)) = (operand(&guard.lhs), operand(&guard.rhs)) {
+            res.push_str(&format!("{}if {} {} {} {{ return; }}\n", indent, lhs, guard.op, rhs));
+        }
+    }
+    res
+}
+
+/// whether any call of the sequence may panic as its doc says
+pub(crate) fn expects_documented_panics(
+    api_graph: &ApiGraph<'_>,
+    sequence: &ApiSequence,
+) -> bool {
+    (0..sequence.len()).any(|i| is_documented_call(api_graph, sequence, i))
+}
@@ -0,0 +1,159 @@
+use crate::fuzz_target::runtime_crate::RUNTIME_CRATE_NAME;
+use std::fs;
+use std::path::PathBuf;
+
+/// member crate of the workspace holding every driver as a binary
+static DRIVERS_CRATE: &'static str = "drivers";
+static AFL_VERSION: &'static str = "0.15";
+
+/// crates the drivers may use besides the tested one, with the version taken when the tested
+/// crate does not lock one
+static DRIVER_DEPENDENCIES: &'static [(&'static str, &'static str)] =
+    &[("serde_json", "1"), ("arbitrary", "1")];
+
+/// A generated file built as a binary of the drivers crate, the path is relative to the output
+/// directory
+pub(crate) struct DriverBin {
+    pub(crate) name: String,
+    pub(crate) path: String,
+}
+
+/// Dependency line on the tested crate for a crate two directories below its root, with the
+/// features rustdoc saw enabled. The crate is found through `CARGO_PKG_NAME` and
+/// `CARGO_MANIFEST_DIR`, which `rumono gen` sets to the package of the crate, also when it is a
+/// member of the workspace `gen` runs in, else it is assumed to be named after the crate and to
+/// hold `fuzz_target`.
+pub(crate) fn crate_dependency(crate_name: &str, features: &[String]) -> String {
+    let package = std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| crate_name.to_string());
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| "../..".to_string());
+    let features = features.iter().map(|feature| format!("\"{}\"", feature)).collect::<Vec<_>>();
+    format!(
+        "{crate_name} = {{ path = \"{crate_dir}\", package = \"{package}\", default-features = false, features = [{features}] }}\n",
+        crate_name = crate_name,
+        crate_dir = crate_dir,
+        package = package,
+        features = features.join(", ")
+    )
+}
+
+/// Version of `name` in the `Cargo.lock` of the tested crate, found from `CARGO_MANIFEST_DIR`
+/// up. A crate locked in several versions is taken in the one the tested package depends on.
+fn locked_version(name: &str) -> Option<String> {
+    let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").ok()?);
+    let lockfile =
+        crate_dir.ancestors().map(|dir| dir.join("Cargo.lock")).find(|path| path.exists())?;
+    let content = fs::read_to_string(lockfile).ok()?;
+    let package = std::env::var("CARGO_PKG_NAME").ok()?;
+    let mut versions = Vec::new();
+    let mut depended = None;
+    for entry in content.split("[[package]]").skip(1) {
+        let field = |key: &str| {
+            entry.lines().find_map(|line| {
+                line.strip_prefix(key)?.strip_prefix(" = \"")?.strip_suffix('"')
+            })
+        };
+        if field("name") == Some(name) {
+            versions.extend(field("version").map(str::to_string));
+        } else if field("name") == Some(package.as_str()) {
+            // `"name version"` is only written out when several versions are locked
+            let prefix = format!("\"{} ", name);
+            depended = entry.lines().find_map(|line| {
+                let rest = line.trim().strip_prefix(&prefix)?;
+                rest.split(|c| c == '"' || c == ' ').next().map(str::to_string)
+            });
+        }
+    }
+    match versions.as_slice() {
+        [] => None,
+        [version] => Some(version.clone()),
+        _ => depended,
+    }
+}
+
+/// Dependency lines of the crates in `DRIVER_DEPENDENCIES` that any of the drivers calls into,
+/// the tested crate is depended on already. A crate the tested one locks is required in that
+/// exact version, so e.g. its `Arbitrary` impls are impls of the trait the drivers name.
+pub(crate) fn driver_dependencies(crate_name: &str, contents: &[&String]) -> String {
+    let mut res = String::new();
+    for (name, version) in DRIVER_DEPENDENCIES {
+        let path = format!("{}::", name);
+        if *name != crate_name && contents.iter().any(|content| content.contains(&path)) {
+            let version = match locked_version(name) {
+                Some(locked) => format!("={}", locked),
+                None => version.to_string(),
+            };
+            res.push_str(&format!("{} = \"{}\"\n", name, version));
+        }
+    }
+    res
+}
+
+/// `Cargo.toml` of the workspace, its `target` directory is shared by all drivers so the tested
+/// crate builds once
+fn workspace_manifest(runtime_crate: bool) -> String {
+    let mut members = vec![format!("\"{}\"", DRIVERS_CRATE)];
+    if runtime_crate {
+        members.push(format!("\"{}\"", RUNTIME_CRATE_NAME));
+    }
+    format!("[workspace]\nmembers = [{}]\nresolver = \"2\"\n", members.join(", "))
+}
+
+/// `Cargo.toml` of the drivers crate with a `[[bin]]` for every generated file
+fn drivers_manifest(
+    crate_name: &str,
+    features: &[String],
+    bins: &[DriverBin],
+    afl: bool,
+    dependencies: &str,
+    runtime_crate: bool,
+) -> String {
+    let mut res = format!(
+        "[package]\nname = \"{crate_name}-{drivers}\"\nversion = \"0.0.0\"\npublish = false\nedition = \"2021\"\n\n",
+        crate_name = crate_name,
+        drivers = DRIVERS_CRATE
+    );
+    res.push_str("[dependencies]\n");
+    if afl {
+        res.push_str(&format!("afl = \"{}\"\n", AFL_VERSION));
+    }
+    res.push_str(dependencies);
+    res.push_str(&crate_dependency(crate_name, features));
+    if runtime_crate {
+        res.push_str(&format!(
+            "{name} = {{ path = \"../{name}\" }}\n",
+            name = RUNTIME_CRATE_NAME
+        ));
+    }
+    for bin in bins {
+        res.push_str(&format!(
+            "\n[[bin]]\nname = \"{name}\"\npath = \"../{path}\"\ntest = false\ndoc = false\nbench = false\n",
+            name = bin.name,
+            path = bin.path
+        ));
+    }
+    res
+}
+
+/// Write a workspace into `dir` whose drivers crate builds the generated files in place, so
+/// `cargo afl build` (or `cargo build` for the replay files) builds every driver at once.
+/// `afl` tells whether the fuzzing drivers are among the binaries, `dependencies` are the lines
+/// of `driver_dependencies`.
+pub(crate) fn write_workspace(
+    dir: &PathBuf,
+    crate_name: &str,
+    features: &[String],
+    bins: &[DriverBin],
+    afl: bool,
+    dependencies: &str,
+    runtime_crate: bool,
+) {
+    let drivers_dir = dir.join(DRIVERS_CRATE);
+    println!("workspace_path: {dir:?}");
+    fs::create_dir_all(&drivers_dir).unwrap();
+    fs::write(dir.join("Cargo.toml"), workspace_manifest(runtime_crate)).unwrap();
+    fs::write(
+        drivers_dir.join("Cargo.toml"),
+        drivers_manifest(crate_name, features, bins, afl, dependencies, runtime_crate),
+    )
+    .unwrap();
+}
@@ -0,0 +1,266 @@
+use crate::clean::types::QPathData;
+use crate::clean::{GenericArg, GenericArgs, Path, PathSegment, PrimitiveType, Type};
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::fuzz_target::api_function::{ApiFunction, ApiUnsafety};
+use crate::fuzz_target::helper_type::{find_external_trait, simple_path};
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{DefId, DefIndex, LOCAL_CRATE};
+use rustc_hir::Mutability;
+use rustc_span::Symbol;
+use thin_vec::ThinVec;
+
+pub(crate) static EVIL_TYPE_NAME: &'static str = "FuzzEvil";
+
+/// traits implemented by `EVIL_DEFINITION`, iterators yield `u8` and keys borrow as `u8`
+static EVIL_TRAITS: &[&str] = &[
+    "core::fmt::Debug",
+    "core::clone::Clone",
+    "core::default::Default",
+    "core::cmp::PartialEq",
+    "core::cmp::Eq",
+    "core::cmp::PartialOrd",
+    "core::cmp::Ord",
+    "core::hash::Hash",
+    "core::iter::traits::iterator::Iterator",
+    "core::iter::traits::exact_size::ExactSizeIterator",
+    "core::borrow::Borrow",
+];
+
+static ITERATOR_TRAIT: &'static str = "core::iter::traits::iterator::Iterator";
+static BORROW_TRAIT: &'static str = "core::borrow::Borrow";
+
+/// Every method asks the global byte pool whether to misbehave, so one input can make
+/// `Ord` inconsistent, `Hash` unstable, `ExactSizeIterator::len` lie, `Borrow` hand out another
+/// key than the one `Eq` and `Hash` use, `Clone` or `Drop` panic.
+/// Such panics carry `FuzzEvilPanic` and are swallowed by the driver, other panics are not.
+static EVIL_DEFINITION: &'static str = r#"struct FuzzEvilPanic;
+
+thread_local! {
+    static FUZZ_EVIL_DATA: std::cell::RefCell<(Vec<u8>, usize)> = std::cell::RefCell::new((Vec::new(), 0));
+}
+
+fn fuzz_evil_byte() -> u8 {
+    FUZZ_EVIL_DATA.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.0.is_empty() {
+            return 0;
+        }
+        let byte = pool.0[pool.1 % pool.0.len()];
+        pool.1 = pool.1.wrapping_add(1);
+        byte
+    })
+}
+
+fn fuzz_evil_misbehave() -> bool {
+    fuzz_evil_byte() % 8 == 0
+}
+
+fn fuzz_evil_panic(msg: &str) {
+    if fuzz_evil_byte() == u8::MAX && !std::thread::panicking() {
+        println!("FuzzEvil: {}", msg);
+        std::panic::panic_any(FuzzEvilPanic);
+    }
+}
+
+fn fuzz_evil_expected(err: &Box<dyn std::any::Any + Send>) -> bool {
+    err.is::<FuzzEvilPanic>()
+}
+
+#[derive(Debug)]
+struct FuzzEvil {
+    key: u8,
+    remain: usize,
+}
+
+impl FuzzEvil {
+    fn new(data: &[u8]) -> Self {
+        static HOOK: std::sync::Once = std::sync::Once::new();
+        HOOK.call_once(|| {
+            let prev = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                if !info.payload().is::<FuzzEvilPanic>() {
+                    prev(info);
+                }
+            }));
+        });
+        FUZZ_EVIL_DATA.with(|pool| *pool.borrow_mut() = (data.to_vec(), 0));
+        FuzzEvil { key: fuzz_evil_byte(), remain: fuzz_evil_byte() as usize }
+    }
+}
+
+impl Clone for FuzzEvil {
+    fn clone(&self) -> Self {
+        fuzz_evil_panic("clone panics");
+        FuzzEvil { key: self.key, remain: self.remain }
+    }
+}
+
+impl Drop for FuzzEvil {
+    fn drop(&mut self) {
+        fuzz_evil_panic("drop panics");
+    }
+}
+
+impl Default for FuzzEvil {
+    fn default() -> Self {
+        FuzzEvil { key: fuzz_evil_byte(), remain: 0 }
+    }
+}
+
+impl PartialEq for FuzzEvil {
+    fn eq(&self, other: &Self) -> bool {
+        if fuzz_evil_misbehave() { fuzz_evil_byte() & 1 == 1 } else { self.key == other.key }
+    }
+}
+
+impl Eq for FuzzEvil {}
+
+impl PartialOrd for FuzzEvil {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if fuzz_evil_misbehave() { None } else { Some(self.cmp(other)) }
+    }
+}
+
+impl Ord for FuzzEvil {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if fuzz_evil_misbehave() {
+            match fuzz_evil_byte() % 3 {
+                0 => std::cmp::Ordering::Less,
+                1 => std::cmp::Ordering::Equal,
+                _ => std::cmp::Ordering::Greater,
+            }
+        } else {
+            self.key.cmp(&other.key)
+        }
+    }
+}
+
+impl std::hash::Hash for FuzzEvil {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::hash::Hash::hash(&self.key, state);
+        if fuzz_evil_misbehave() {
+            std::hash::Hash::hash(&fuzz_evil_byte(), state);
+        }
+    }
+}
+
+impl Iterator for FuzzEvil {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.remain == 0 {
+            return None;
+        }
+        self.remain -= 1;
+        Some(fuzz_evil_byte())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for FuzzEvil {
+    fn len(&self) -> usize {
+        if fuzz_evil_misbehave() { fuzz_evil_byte() as usize } else { self.remain }
+    }
+}
+
+static FUZZ_EVIL_KEYS: [u8; 256] = {
+    let mut keys = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        keys[i] = i as u8;
+        i += 1;
+    }
+    keys
+};
+
+impl std::borrow::Borrow<u8> for FuzzEvil {
+    fn borrow(&self) -> &u8 {
+        if fuzz_evil_misbehave() { &FUZZ_EVIL_KEYS[fuzz_evil_byte() as usize] } else { &self.key }
+    }
+}
+"#;
+
+/// Fake def id of the evil type. It is only known by `cache.paths`.
+pub(crate) fn evil_did() -> DefId {
+    DefId { krate: LOCAL_CRATE, index: DefIndex::from_u32(DefIndex::MAX_AS_U32 - 1) }
+}
+
+/// make the evil type nameable like other local types
+pub(crate) fn register_evil_type(cache: &mut Cache) {
+    cache.paths.insert(evil_did(), (vec![Symbol::intern(EVIL_TYPE_NAME)], ItemType::Struct));
+}
+
+pub(crate) fn evil_type() -> Type {
+    Type::Path { path: simple_path(Res::Def(DefKind::Struct, evil_did()), Symbol::intern(EVIL_TYPE_NAME)) }
+}
+
+/// Paths of the traits implemented by the evil type, traits never mentioned by the crate are
+/// missing from the cache and skipped. Iterators come with their `Item`, `Borrow` with its `u8`.
+pub(crate) fn evil_traits(cache: &Cache) -> Vec<(Path, Vec<(QPathData, Type)>)> {
+    let mut res = Vec::new();
+    for name in EVIL_TRAITS.iter() {
+        let Some(mut trait_) = find_external_trait(name, cache) else {
+            continue;
+        };
+        if *name == BORROW_TRAIT {
+            trait_.segments.last_mut().unwrap().args = GenericArgs::AngleBracketed {
+                args: Box::new([GenericArg::Type(Type::Primitive(PrimitiveType::U8))]),
+                constraints: ThinVec::new(),
+            };
+        }
+        let mut assoc_items = Vec::new();
+        if *name == ITERATOR_TRAIT {
+            assoc_items.push((
+                QPathData {
+                    assoc: PathSegment {
+                        name: Symbol::intern("Item"),
+                        args: GenericArgs::AngleBracketed {
+                            args: Box::new([]),
+                            constraints: ThinVec::new(),
+                        },
+                    },
+                    self_type: evil_type(),
+                    should_show_cast: false,
+                    trait_: Some(trait_.clone()),
+                },
+                Type::Primitive(PrimitiveType::U8),
+            ));
+        }
+        res.push((trait_, assoc_items));
+    }
+    res
+}
+
+/// `FuzzEvil::new(&[u8]) -> FuzzEvil`, the bytes drive all later misbehavior
+pub(crate) fn constructor() -> ApiFunction {
+    ApiFunction {
+        name: "new".to_string(),
+        full_path: EVIL_TYPE_NAME.to_string(),
+        inputs: vec![Type::BorrowedRef {
+            lifetime: None,
+            mutability: Mutability::Not,
+            type_: Box::new(Type::Slice(Box::new(Type::Primitive(PrimitiveType::U8)))),
+        }],
+        output: Some(evil_type()),
+        self_: Some(evil_type()),
+        trait_: None,
+        _unsafe_tag: ApiUnsafety::Normal,
+        trivial_safety: false,
+        panics_doc: None,
+        mono: false,
+        local: false,
+        rpg_local: false,
+    }
+}
+
+/// source of the evil type in driver
+pub(crate) fn definition() -> String {
+    let mut res = EVIL_DEFINITION.to_string();
+    res.push('\n');
+    res
+}
@@ -1,4 +1,17 @@
 use crate::fuzz_target::api_graph::ApiGraph;
+use crate::fuzz_target::cargo_fuzz::{self, FuzzEngine};
+use crate::fuzz_target::coverage_feedback;
+use crate::fuzz_target::crash_minimizer;
+use crate::fuzz_target::differential;
+use crate::fuzz_target::driver_workspace::{self, DriverBin};
+use crate::fuzz_target::multiplex;
+use crate::fuzz_target::op_loop;
+use crate::fuzz_target::regression;
+use crate::fuzz_target::round_trip;
+use crate::fuzz_target::runtime_crate;
+use crate::fuzz_target::seed_corpus;
+use crate::fuzz_target::trait_law;
+use crate::fuzz_target::unsafe_policy::UnsafePolicy;
 use lazy_static::lazy_static;
 use rustc_data_structures::fx::{FxHashMap};
 use std::fs;
@@ -46,17 +59,6 @@
     };
 }
 
-lazy_static! {
-    static ref LIBFUZZER_FUZZ_TARGET_DIR: FxHashMap<&'static str, &'static str> = {
-        let mut m = FxHashMap::default();
-        m.insert("url", "/home/jjf/libfuzzer_work/url-libfuzzer-targets");
-        m.insert("regex_syntax", "/home/jjf/libfuzzer_work/regex-syntax-libfuzzer-targets");
-        m.insert("syn", "/home/jjf/libfuzzer_work/syn-libfuzzer-targets");
-        m.insert("semver_parser", "/home/jjf/libfuzzer_work/sem-libfuzzer-targets");
-        m
-    };
-}
-
 lazy_static! {
     static ref RANDOM_TEST_FILE_NUMBERS: FxHashMap<&'static str, usize> = {
         let mut m = FxHashMap::default();
@@ -69,10 +71,19 @@
 
 static _TEST_FILE_DIR: &'static str = "test_files";
 static _REPRODUCE_FILE_DIR: &'static str = "replay_files";
-static _LIBFUZZER_DIR_NAME: &'static str = "libfuzzer_files";
+static _MINIMIZE_FILE_DIR: &'static str = "minimize_files";
+static REGRESSION_FILE_DIR: &'static str = "regression_files";
+static SEED_DIR: &'static str = "seeds";
 static MAX_TEST_FILE_NUMBER: usize = 300;
 static DEFAULT_RANDOM_FILE_NUMBER: usize = 100;
 
+/// directory that all generated files are written into
+pub(crate) fn output_dir() -> PathBuf {
+    let mut dir = std::env::current_dir().unwrap();
+    dir.push("fuzz_target");
+    dir
+}
+
 pub(crate) fn can_write_to_file(crate_name: &String, random_strategy: bool) -> bool {
     true
     /* if !random_strategy && CRATE_TEST_DIR.contains_key(crate_name.as_str()) {
@@ -86,34 +97,43 @@ pub(crate) fn can_write_to_file(crate_name: &String, random_strategy: bool) -> b
     return false; */
 }
 
-pub(crate) fn can_generate_libfuzzer_target(crate_name: &String) -> bool {
-    if LIBFUZZER_FUZZ_TARGET_DIR.contains_key(crate_name.as_str()) {
-        return true;
-    } else {
-        return false;
-    }
-}
-
 #[derive(Debug, Clone)]
 pub(crate) struct FileHelper {
     pub(crate) crate_name: String,
     pub(crate) test_dir: PathBuf,
     pub(crate) test_files: Vec<String>,
     pub(crate) reproduce_files: Vec<String>,
-    pub(crate) libfuzzer_files: Vec<String>,
+    pub(crate) unsafe_test_files: Vec<String>, // drivers isolated for calling unsafe code
+    pub(crate) unsafe_reproduce_files: Vec<String>,
+    pub(crate) differential_test_files: Vec<String>, // drivers comparing mono variants
+    pub(crate) differential_reproduce_files: Vec<String>,
+    pub(crate) multiplexed_test_files: Vec<String>, // the driver running all other sequences
+    pub(crate) multiplexed_reproduce_files: Vec<String>,
+    pub(crate) drivers: Vec<(String, Vec<String>)>, // test file name and its api sequence
+    pub(crate) minimizer: Option<(String, String)>,  // file name and content of the minimizer
+    pub(crate) regression: Option<(String, String)>, // same for the crash to test converter
+    pub(crate) runtime_crate: bool,                  // drivers depend on the runtime crate
+    pub(crate) engine: FuzzEngine,                   // how test files are fuzzed
+    pub(crate) features: Vec<String>,                // features the drivers build the crate with
+    pub(crate) seeds: Vec<(String, Vec<Vec<u8>>)>,   // driver name and its seed inputs
 }
 
 impl FileHelper {
     pub(crate) fn new(api_graph: &ApiGraph<'_>, random_strategy: bool) -> Self {
         let crate_name = api_graph._crate_name.clone();
-        let mut test_dir=std::env::current_dir().unwrap();
-        test_dir.push("fuzz_target");
+        let test_dir = output_dir();
         let mut sequence_count = 0;
         let mut test_files = Vec::new();
         let mut reproduce_files = Vec::new();
-        let mut libfuzzer_files = Vec::new();
+        let mut unsafe_count = 0;
+        let mut unsafe_test_files = Vec::new();
+        let mut unsafe_reproduce_files = Vec::new();
+        let mut drivers = Vec::new();
+        let mut minimizer = None;
+        let mut regression = None;
+        let mut seeds = Vec::new();
         //let chosen_sequences = api_graph._naive_choose_sequence(MAX_TEST_FILE_NUMBER);
-        let chosen_sequences = if !random_strategy {
+        let mut chosen_sequences = if !random_strategy {
             api_graph._heuristic_choose(MAX_TEST_FILE_NUMBER, true)
         } else {
             let random_size = if RANDOM_TEST_FILE_NUMBERS.contains_key(crate_name.as_str()) {
@@ -124,56 +144,277 @@ pub(crate) fn new(api_graph: &ApiGraph<'_>, random_strategy: bool) -> Self {
             api_graph._first_choose(random_size)
         };
         //println!("chosen sequences number: {}", chosen_sequences.len());
+        chosen_sequences.truncate(MAX_TEST_FILE_NUMBER);
+        if api_graph.op_loop {
+            let op_loop_sequences = op_loop::op_loop_sequences(api_graph, &chosen_sequences);
+            println!("[OpLoop] {} op loop drivers", op_loop_sequences.len());
+            chosen_sequences.extend(op_loop_sequences);
+        }
+        if api_graph.trait_laws {
+            let law_check_sequences = trait_law::law_check_sequences(api_graph);
+            println!("[TraitLaw] {} law check drivers", law_check_sequences.len());
+            chosen_sequences.extend(law_check_sequences);
+        }
+        if api_graph.round_trip {
+            let round_trip_sequences = round_trip::round_trip_sequences(api_graph);
+            println!("[RoundTrip] {} round trip drivers", round_trip_sequences.len());
+            chosen_sequences.extend(round_trip_sequences);
+        }
 
+        let mut multiplexed = Vec::new();
         for sequence in &chosen_sequences {
-            if sequence_count >= MAX_TEST_FILE_NUMBER {
-                break;
+            // isolated unsafe drivers are named and numbered on their own
+            let isolated = api_graph.unsafe_policy == UnsafePolicy::Isolate && sequence._unsafe_tag;
+            if api_graph.multiplex && !isolated {
+                multiplexed.push(sequence);
+                continue;
             }
-            let test_file = sequence.to_afl_test_file(api_graph, sequence_count);
-            test_files.push(test_file);
-            let reproduce_file = sequence._to_replay_crash_file(api_graph, sequence_count);
-            reproduce_files.push(reproduce_file);
-            let libfuzzer_file = sequence.to_libfuzzer_test_file(api_graph, sequence_count);
-            libfuzzer_files.push(libfuzzer_file);
-            sequence_count = sequence_count + 1;
+            let (prefix, index) =
+                if isolated { ("test_unsafe", unsafe_count) } else { ("test", sequence_count) };
+            let test_file = match api_graph.engine {
+                FuzzEngine::Afl => sequence.to_afl_test_file(api_graph, index),
+                FuzzEngine::Libfuzzer => sequence.to_libfuzzer_test_file(api_graph, index),
+            };
+            let reproduce_file = sequence._to_replay_crash_file(api_graph, index);
+            if isolated {
+                unsafe_test_files.push(test_file);
+                unsafe_reproduce_files.push(reproduce_file);
+                unsafe_count = unsafe_count + 1;
+            } else {
+                test_files.push(test_file);
+                reproduce_files.push(reproduce_file);
+                sequence_count = sequence_count + 1;
+            }
+            let driver = format!("{}_{}{}", prefix, crate_name, index);
+            if api_graph.minimize.as_ref() == Some(&driver) && sequence.op_loop.is_some() {
+                println!("[Minimize] {} is an op loop driver, not minimized", driver);
+            } else if api_graph.minimize.as_ref() == Some(&driver) {
+                minimizer = Some((
+                    format!("minimize{}.rs", &driver["test".len()..]),
+                    crash_minimizer::minimizer_file(api_graph, sequence),
+                ));
+            }
+            if api_graph.regression.as_ref() == Some(&driver) {
+                match regression::unsupported(api_graph, sequence) {
+                    Some(reason) => println!("[Regression] {} {}, not converted", driver, reason),
+                    None => {
+                        regression = Some((
+                            format!("regression{}.rs", &driver["test".len()..]),
+                            regression::regression_file(api_graph, sequence, &driver, index),
+                        ));
+                    }
+                }
+            }
+            seeds.push((driver.clone(), seed_corpus::sequence_seeds(api_graph, sequence)));
+            drivers.push((driver, api_graph.sequence_api_names(sequence)));
+        }
+        if api_graph.unsafe_policy == UnsafePolicy::Isolate {
+            println!("[Unsafe] {} drivers isolated", unsafe_count);
+        }
+        let mut multiplexed_test_files = Vec::new();
+        let mut multiplexed_reproduce_files = Vec::new();
+        if !multiplexed.is_empty() {
+            println!("[Multiplex] {} sequences in one driver", multiplexed.len());
+            multiplexed_test_files
+                .push(multiplex::multiplexed_file(api_graph, &multiplexed, false));
+            multiplexed_reproduce_files
+                .push(multiplex::multiplexed_file(api_graph, &multiplexed, true));
+            let driver = format!("test_multi_{}0", crate_name);
+            if api_graph.minimize.as_ref() == Some(&driver) {
+                println!("[Minimize] {} is a multiplexed driver, not minimized", driver);
+            }
+            if api_graph.regression.as_ref() == Some(&driver) {
+                println!("[Regression] {} is a multiplexed driver, not converted", driver);
+            }
+            let api_names = multiplexed
+                .iter()
+                .flat_map(|sequence| api_graph.sequence_api_names(sequence))
+                .collect();
+            seeds.push((driver.clone(), seed_corpus::multiplexed_seeds(api_graph, &multiplexed)));
+            drivers.push((driver, api_names));
+        }
+        let mut differential_test_files = Vec::new();
+        let mut differential_reproduce_files = Vec::new();
+        if api_graph.differential {
+            let groups = differential::differential_groups(api_graph);
+            println!("[Differential] {} differential drivers", groups.len());
+            for (index, group) in groups.iter().enumerate() {
+                differential_test_files
+                    .push(differential::differential_file(api_graph, group, false));
+                differential_reproduce_files
+                    .push(differential::differential_file(api_graph, group, true));
+                let driver = format!("test_diff_{}{}", crate_name, index);
+                if api_graph.minimize.as_ref() == Some(&driver) {
+                    println!("[Minimize] {} is a differential driver, not minimized", driver);
+                }
+                if api_graph.regression.as_ref() == Some(&driver) {
+                    println!("[Regression] {} is a differential driver, not converted", driver);
+                }
+                let api_names = group
+                    .iter()
+                    .flat_map(|sequence| api_graph.sequence_api_names(sequence))
+                    .collect();
+                // the variants decode the same params from the input
+                seeds.push((driver.clone(), seed_corpus::sequence_seeds(api_graph, &group[0])));
+                drivers.push((driver, api_names));
+            }
+        }
+        println!(
+            "[Seed] {} seeds for {} drivers",
+            seeds.iter().map(|(_, driver_seeds)| driver_seeds.len()).sum::<usize>(),
+            seeds.len()
+        );
+        if let Some(ref driver) = api_graph.minimize {
+            if !drivers.iter().any(|(name, _)| name == driver) {
+                println!("[Minimize] no driver named {}", driver);
+            }
+        }
+        if let Some(ref driver) = api_graph.regression {
+            if !drivers.iter().any(|(name, _)| name == driver) {
+                println!("[Regression] no driver named {}", driver);
+            }
+        }
+        FileHelper {
+            crate_name,
+            test_dir,
+            test_files,
+            reproduce_files,
+            unsafe_test_files,
+            unsafe_reproduce_files,
+            differential_test_files,
+            differential_reproduce_files,
+            multiplexed_test_files,
+            multiplexed_reproduce_files,
+            drivers,
+            minimizer,
+            regression,
+            runtime_crate: api_graph.runtime_crate,
+            engine: api_graph.engine,
+            features: api_graph.features.clone(),
+            seeds,
         }
-        FileHelper { crate_name, test_dir, test_files, reproduce_files, libfuzzer_files }
     }
 
     pub(crate) fn write_files(&self) {
         let test_path = PathBuf::from(&self.test_dir);
         let test_file_path = test_path.clone().join(_TEST_FILE_DIR);
         let reproduce_file_path = test_path.clone().join(_REPRODUCE_FILE_DIR);
-        println!("test_file_path: {test_file_path:?}");
         println!("reproduce_file_path: {reproduce_file_path:?}");
-        fs::create_dir_all(&test_file_path);
         fs::create_dir_all(&reproduce_file_path);
 
-        write_to_files(&self.crate_name, &test_file_path, &self.test_files, "test");
+        let test_files = [
+            ("test", &self.test_files),
+            ("test_unsafe", &self.unsafe_test_files),
+            ("test_diff", &self.differential_test_files),
+            ("test_multi", &self.multiplexed_test_files),
+        ];
+        // every file built as a binary of the workspace, named like the file
+        let mut bins = Vec::new();
+        let mut in_dir = |dir: &str, names: Vec<String>| {
+            bins.extend(names.into_iter().map(|name| DriverBin {
+                path: format!("{}/{}.rs", dir, name),
+                name,
+            }));
+        };
+        match self.engine {
+            FuzzEngine::Afl => {
+                println!("test_file_path: {test_file_path:?}");
+                fs::create_dir_all(&test_file_path).unwrap();
+                for (prefix, contents) in test_files {
+                    in_dir(
+                        _TEST_FILE_DIR,
+                        write_to_files(&self.crate_name, &test_file_path, contents, prefix),
+                    );
+                }
+            }
+            FuzzEngine::Libfuzzer => {
+                let drivers = test_files
+                    .iter()
+                    .flat_map(|(prefix, contents)| {
+                        contents.iter().enumerate().map(move |(i, content)| {
+                            (format!("{}_{}{}", prefix, self.crate_name, i), content)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                cargo_fuzz::write_cargo_fuzz(
+                    &test_path,
+                    &self.crate_name,
+                    &self.features,
+                    &drivers,
+                    self.runtime_crate,
+                );
+            }
+        }
         //暂时用test file代替一下，后续改成真正的reproduce file
-        write_to_files(&self.crate_name, &reproduce_file_path, &self.reproduce_files, "replay");
-    }
-
-    pub(crate) fn write_libfuzzer_files(&self) {
-        let libfuzzer_dir = LIBFUZZER_FUZZ_TARGET_DIR.get(self.crate_name.as_str()).unwrap();
-        let libfuzzer_path = PathBuf::from(libfuzzer_dir);
-        let libfuzzer_files_path = libfuzzer_path.join(_LIBFUZZER_DIR_NAME);
-        fs::create_dir_all(&libfuzzer_files_path);
-        write_to_files(
+        let reproduce_files = [
+            ("replay", &self.reproduce_files),
+            ("replay_unsafe", &self.unsafe_reproduce_files),
+            ("replay_diff", &self.differential_reproduce_files),
+            ("replay_multi", &self.multiplexed_reproduce_files),
+        ];
+        for (prefix, contents) in reproduce_files {
+            in_dir(
+                _REPRODUCE_FILE_DIR,
+                write_to_files(&self.crate_name, &reproduce_file_path, contents, prefix),
+            );
+        }
+        coverage_feedback::write_manifest(&test_path, &self.drivers);
+        let seed_path = match self.engine {
+            FuzzEngine::Afl => test_path.join(SEED_DIR),
+            FuzzEngine::Libfuzzer => cargo_fuzz::corpus_dir(&test_path),
+        };
+        seed_corpus::write_seeds(&seed_path, &self.seeds);
+        if self.runtime_crate {
+            runtime_crate::write_runtime_crate(&test_path);
+        }
+        if let Some((ref filename, ref content)) = self.minimizer {
+            let minimize_file_path = test_path.join(_MINIMIZE_FILE_DIR);
+            println!("minimize_file_path: {minimize_file_path:?}");
+            fs::create_dir_all(&minimize_file_path).unwrap();
+            fs::write(minimize_file_path.join(filename), content).unwrap();
+            let name = filename.trim_end_matches(".rs").to_string();
+            in_dir(_MINIMIZE_FILE_DIR, vec![name]);
+        }
+        if let Some((ref filename, ref content)) = self.regression {
+            let regression_file_path = test_path.join(REGRESSION_FILE_DIR);
+            println!("regression_file_path: {regression_file_path:?}");
+            fs::create_dir_all(&regression_file_path).unwrap();
+            fs::write(regression_file_path.join(filename), content).unwrap();
+            let name = filename.trim_end_matches(".rs").to_string();
+            in_dir(REGRESSION_FILE_DIR, vec![name]);
+        }
+        let contents = test_files
+            .iter()
+            .chain(reproduce_files.iter())
+            .flat_map(|(_, contents)| contents.iter())
+            .collect::<Vec<_>>();
+        driver_workspace::write_workspace(
+            &test_path,
             &self.crate_name,
-            &libfuzzer_files_path,
-            &self.libfuzzer_files,
-            "fuzz_target",
+            &self.features,
+            &bins,
+            self.engine == FuzzEngine::Afl,
+            &driver_workspace::driver_dependencies(&self.crate_name, &contents),
+            self.runtime_crate,
         );
     }
 }
 
-fn write_to_files(crate_name: &String, path: &PathBuf, contents: &Vec<String>, prefix: &str) {
+/// write the files and return their names without extension
+fn write_to_files(
+    crate_name: &String,
+    path: &PathBuf,
+    contents: &Vec<String>,
+    prefix: &str,
+) -> Vec<String> {
     let file_number = contents.len();
+    let mut names = Vec::new();
     for i in 0..file_number {
-        let filename = format!("{}_{}{}.rs", prefix, crate_name, i);
-        let full_filename = path.join(filename);
+        let name = format!("{}_{}{}", prefix, crate_name, i);
+        let full_filename = path.join(format!("{}.rs", name));
         let mut file = fs::File::create(full_filename).unwrap();
         file.write_all(contents[i].as_bytes()).unwrap();
+        names.push(name);
     }
+    names
 }
\ No newline at end of file
@@ -8,11 +8,19 @@
 use crate::fuzz_target::api_util;
 use crate::fuzz_target::file_util;
 use crate::fuzz_target::generic_function::GenericFunction;
+use crate::fuzz_target::alloc_type;
+use crate::fuzz_target::coverage_feedback::CoverageFeedback;
+use crate::fuzz_target::documented_panic;
+use crate::fuzz_target::evil_type;
+use crate::fuzz_target::fuzzable_type::InputLayout;
+use crate::fuzz_target::generic_hint::GenericHints;
+use crate::fuzz_target::helper_type;
+use crate::fuzz_target::seed_corpus;
 use crate::fuzz_target::impl_util::{self, FullNameMap};
-use crate::fuzz_target::{api_function, statistic};
+use crate::fuzz_target::{api_function, statistic, unsafe_policy};
 use crate::html::format::join_with_double_colon;
 use crate::TyCtxt;
-use rustc_span::symbol::Symbol;
+use rustc_span::symbol::{sym, Symbol};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -50,6 +58,10 @@ fn analyse_item(&mut self, item: clean::Item) -> Result<(), Error> {
                 let api_unsafety = api_function::ApiUnsafety::_get_unsafety_from_fnheader(
                     &item.fn_header(self.context.tcx).unwrap(),
                 );
+                let trivial_safety = api_unsafety._is_unsafe()
+                    && unsafe_policy::is_trivial_safety_contract(&item.doc_value(), &inputs);
+                let panics_doc =
+                    documented_panic::panics_doc(self.context.tcx, &item, &func.decl.inputs);
                 let api_fun = api_function::ApiFunction {
                     name: item.name.unwrap().to_string(),
                     full_path: full_name,
@@ -58,6 +70,8 @@ fn analyse_item(&mut self, item: clean::Item) -> Result<(), Error> {
                     trait_: None,
                     self_: None,
                     _unsafe_tag: api_unsafety,
+                    trivial_safety,
+                    panics_doc,
                     mono: false,
                     local: true,
                     rpg_local: true
@@ -68,7 +82,7 @@ fn analyse_item(&mut self, item: clean::Item) -> Result<(), Error> {
                     statistic::inc("UNSAFE");
                 }
                 let mut generic_function = GenericFunction::from(api_fun);
-                generic_function.add_generics(&func.generics, None);
+                generic_function.add_generics(&func.generics, &[]);
                 if !generic_function.generic_map.is_empty() {
                     self.api_dependency_graph.borrow_mut().generic_functions.push(generic_function);
                 } else {
@@ -98,12 +112,20 @@ fn descr() -> &'static str {
 
     fn init(
         krate: clean::Crate,
-        _options: RenderOptions,
-        cache: Cache,
+        options: RenderOptions,
+        mut cache: Cache,
         tcx: TyCtxt<'tcx>,
     ) -> Result<(Self, clean::Crate), Error> {
         println!("Fuzz Target Renderer Init");
         println!("crate: {}", krate.module.name.unwrap().as_str());
+        // cache is frozen after this, so the driver-local helper type is registered in advance
+        helper_type::register_helper_type(&mut cache);
+        if options.fuzz_evil_impls {
+            evil_type::register_evil_type(&mut cache);
+        }
+        if options.fuzz_allocator {
+            alloc_type::register_alloc_type(&mut cache);
+        }
         let rcx = Rc::new(FuzzTargetContext { cache, tcx });
         let mut api_dependency_graph = ApiGraph::new(krate.name(tcx).to_string(), rcx.clone());
         //从cache中提出def_id与full_name的对应关系，存入full_name_map来进行调用
@@ -111,6 +133,45 @@ fn init(
         let mut full_name_map = FullNameMap::new();
         // impl_util::analyse_trait(&mut api_dependency_graph);
         impl_util::extract_full_name_from_cache(&mut full_name_map, &mut api_dependency_graph);
+        if let Some(ref hint_file) = options.fuzz_hints {
+            api_dependency_graph.generic_hints = GenericHints::load(hint_file, &rcx.cache, tcx);
+        }
+        api_dependency_graph.evil_type = options.fuzz_evil_impls;
+        api_dependency_graph.alloc_type = options.fuzz_allocator;
+        api_dependency_graph.global_allocator = options.fuzz_global_allocator;
+        api_dependency_graph.strategy = options.fuzz_strategy;
+        api_dependency_graph.seed = options.fuzz_seed;
+        api_dependency_graph.minimize = options.fuzz_minimize.clone();
+        // only the runtime crate reads the sequential layout, which op loops use
+        api_dependency_graph.runtime_crate = options.fuzz_runtime_crate
+            || options.fuzz_layout == InputLayout::Sequential
+            || options.fuzz_op_loop;
+        api_dependency_graph.input_layout = options.fuzz_layout;
+        api_dependency_graph.op_loop = options.fuzz_op_loop;
+        api_dependency_graph.unsafe_policy = options.fuzz_unsafe;
+        api_dependency_graph.differential = options.fuzz_differential;
+        api_dependency_graph.trait_laws = options.fuzz_trait_laws;
+        api_dependency_graph.round_trip = options.fuzz_round_trip;
+        api_dependency_graph.expect_documented_panics = options.fuzz_expect_documented_panics;
+        api_dependency_graph.engine = options.fuzz_engine;
+        api_dependency_graph.multiplex = options.fuzz_multiplex;
+        api_dependency_graph.regression = options.fuzz_regression.clone();
+        // the drivers build the crate with the features it was documented with
+        let mut features = tcx
+            .sess
+            .psess
+            .config
+            .iter()
+            .filter(|(name, _)| *name == sym::feature)
+            .filter_map(|(_, value)| value.map(|value| value.to_string()))
+            .collect::<Vec<_>>();
+        features.sort();
+        api_dependency_graph.features = features;
+        api_dependency_graph.literals = seed_corpus::crate_literals(tcx);
+        if let Some(ref coverage) = options.fuzz_coverage {
+            api_dependency_graph.coverage_feedback =
+                CoverageFeedback::load(coverage, &file_util::output_dir());
+        }
         Ok((
             FuzzTargetRenderer {
                 context: rcx,
@@ -176,16 +237,14 @@ fn after_krate(&mut self) -> Result<(), Error> {
         // Resolve all visible generic functions to normal function
         api_dependency_graph.resolve_generic_functions();
         statistic::print_summary();
+        println!("====== unsolvable generic ======");
+        print!("{}", api_dependency_graph.unsolvable_report.summary());
+        api_dependency_graph.unsolvable_report.write_files(&file_util::output_dir());
         //寻找所有依赖，并且构建序列
         api_dependency_graph.find_all_dependencies();
 
-        let random_strategy = false;
-        if !random_strategy {
-            api_dependency_graph.default_generate_sequences();
-        } else {
-            use crate::fuzz_target::api_graph::GraphTraverseAlgorithm::_RandomWalk;
-            api_dependency_graph.generate_all_possoble_sequences(_RandomWalk);
-        }
+        let random_strategy = api_dependency_graph.is_random_strategy();
+        api_dependency_graph.generate_sequences();
         // api_dependency_graph._
         //api_dependency_graph._print_generated_libfuzzer_file();
         //api_dependency_graph._print_pretty_functions(false);
@@ -213,10 +272,6 @@ fn after_krate(&mut self) -> Result<(), Error> {
             let file_helper = file_util::FileHelper::new(&api_dependency_graph, random_strategy);
             // println!("file_helper:{:?}", file_helper);
             file_helper.write_files();
-            if file_util::can_generate_libfuzzer_target(&api_dependency_graph._crate_name) {
-                // println!("libfuzzer file_helper:{:?}", file_helper);
-                file_helper.write_libfuzzer_files();
-            }
         }
 
         Ok(())
@@ -6,12 +6,16 @@
 use crate::fuzz_target::api_util::get_type_name_from_did;
 use crate::fuzz_target::call_type::CallType;
 use crate::fuzz_target::impl_util::FullNameMap;
+use crate::fuzz_target::named_value::NamedValue;
 use crate::fuzz_target::prelude_type::PreludeType;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def::CtorKind;
 use rustc_hir::def_id::DefId;
 use rustc_hir::Mutability;
 
+#[cfg(test)]
+mod tests;
+
 //如果构造一个fuzzable的变量
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub(crate) enum FuzzableCallType {
@@ -39,6 +43,20 @@ pub(crate) enum FuzzableType {
     Tuple(Vec<Box<FuzzableType>>),
 }
 
+/// How a driver lays out its fuzzable params in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InputLayout {
+    /// fixed length parts first, the rest split evenly between the dynamic length values
+    Split,
+    /// values one after another, dynamic length ones preceded by a length byte
+    Sequential,
+}
+
+impl NamedValue for InputLayout {
+    const NAMES: &'static [(&'static str, InputLayout)] =
+        &[("split", InputLayout::Split), ("sequential", InputLayout::Sequential)];
+}
+
 impl FuzzableType {
     pub(crate) fn is_fuzzable(&self) -> bool {
         match self {
@@ -326,6 +344,45 @@ pub(crate) fn _is_multiple_dynamic_length(&self) -> bool {
         }
     }
 
+    /// whether a driver can decode this type from an input of the layout
+    pub(crate) fn _is_decodable(&self, layout: InputLayout) -> bool {
+        match layout {
+            InputLayout::Split => !self._is_multiple_dynamic_length(),
+            InputLayout::Sequential => self._is_sequentially_decodable(false),
+        }
+    }
+
+    /// Slices of primitives borrow the input, other slices are collected into a `Vec` that
+    /// lives as long as the param. Inside the element of such a slice, or in the arguments of a
+    /// tuple struct constructor, the `Vec` would not be kept alive, so it must not collect there.
+    fn _is_sequentially_decodable(&self, in_element: bool) -> bool {
+        match self {
+            FuzzableType::NoFuzzable => false,
+            FuzzableType::Primitive(_) | FuzzableType::RefStr => true,
+            FuzzableType::RefSlice(inner_fuzzable) => {
+                inner_fuzzable._is_input_slice_element()
+                    || (!in_element && inner_fuzzable._is_sequentially_decodable(true))
+            }
+            FuzzableType::Tuple(inner_fuzzables) => inner_fuzzables
+                .iter()
+                .all(|inner_fuzzable| inner_fuzzable._is_sequentially_decodable(in_element)),
+            FuzzableType::Struct(_, kind, inner) => inner.iter().all(|(_, inner_fuzzable)| {
+                inner_fuzzable._is_sequentially_decodable(in_element || *kind == 0)
+            }),
+        }
+    }
+
+    /// slices of this type are read from the input as they are in the sequential layout. Not
+    /// every bit pattern is a `char` or `bool`, so those are read one by one.
+    pub(crate) fn _is_input_slice_element(&self) -> bool {
+        match self {
+            FuzzableType::Primitive(primitive) => {
+                !matches!(primitive, PrimitiveType::Char | PrimitiveType::Bool)
+            }
+            _ => false,
+        }
+    }
+
     pub(crate) fn _to_type_string(&self) -> String {
         match self {
             FuzzableType::NoFuzzable => "nofuzzable".to_string(),
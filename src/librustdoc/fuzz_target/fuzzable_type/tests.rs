@@ -0,0 +1,44 @@
+use super::FuzzableType;
+use crate::clean::PrimitiveType;
+
+fn u8_() -> FuzzableType {
+    FuzzableType::Primitive(PrimitiveType::U8)
+}
+
+fn slice(inner: FuzzableType) -> FuzzableType {
+    FuzzableType::RefSlice(Box::new(inner))
+}
+
+#[test]
+fn test_sequentially_decodable() {
+    assert!(u8_()._is_sequentially_decodable(false));
+    assert!(FuzzableType::RefStr._is_sequentially_decodable(false));
+    assert!(!FuzzableType::NoFuzzable._is_sequentially_decodable(false));
+    // slices of primitives borrow the input, also inside elements
+    assert!(slice(u8_())._is_sequentially_decodable(true));
+    assert!(slice(slice(u8_()))._is_sequentially_decodable(false));
+    // other slices collect into a `Vec`, which only a param keeps alive
+    assert!(slice(FuzzableType::RefStr)._is_sequentially_decodable(false));
+    assert!(!slice(FuzzableType::RefStr)._is_sequentially_decodable(true));
+    assert!(!slice(slice(FuzzableType::RefStr))._is_sequentially_decodable(false));
+    let tuple = FuzzableType::Tuple(vec![Box::new(u8_()), Box::new(slice(FuzzableType::RefStr))]);
+    assert!(tuple._is_sequentially_decodable(false));
+    assert!(!slice(tuple)._is_sequentially_decodable(false));
+    // not every input is a `char` or `bool`, their slices are read element by element
+    let char_ = FuzzableType::Primitive(PrimitiveType::Char);
+    assert!(!char_._is_input_slice_element());
+    assert!(slice(char_.clone())._is_sequentially_decodable(false));
+    assert!(!slice(slice(char_))._is_sequentially_decodable(false));
+}
+
+#[test]
+fn test_sequentially_decodable_struct() {
+    let fields = vec![("0".to_string(), slice(FuzzableType::RefStr))];
+    // the arguments of a tuple struct constructor do not keep a `Vec` alive
+    let tuple_struct = FuzzableType::Struct("S".to_string(), 0, fields.clone());
+    assert!(!tuple_struct._is_sequentially_decodable(false));
+    let named_struct = FuzzableType::Struct("S".to_string(), 2, fields);
+    assert!(named_struct._is_sequentially_decodable(false));
+    let fields = vec![("0".to_string(), slice(u8_()))];
+    assert!(FuzzableType::Struct("S".to_string(), 0, fields)._is_sequentially_decodable(false));
+}
@@ -75,12 +75,21 @@ pub(crate) fn set_self_type(&mut self, self_type: &Type) {
         self.generic_map.set_self_type(self_type);
     }
 
-    pub(crate) fn add_generics(&mut self, generics: &Generics, ignore: Option<String>) {
+    pub(crate) fn add_generics(&mut self, generics: &Generics, ignore: &[String]) {
         self.generic_map.add_generics(generics, ignore);
     }
 
     pub(crate) fn is_solvable(&self) -> bool {
-        let mut have_unknown_generic = false;
+        if !self.unknown_generics().is_empty() {
+            return false;
+        }
+
+        return self.generic_map.is_solvable();
+    }
+
+    /// generics used in signature but not declared by function or impl
+    pub(crate) fn unknown_generics(&self) -> Vec<String> {
+        let mut unknown_generics = Vec::<String>::new();
         let mut check = |type_: &Type| -> bool {
             if let Type::Generic(sym) = type_ {
                 for defname in self.generic_map.generic_defs.iter() {
@@ -89,7 +98,9 @@ pub(crate) fn is_solvable(&self) -> bool {
                     }
                 }
                 println!("unknown generic: {:?}", sym);
-                have_unknown_generic = true;
+                if !unknown_generics.iter().any(|x| x == sym.as_str()) {
+                    unknown_generics.push(sym.to_string());
+                }
                 return false;
             }
             true
@@ -103,11 +114,7 @@ pub(crate) fn is_solvable(&self) -> bool {
             scan_type_with(output, &mut check);
         }
 
-        if have_unknown_generic {
-            return false;
-        }
-
-        return self.generic_map.is_solvable();
+        unknown_generics
     }
 
     fn resolve_argument_type(&mut self) {
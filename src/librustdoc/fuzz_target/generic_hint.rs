@@ -0,0 +1,388 @@
+use crate::clean::{GenericArg, GenericArgs, Path, PathSegment, PrimitiveType, Type};
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::fuzz_target::api_util::{_type_name, is_driver_type, try_type_name_from_did};
+use crate::fuzz_target::generic_function::GenericFunction;
+use crate::fuzz_target::generic_solution::Solution;
+use crate::html::format::join_with_double_colon;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::DefId;
+use rustc_hir::Mutability;
+use rustc_middle::ty::{self, GenericParamDefKind, TyCtxt};
+use rustc_span::Symbol;
+use std::fs;
+use std::path::PathBuf;
+use thin_vec::{thin_vec, ThinVec};
+
+#[cfg(test)]
+mod tests;
+
+/// One line of the hint file.
+///
+/// `mycrate::decode::T = mycrate::codecs::Utf8` only applies to `mycrate::decode`,
+/// `T = u8` applies to every generic API with a param named `T`.
+#[derive(Debug, Clone)]
+pub(crate) struct GenericHint {
+    pub(crate) api: Option<String>,
+    pub(crate) param: String,
+    pub(crate) type_: Type,
+    pub(crate) line: usize,
+    pub(crate) used: bool,
+}
+
+/// user supplied instantiations for generic params
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GenericHints {
+    pub(crate) hints: Vec<GenericHint>,
+}
+
+impl GenericHints {
+    pub(crate) fn new() -> Self {
+        GenericHints { hints: Vec::new() }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hints.is_empty()
+    }
+
+    /// read hint file, lines starting with `#` are comments.
+    /// Malformed lines are reported and skipped.
+    pub(crate) fn load(file: &PathBuf, cache: &Cache, tcx: TyCtxt<'_>) -> Self {
+        let mut res = GenericHints::new();
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("[Hint] cannot read hint file {}: {}", file.display(), e);
+                return res;
+            }
+        };
+        let type_map = TypeNameMap::new(cache, tcx);
+
+        for (no, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                println!("[Hint] line {}: expect `path::Param = Type`, found `{}`", no + 1, line);
+                continue;
+            };
+            let key = key.trim();
+            let (api, param) = match key.rsplit_once("::") {
+                Some((api, param)) => (Some(api.to_string()), param.to_string()),
+                None => (None, key.to_string()),
+            };
+            match TypeParser::new(value, &type_map).parse() {
+                Ok(type_) => {
+                    println!(
+                        "[Hint] line {}: {} = {}",
+                        no + 1,
+                        key,
+                        _type_name(&type_, Some(cache))
+                    );
+                    res.hints.push(GenericHint { api, param, type_, line: no + 1, used: false });
+                }
+                Err(e) => {
+                    println!("[Hint] line {}: cannot parse `{}`: {}", no + 1, value.trim(), e);
+                }
+            }
+        }
+        res
+    }
+
+    /// Build the seed solution of a generic function.
+    /// Hint for the api overrides the hint for bare param name.
+    pub(crate) fn seed_for(
+        &mut self,
+        function: &GenericFunction,
+        cache: &Cache,
+    ) -> Option<Solution> {
+        let generic_defs = &function.generic_map.generic_defs;
+        let api_function = &function.api_function;
+        let mut api_paths = vec![api_function.full_name(cache)];
+        if api_function.self_.is_some() {
+            api_paths.push(format!("{}::{}", api_function.full_path, api_function.name));
+        }
+
+        let mut seed = vec![Type::Infer; generic_defs.len()];
+        let mut found = false;
+        for (i, param) in generic_defs.iter().enumerate() {
+            let mut chosen = None;
+            for (j, hint) in self.hints.iter().enumerate() {
+                if &hint.param != param {
+                    continue;
+                }
+                match hint.api {
+                    Some(ref api) if api_paths.contains(api) => {
+                        chosen = Some(j);
+                        break;
+                    }
+                    None if chosen.is_none() => chosen = Some(j),
+                    _ => {}
+                }
+            }
+            if let Some(j) = chosen {
+                self.hints[j].used = true;
+                seed[i] = self.hints[j].type_.clone();
+                found = true;
+            }
+        }
+        if found { Some(seed) } else { None }
+    }
+
+    pub(crate) fn print_unused(&self) {
+        for hint in self.hints.iter() {
+            if !hint.used {
+                println!(
+                    "[Hint] line {}: {}{} does not match any generic api",
+                    hint.line,
+                    hint.api.as_ref().map_or(String::new(), |api| format!("{}::", api)),
+                    hint.param
+                );
+            }
+        }
+    }
+}
+
+/// full path of types visible to rustdoc => (did, kind)
+struct TypeNameMap {
+    inner: FxHashMap<String, (DefId, DefKind)>,
+    /// type params of the types with a defaulted one, with the default if it is a plain type,
+    /// e.g. `[None, Some(Global)]` for `Vec<T, A = Global>`
+    defaults: FxHashMap<DefId, Vec<Option<Type>>>,
+}
+
+impl TypeNameMap {
+    fn new(cache: &Cache, tcx: TyCtxt<'_>) -> Self {
+        let mut inner = FxHashMap::default();
+        let mut defaults = FxHashMap::default();
+        for (did, (syms, item_type)) in cache.paths.iter().chain(cache.external_paths.iter()) {
+            let kind = match item_type {
+                ItemType::Struct => DefKind::Struct,
+                ItemType::Enum => DefKind::Enum,
+                ItemType::Union => DefKind::Union,
+                _ => continue,
+            };
+            inner.insert(join_with_double_colon(syms), (*did, kind));
+            // also accept the public name, e.g. `std::vec::Vec` for `alloc::vec::Vec`
+            if let Some(name) = try_type_name_from_did(*did, cache) {
+                inner.entry(name).or_insert((*did, kind));
+            }
+            if is_driver_type(*did) {
+                continue;
+            }
+            let params = type_param_defaults(*did, tcx);
+            if params.iter().any(Option::is_some) {
+                defaults.insert(*did, params);
+            }
+        }
+        TypeNameMap { inner, defaults }
+    }
+}
+
+/// Defaults of the type params of a type, as rustdoc writes the path of an external type (e.g.
+/// `Global`). Defaults with generic args or referring to other params are left out, so are the
+/// params after a const param.
+fn type_param_defaults(did: DefId, tcx: TyCtxt<'_>) -> Vec<Option<Type>> {
+    let mut res = Vec::new();
+    for param in tcx.generics_of(did).own_params.iter() {
+        match param.kind {
+            GenericParamDefKind::Lifetime => continue,
+            GenericParamDefKind::Const { .. } => break,
+            GenericParamDefKind::Type { .. } => {}
+        }
+        let default = param.default_value(tcx).and_then(|default| {
+            let ty::Adt(adt, args) = default.instantiate_identity().expect_ty().kind() else {
+                return None;
+            };
+            if !args.is_empty() {
+                return None;
+            }
+            let default_did = adt.did();
+            Some(Type::Path {
+                path: Path {
+                    res: Res::Def(tcx.def_kind(default_did), default_did),
+                    segments: thin_vec![PathSegment {
+                        name: tcx.item_name(default_did),
+                        args: GenericArgs::AngleBracketed {
+                            args: Box::new([]),
+                            constraints: ThinVec::new(),
+                        },
+                    }],
+                },
+            })
+        });
+        res.push(default);
+    }
+    res
+}
+
+/// parse type written in rust syntax, e.g. `&mut [u8]`, `Vec<(u8, String)>`
+struct TypeParser<'a> {
+    src: &'a str,
+    pos: usize,
+    type_map: &'a TypeNameMap,
+}
+
+impl<'a> TypeParser<'a> {
+    fn new(src: &'a str, type_map: &'a TypeNameMap) -> Self {
+        TypeParser { src, pos: 0, type_map }
+    }
+
+    fn parse(&mut self) -> Result<Type, String> {
+        let type_ = self.parse_type()?;
+        self.skip_space();
+        if self.pos != self.src.len() {
+            return Err(format!("unexpected `{}`", &self.src[self.pos..]));
+        }
+        Ok(type_)
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(c) = self.src[self.pos..].chars().next() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_space();
+        if self.src[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// eat a keyword, which must not run into an identifier, e.g. `mut` in `&mut[u8]`
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_space();
+        let rest = &self.src[self.pos..];
+        let Some(after) = rest.strip_prefix(keyword) else {
+            return false;
+        };
+        if after.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            return false;
+        }
+        self.pos += keyword.len();
+        true
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) { Ok(()) } else { Err(format!("expect `{}` at {}", token, self.pos)) }
+    }
+
+    fn ident(&mut self) -> Result<&'a str, String> {
+        self.skip_space();
+        let src: &'a str = self.src;
+        let rest = &src[self.pos..];
+        let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(format!("expect identifier at {}", self.pos));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn parse_type(&mut self) -> Result<Type, String> {
+        if self.eat("&") {
+            let mutability =
+                if self.eat_keyword("mut") { Mutability::Mut } else { Mutability::Not };
+            let type_ = self.parse_type()?;
+            return Ok(Type::BorrowedRef { lifetime: None, mutability, type_: Box::new(type_) });
+        }
+        if self.eat("*") {
+            let mutability = if self.eat_keyword("mut") {
+                Mutability::Mut
+            } else if self.eat_keyword("const") {
+                Mutability::Not
+            } else {
+                return Err(format!("expect `mut` or `const` at {}", self.pos));
+            };
+            let type_ = self.parse_type()?;
+            return Ok(Type::RawPointer(mutability, Box::new(type_)));
+        }
+        if self.eat("[") {
+            let type_ = self.parse_type()?;
+            if self.eat(";") {
+                self.skip_space();
+                let rest = &self.src[self.pos..];
+                let len = rest.find(']').ok_or("expect `]`".to_string())?;
+                let len_str = rest[..len].trim().to_string();
+                self.pos += len;
+                self.expect("]")?;
+                return Ok(Type::Array(Box::new(type_), len_str.into_boxed_str()));
+            }
+            self.expect("]")?;
+            return Ok(Type::Slice(Box::new(type_)));
+        }
+        if self.eat("(") {
+            let mut types = Vec::new();
+            while !self.eat(")") {
+                types.push(self.parse_type()?);
+                if !self.eat(",") {
+                    self.expect(")")?;
+                    break;
+                }
+            }
+            return Ok(Type::Tuple(types));
+        }
+        self.parse_path()
+    }
+
+    fn parse_path(&mut self) -> Result<Type, String> {
+        let mut names = vec![self.ident()?];
+        while self.eat("::") {
+            names.push(self.ident()?);
+        }
+        let mut args = Vec::new();
+        if self.eat("<") {
+            loop {
+                args.push(GenericArg::Type(self.parse_type()?));
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(">")?;
+        }
+
+        if names.len() == 1 && args.is_empty() {
+            if let Some(primitive) = PrimitiveType::from_symbol(Symbol::intern(names[0])) {
+                return Ok(Type::Primitive(primitive));
+            }
+        }
+
+        let full_name = names.join("::");
+        let Some(&(did, kind)) = self.type_map.inner.get(&full_name) else {
+            return Err(format!("unknown type `{}`", full_name));
+        };
+        // rustdoc spells out defaulted params, e.g. `Vec<u8>` is `Vec<u8, Global>`
+        if let Some(defaults) = self.type_map.defaults.get(&did) {
+            for default in defaults.iter().skip(args.len()) {
+                let Some(default) = default else {
+                    break;
+                };
+                args.push(GenericArg::Type(default.clone()));
+            }
+        }
+        let mut segments: ThinVec<PathSegment> = names
+            .iter()
+            .map(|name| PathSegment {
+                name: Symbol::intern(name),
+                args: GenericArgs::AngleBracketed {
+                    args: Box::new([]),
+                    constraints: ThinVec::new(),
+                },
+            })
+            .collect();
+        segments.last_mut().unwrap().args = GenericArgs::AngleBracketed {
+            args: args.into_boxed_slice(),
+            constraints: ThinVec::new(),
+        };
+        Ok(Type::Path { path: Path { res: Res::Def(kind, did), segments } })
+    }
+}
@@ -0,0 +1,85 @@
+use super::{TypeNameMap, TypeParser};
+use crate::clean::{GenericArg, GenericArgs, Path, PathSegment, PrimitiveType, Type};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex};
+use rustc_hir::Mutability;
+use rustc_span::create_default_session_globals_then;
+use rustc_span::Symbol;
+use thin_vec::{thin_vec, ThinVec};
+
+fn external_did(index: u32) -> DefId {
+    DefId { krate: CrateNum::from_u32(1), index: DefIndex::from_u32(index) }
+}
+
+/// `std::vec::Vec<T, A = Global>` and `std::alloc::Global`
+fn vec_type_map() -> TypeNameMap {
+    let global = Type::Path {
+        path: Path {
+            res: Res::Def(DefKind::Struct, external_did(1)),
+            segments: thin_vec![PathSegment {
+                name: Symbol::intern("Global"),
+                args: GenericArgs::AngleBracketed {
+                    args: Box::new([]),
+                    constraints: ThinVec::new(),
+                },
+            }],
+        },
+    };
+    let mut inner = FxHashMap::default();
+    inner.insert("std::vec::Vec".to_string(), (external_did(0), DefKind::Struct));
+    inner.insert("std::alloc::Global".to_string(), (external_did(1), DefKind::Struct));
+    let mut defaults = FxHashMap::default();
+    defaults.insert(external_did(0), vec![None, Some(global)]);
+    TypeNameMap { inner, defaults }
+}
+
+fn last_args(type_: &Type) -> Vec<GenericArg> {
+    let Type::Path { path } = type_ else {
+        panic!("not a path: {type_:?}");
+    };
+    match path.segments.last().unwrap().args {
+        GenericArgs::AngleBracketed { ref args, .. } => args.to_vec(),
+        GenericArgs::Parenthesized { .. } => panic!("parenthesized args"),
+    }
+}
+
+#[test]
+fn test_vec_hint_fills_default_allocator() {
+    create_default_session_globals_then(|| {
+        let type_map = vec_type_map();
+        let global = type_map.defaults[&external_did(0)][1].clone().unwrap();
+        let vec_u8 = TypeParser::new("std::vec::Vec<u8>", &type_map).parse().unwrap();
+        assert_eq!(
+            last_args(&vec_u8),
+            vec![GenericArg::Type(Type::Primitive(PrimitiveType::U8)), GenericArg::Type(global)]
+        );
+        // an allocator written out is kept
+        let explicit = TypeParser::new("std::vec::Vec<u8, std::alloc::Global>", &type_map);
+        assert_eq!(last_args(&explicit.parse().unwrap()).len(), 2);
+    });
+}
+
+#[test]
+fn test_mut_without_space() {
+    create_default_session_globals_then(|| {
+        let type_map = vec_type_map();
+        let slice = Type::Slice(Box::new(Type::Primitive(PrimitiveType::U8)));
+        for src in ["&mut[u8]", "&mut [u8]", "& mut[u8]"] {
+            let Ok(Type::BorrowedRef { mutability, type_, .. }) =
+                TypeParser::new(src, &type_map).parse()
+            else {
+                panic!("cannot parse {src}");
+            };
+            assert_eq!(mutability, Mutability::Mut, "{src}");
+            assert_eq!(*type_, slice, "{src}");
+        }
+        let pointer = TypeParser::new("*const*mut u8", &type_map).parse().unwrap();
+        let Type::RawPointer(Mutability::Not, inner) = pointer else {
+            panic!("not a const pointer: {pointer:?}");
+        };
+        assert!(matches!(*inner, Type::RawPointer(Mutability::Mut, _)));
+        // `mutable` is an identifier, not `mut` followed by `able`
+        assert!(TypeParser::new("&mutable", &type_map).parse().is_err());
+    });
+}
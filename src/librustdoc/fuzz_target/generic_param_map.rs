@@ -12,6 +12,7 @@
 use crate::fuzz_target::{api_function::ApiFunction, api_util, impl_util::FullNameMap};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::def_id::DefId;
+use rustc_hir::TraitBoundModifier;
 use std::cmp::Eq;
 use std::collections::hash_map::Iter;
 use std::hash::Hash;
@@ -43,7 +44,11 @@ fn bounds_to_vec(bounds: &[GenericBound]) -> Vec<Path> {
     let mut res = Vec::new();
     for bound in bounds {
         match bound {
-            GenericBound::TraitBound(poly, _) => {
+            GenericBound::TraitBound(poly, modifier) => {
+                if matches!(modifier, TraitBoundModifier::Maybe) {
+                    // `?Sized` relaxes a bound instead of adding one
+                    continue;
+                }
                 res.push(poly.trait_.clone());
                 // traitbound should not include type generic information, we must assure this
                 for param in &poly.generic_params {
@@ -69,10 +74,15 @@ pub fn iter(&self) -> Iter<'_, String, Vec<Path>> {
     }
 
     pub fn is_solvable(&self) -> bool {
+        self.unsolvable_bounds().is_empty()
+    }
+
+    pub fn unsolvable_bounds(&self) -> Vec<&Path> {
+        let mut res = Vec::new();
         for (name, bounds) in self.inner.iter() {
             for bound in bounds {
                 if !is_solvable_bound(bound) {
-                    return false;
+                    res.push(bound);
                 }
             }
         }
@@ -80,11 +90,11 @@ pub fn is_solvable(&self) -> bool {
         for (type_, bounds) in self.type_pred.iter() {
             for bound in bounds {
                 if !is_solvable_bound(bound) {
-                    return false;
+                    res.push(bound);
                 }
             }
         }
-        return true;
+        res
     }
 
     pub fn new() -> GenericParamMap {
@@ -124,7 +134,7 @@ pub fn set_self_type(&mut self, self_type: &Type) {
         }
     }
 
-    pub fn add_generics(&mut self, generics: &Generics, ignore: Option<String>) {
+    pub fn add_generics(&mut self, generics: &Generics, ignore: &[String]) {
         for param in generics.params.iter() {
             match &param.kind {
                 GenericParamDefKind::Type { bounds, default, .. } => {
@@ -133,7 +143,7 @@ pub fn add_generics(&mut self, generics: &Generics, ignore: Option<String>) {
                         continue;
                     }
 
-                    if ignore.as_ref().map_or(false,|x| x==param.name.as_str()) {
+                    if ignore.iter().any(|x| x == param.name.as_str()) {
                         continue;
                     } 
 
@@ -241,6 +251,54 @@ pub fn check_solution(
         Some(visited)
     }
 
+    /// find the (type, trait) pair that makes `check_solution` fail
+    pub fn find_unsatisfied_bound(
+        &self,
+        solution: &Solution,
+        trait_impl_map: &TraitImplMap,
+        type_trait_cache: &mut TypeTraitCache,
+        cache: &Cache,
+    ) -> Option<(Type, Path)> {
+        let mut preds = Vec::<(Type, Vec<Path>)>::new();
+        for i in 0..solution.len() {
+            preds.push((solution[i].clone(), self.get_bounds(&self.generic_defs[i]).clone()));
+        }
+        for (type_, bounds) in self.type_pred.iter() {
+            if matches!(type_, Type::QPath(_)) {
+                continue;
+            }
+            let mut complete_type = type_.clone();
+            replace_generic_with_solution(&mut complete_type, solution, &self.generic_defs);
+            let mut complete_bounds = Vec::<Path>::new();
+            for bound in bounds {
+                let mut ty = Type::Path { path: bound.clone() };
+                replace_generic_with_solution(&mut ty, solution, &self.generic_defs);
+                match ty {
+                    Type::Path { path } => complete_bounds.push(path),
+                    _ => unreachable!(),
+                }
+            }
+            preds.push((complete_type, complete_bounds));
+        }
+
+        for (type_, bounds) in preds.into_iter() {
+            for bound in bounds {
+                if trait_impl_map
+                    .extract_type_impls_with_bounds(
+                        &type_,
+                        std::slice::from_ref(&bound),
+                        type_trait_cache,
+                        cache,
+                    )
+                    .is_none()
+                {
+                    return Some((type_, bound));
+                }
+            }
+        }
+        None
+    }
+
     pub fn add_generic_bounds(&mut self, name: &str, bounds: &[GenericBound]) {
         let v = bounds_to_vec(bounds);
 
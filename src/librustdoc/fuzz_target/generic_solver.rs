@@ -4,7 +4,7 @@
 use crate::fuzz_target::api_function::ApiFunction;
 use crate::fuzz_target::api_graph::{any_type_match, ApiGraph, TypeContext};
 use crate::fuzz_target::api_util::{
-    self, _type_name, is_generic_type, is_support_type, replace_type_with, type_depth,
+    self, _type_name, is_generic_type, is_support_type, print_path, replace_type_with, type_depth,
 };
 use crate::fuzz_target::generic_function::GenericFunction;
 use crate::fuzz_target::generic_param_map::set_union;
@@ -14,6 +14,7 @@
 use crate::fuzz_target::impl_util::FullNameMap;
 use crate::fuzz_target::statistic;
 use crate::fuzz_target::trait_impl::{TraitImpl,TypeTraitCache};
+use crate::fuzz_target::unsolvable_report::{add_reason, UnsolvableReason};
 use itertools::Itertools;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_hir::def_id::DefId;
@@ -42,25 +43,44 @@ pub(crate) struct GenericSolver {
     solvable: bool,
     success: bool,
     try_count: usize,
+    reasons: Vec<UnsolvableReason>,
+    hint: Option<Solution>, // user supplied seed, Type::Infer for params without hint
+    searching_hint: bool,
 }
 
 impl GenericSolver {
     pub(crate) fn new(
         type_context: Rc<RefCell<TypeContext>>,
         generic_function: GenericFunction,
+        hint: Option<Solution>,
     ) -> GenericSolver {
         let mut solvable = generic_function.is_solvable();
         let len = generic_function.api_function.inputs.len();
         let mut contain_generic = vec![false; len];
+        let mut reasons = Vec::new();
 
         for (i, input) in generic_function.api_function.inputs.iter().enumerate() {
             if !is_support_type(input) {
                 println!("[Solver] {} is unsupported", _type_name(input, None));
-
+                add_reason(
+                    &mut reasons,
+                    UnsolvableReason::UnsupportedType { input: i, type_: _type_name(input, None) },
+                );
                 solvable = false;
             }
             contain_generic[i] = is_generic_type(input);
         }
+        if !generic_function.is_solvable() {
+            for bound in generic_function.generic_map.unsolvable_bounds() {
+                add_reason(
+                    &mut reasons,
+                    UnsolvableReason::UnsupportedBound { bound: print_path(bound, None) },
+                );
+            }
+            for name in generic_function.unknown_generics() {
+                add_reason(&mut reasons, UnsolvableReason::UnknownGeneric { name });
+            }
+        }
         // println!("[Solver] generic_param: {:?}", generic_param);
         GenericSolver {
             type_context,
@@ -76,6 +96,9 @@ pub(crate) fn new(
             solution_count: 0,
             try_count: 0,
             success: false,
+            reasons,
+            hint,
+            searching_hint: false,
         }
     }
 
@@ -83,6 +106,10 @@ pub(crate) fn num_solution(&self) -> usize {
         self.solutions.len()
     }
 
+    pub(crate) fn reasons(&self) -> &Vec<UnsolvableReason> {
+        &self.reasons
+    }
+
     fn make_function_with(&self, solution: &Solution) -> ApiFunction {
         let mut func = self.current_function.api_function.clone();
         let generic_defs = &self.current_function.generic_map.generic_defs;
@@ -135,6 +162,10 @@ fn dfs(
                     println!("[Solver] output depth = {}", depth);
                     if depth > MAX_TYPE_DEPTH {
                         println!("[Solver] solution is refused because output is too deep.");
+                        add_reason(
+                            &mut self.reasons,
+                            UnsolvableReason::DepthLimit { output: _type_name(output, Some(cache)) },
+                        );
                         return;
                     }
                 }
@@ -145,6 +176,28 @@ fn dfs(
                 self.solutions.push((solution.to_vec(), func, impl_set));
                 self.reserved.push(false);
                 self.success = true;
+                if self.is_num_enough() {
+                    add_reason(
+                        &mut self.reasons,
+                        UnsolvableReason::MonoCap { limit: MAX_MONO_PER_FUNC },
+                    );
+                }
+            } else if let Some((type_, trait_)) = self
+                .current_function
+                .generic_map
+                .find_unsatisfied_bound(&solution, trait_impl_map, type_trait_cache, cache)
+            {
+                let type_ = _type_name(&type_, Some(cache));
+                let trait_ = print_path(&trait_, Some(cache));
+                if self.searching_hint {
+                    println!("[Hint] {} is rejected: {} does not implement {}", solution_string(&solution), type_, trait_);
+                    add_reason(&mut self.reasons, UnsolvableReason::RejectedHint { type_, trait_ });
+                } else {
+                    add_reason(
+                        &mut self.reasons,
+                        UnsolvableReason::UnsatisfiedBound { trait_, candidates: vec![type_] },
+                    );
+                }
             }
             return;
         }
@@ -154,8 +207,17 @@ fn dfs(
                 solution[no] = ty.clone();
                 self.dfs(solution, no + 1, cache, trait_impl_map, type_trait_cache);
             } */
-            self.solvable = false;
-            println!("[Solver] mark function as unsolvable");
+            // the hint may fix this param in another solution, so keep the function solvable
+            if self.hint.is_none() {
+                self.solvable = false;
+                println!("[Solver] mark function as unsolvable");
+            }
+            add_reason(
+                &mut self.reasons,
+                UnsolvableReason::UndeterminedParam {
+                    param: self.current_function.generic_map.generic_defs[no].clone(),
+                },
+            );
             return;
         }
 
@@ -163,14 +225,41 @@ fn dfs(
         self.dfs(solution, no + 1, cache, trait_impl_map, type_trait_cache);
     }
 
-    fn search(&mut self, cache: &Cache, trait_impl_map: &TraitImplMap, type_trait_cache:&mut TypeTraitCache) -> bool {
+    /// substitute the hinted params of type_, params without hint are kept
+    fn apply_seed(&self, type_: &Type, seed: &Solution) -> Type {
+        let generic_defs = &self.current_function.generic_map.generic_defs;
+        let mut res = type_.clone();
+        let mut replace = |ty: &mut Type| -> bool {
+            if let Type::Generic(sym) = ty {
+                for i in 0..generic_defs.len() {
+                    if generic_defs[i] == sym.as_str() && seed[i] != Type::Infer {
+                        *ty = seed[i].clone();
+                        break;
+                    }
+                }
+                return false;
+            }
+            true
+        };
+        replace_type_with(&mut res, &mut replace);
+        res
+    }
+
+    /// Solutions that every generic input can be produced by a reachable type.
+    /// Inputs that are completely fixed by the seed do not need a producer.
+    fn reachable_solution_set(&mut self, seed: Solution, cache: &Cache) -> Vec<Solution> {
         let mut solution_set = Vec::<Solution>::new();
-        solution_set.push(vec![Type::Infer; self.current_function.generic_map.generic_defs.len()]);
+        solution_set.push(seed.clone());
         // get reachable solution set
-        for (i, pat) in self.current_function.api_function.inputs.iter().enumerate() {
+        for i in 0..self.current_function.api_function.inputs.len() {
             if !self.contain_generic[i] {
                 continue;
             }
+            let pat = &self.apply_seed(&self.current_function.api_function.inputs[i], &seed);
+            if !is_generic_type(pat) {
+                println!("[Solver] input argument {} is given by hint", _type_name(pat, None));
+                continue;
+            }
             println!("[Solver] search for input argument {}:", _type_name(pat, None));
             let mut sols = FxHashSet::<Solution>::default();
             for src in self.type_context.borrow().type_candidates.keys() {
@@ -189,18 +278,52 @@ fn search(&mut self, cache: &Cache, trait_impl_map: &TraitImplMap, type_trait_ca
                     sols.insert(sol);
                 }
             }
-            let sols = sols.into_iter().collect();
+            // the set iterates in hash order, sort it to keep the generated drivers stable
+            let mut sols: Vec<Solution> = sols.into_iter().collect();
+            sols.sort_by_cached_key(|sol| solution_string(sol));
             solution_set = merge_solution_set(
                 &solution_set,
                 &sols,
                 &self.current_function.generic_map.generic_defs,
             );
+            if solution_set.is_empty() {
+                // no reachable type fit this argument (together with previous arguments)
+                add_reason(
+                    &mut self.reasons,
+                    UnsolvableReason::NoProducer { input: i, type_: _type_name(pat, Some(cache)) },
+                );
+                break;
+            }
+        }
+        solution_set
+    }
+
+    fn search(&mut self, cache: &Cache, trait_impl_map: &TraitImplMap, type_trait_cache:&mut TypeTraitCache) -> bool {
+        self.success = false;
+        // hinted solutions go first, so they are not dropped by MAX_MONO_PER_FUNC
+        if let Some(hint) = self.hint.clone() {
+            let solution_set = self.reachable_solution_set(hint, cache);
+            println!("[Solver] Hinted Solution Set = {}", solution_set_string(&solution_set));
+            self.searching_hint = true;
+            self.check_solution_set(solution_set, cache, trait_impl_map, type_trait_cache);
+            self.searching_hint = false;
         }
 
+        let len = self.current_function.generic_map.generic_defs.len();
+        let solution_set = self.reachable_solution_set(vec![Type::Infer; len], cache);
         println!("[Solver] Solution Set = {}", solution_set_string(&solution_set));
+        self.check_solution_set(solution_set, cache, trait_impl_map, type_trait_cache);
+        self.success
+    }
 
+    fn check_solution_set(
+        &mut self,
+        solution_set: Vec<Solution>,
+        cache: &Cache,
+        trait_impl_map: &TraitImplMap,
+        type_trait_cache: &mut TypeTraitCache,
+    ) {
         // check type predicate
-        self.success = false;
         for mut solution in solution_set.into_iter() {
             // prevent duplicate
             if self.solution_set.get(&solution).is_some() {
@@ -210,7 +333,6 @@ fn search(&mut self, cache: &Cache, trait_impl_map: &TraitImplMap, type_trait_ca
 
             self.dfs(&mut solution, 0, cache, trait_impl_map, type_trait_cache);
         }
-        self.success
     }
     pub(crate) fn is_solvable(&self) -> bool {
         self.solvable
@@ -228,6 +350,13 @@ pub(crate) fn check_reachable(&mut self, full_name_map: &FullNameMap, cache: &Ca
                         i,
                         _type_name(type_, Some(cache))
                     );
+                    add_reason(
+                        &mut self.reasons,
+                        UnsolvableReason::NoProducer {
+                            input: i,
+                            type_: _type_name(type_, Some(cache)),
+                        },
+                    );
                     success = false;
                 }
             }
@@ -262,6 +391,8 @@ pub(crate) fn solve(
             println!("[Solver] Skip it. It have enough solutions.");
             return;
         }
+        // reachability may change between iterations, only keep the latest one
+        self.reasons.retain(|reason| !reason.is_transient());
         if !self.check_reachable(full_name_map, cache) {
             println!("[Solver] Skip it. It is currently unreachable.");
             return;
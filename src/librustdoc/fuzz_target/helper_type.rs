@@ -0,0 +1,473 @@
+use crate::clean::{self, GenericArgs, GenericBound, GenericParamDefKind, ItemKind};
+use crate::clean::{Path, PathSegment, PrimitiveType, SelfTy, Type};
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::fuzz_target::api_function::{ApiFunction, ApiUnsafety};
+use crate::fuzz_target::api_util::{_type_name, is_support_type, print_path, scan_type_with};
+use crate::fuzz_target::api_util::try_type_name_from_did;
+use crate::html::format::join_with_double_colon;
+use crate::TyCtxt;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{DefId, DefIndex, LOCAL_CRATE};
+use rustc_hir::{Mutability, Safety, TraitBoundModifier};
+use rustc_span::symbol::{kw, Symbol};
+use thin_vec::{thin_vec, ThinVec};
+
+pub(crate) static HELPER_TYPE_NAME: &'static str = "FuzzHelper";
+
+/// traits that hold for the helper without an impl
+static MARKER_TRAITS: &[&str] =
+    &["std::marker::Sized", "std::marker::Send", "std::marker::Sync", "std::marker::Unpin"];
+
+/// std traits derived or implemented in `HELPER_DEFINITION`
+static STD_TRAITS: &[&str] = &[
+    "std::fmt::Debug",
+    "std::clone::Clone",
+    "std::default::Default",
+    "std::cmp::PartialEq",
+    "std::cmp::Eq",
+    "std::hash::Hash",
+    "std::cmp::PartialOrd",
+    "std::cmp::Ord",
+    "std::io::Read",
+    "std::io::BufRead",
+    "std::io::Seek",
+    "std::io::Write",
+    "std::hash::Hasher",
+];
+
+/// The helper keeps the bytes given to its constructor. Every value it returns is taken from them,
+/// `pos` is moved by reads and writes so that repeated calls see different bytes.
+static HELPER_DEFINITION: &'static str = r#"#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct FuzzHelper {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl FuzzHelper {
+    fn new(data: &[u8]) -> Self {
+        FuzzHelper { data: data.to_vec(), pos: 0 }
+    }
+
+    fn fuzz_byte(&self, seed: usize) -> u8 {
+        if self.data.is_empty() {
+            return 0;
+        }
+        self.data[self.pos.wrapping_add(seed) % self.data.len()]
+    }
+
+    fn fuzz_bytes<const N: usize>(&self, seed: usize) -> [u8; N] {
+        let mut res = [0u8; N];
+        for i in 0..N {
+            res[i] = self.fuzz_byte(seed.wrapping_add(i));
+        }
+        res
+    }
+}
+
+impl std::io::Read for FuzzHelper {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let start = self.pos.min(self.data.len());
+        let len = buf.len().min(self.data.len() - start);
+        buf[..len].copy_from_slice(&self.data[start..start + len]);
+        self.pos = start + len;
+        Ok(len)
+    }
+}
+
+impl std::io::BufRead for FuzzHelper {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        let start = self.pos.min(self.data.len());
+        Ok(&self.data[start..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = self.pos.saturating_add(amt).min(self.data.len());
+    }
+}
+
+impl std::io::Seek for FuzzHelper {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            std::io::SeekFrom::Start(n) => Some(n),
+            std::io::SeekFrom::End(n) => (self.data.len() as u64).checked_add_signed(n),
+            std::io::SeekFrom::Current(n) => (self.pos as u64).checked_add_signed(n),
+        };
+        match new_pos {
+            Some(new_pos) => {
+                self.pos = new_pos as usize;
+                Ok(new_pos)
+            }
+            None => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek")),
+        }
+    }
+}
+
+impl std::io::Write for FuzzHelper {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let ctrl = self.fuzz_byte(0);
+        self.pos = self.pos.wrapping_add(1);
+        if ctrl == u8::MAX {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, "fuzz error"));
+        }
+        // partial write, but never report 0 bytes for a non-empty buffer
+        if buf.is_empty() { Ok(0) } else { Ok(1 + ctrl as usize % buf.len()) }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::hash::Hasher for FuzzHelper {
+    fn finish(&self) -> u64 {
+        u64::from_le_bytes(self.fuzz_bytes(0))
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.pos = self.pos.wrapping_add(bytes.len());
+    }
+}
+"#;
+
+/// Fake def id of the helper type. It is only known by `cache.paths`.
+pub(crate) fn helper_did() -> DefId {
+    DefId { krate: LOCAL_CRATE, index: DefIndex::from_u32(DefIndex::MAX_AS_U32) }
+}
+
+/// make the helper type nameable like other local types
+pub(crate) fn register_helper_type(cache: &mut Cache) {
+    cache.paths.insert(helper_did(), (vec![Symbol::intern(HELPER_TYPE_NAME)], ItemType::Struct));
+}
+
+/// path with a single segment and no generic args
+pub(crate) fn simple_path(res: Res, name: Symbol) -> Path {
+    Path {
+        res,
+        segments: thin_vec![PathSegment {
+            name,
+            args: GenericArgs::AngleBracketed { args: Box::new([]), constraints: ThinVec::new() },
+        }],
+    }
+}
+
+/// path of a std trait by its private name, e.g. `core::alloc::Allocator`.
+/// Traits never mentioned by the crate are not in the cache.
+pub(crate) fn find_external_trait(name: &str, cache: &Cache) -> Option<Path> {
+    cache.external_paths.iter().find_map(|(did, (syms, item_type))| {
+        if *item_type == ItemType::Trait && join_with_double_colon(syms) == name {
+            Some(simple_path(Res::Def(DefKind::Trait, *did), *syms.last().unwrap()))
+        } else {
+            None
+        }
+    })
+}
+
+pub(crate) fn helper_type() -> Type {
+    Type::Path {
+        path: simple_path(Res::Def(DefKind::Struct, helper_did()), Symbol::intern(HELPER_TYPE_NAME)),
+    }
+}
+
+/// whether the type of did occurs in the signature of func
+pub(crate) fn mentions_type(func: &ApiFunction, did: DefId) -> bool {
+    let mut found = false;
+    let mut check = |type_: &Type| -> bool {
+        if let Type::Path { path } = type_ {
+            if path.def_id() == did {
+                found = true;
+            }
+        }
+        !found
+    };
+    for input in func.inputs.iter() {
+        scan_type_with(input, &mut check);
+    }
+    if let Some(ref output) = func.output {
+        scan_type_with(output, &mut check);
+    }
+    found
+}
+
+fn has_generic_args(path: &Path) -> bool {
+    match path.segments.last().map(|segment| &segment.args) {
+        Some(GenericArgs::AngleBracketed { args, constraints }) => {
+            !args.is_empty() || !constraints.is_empty()
+        }
+        Some(GenericArgs::Parenthesized { .. }) => true,
+        None => false,
+    }
+}
+
+fn is_number(primitive: &PrimitiveType) -> bool {
+    matches!(
+        primitive,
+        PrimitiveType::I8
+            | PrimitiveType::I16
+            | PrimitiveType::I32
+            | PrimitiveType::I64
+            | PrimitiveType::I128
+            | PrimitiveType::Isize
+            | PrimitiveType::U8
+            | PrimitiveType::U16
+            | PrimitiveType::U32
+            | PrimitiveType::U64
+            | PrimitiveType::U128
+            | PrimitiveType::Usize
+            | PrimitiveType::F32
+            | PrimitiveType::F64
+    )
+}
+
+/// expression of type_ built from the bytes of recv, None if we do not know how to build it
+fn fuzz_value(type_: &Type, recv: &str, seed: usize, cache: &Cache) -> Option<String> {
+    let value = match type_ {
+        Type::Tuple(types) => {
+            let values = types
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| fuzz_value(ty, recv, seed + i * 16, cache))
+                .collect::<Option<Vec<_>>>()?;
+            if values.len() == 1 {
+                format!("({},)", values[0])
+            } else {
+                format!("({})", values.join(", "))
+            }
+        }
+        Type::Primitive(PrimitiveType::Bool) => format!("{}.fuzz_byte({}) & 1 == 1", recv, seed),
+        Type::Primitive(PrimitiveType::Char) => format!(
+            "char::from_u32(u32::from_le_bytes({}.fuzz_bytes({}))).unwrap_or('\\0')",
+            recv, seed
+        ),
+        Type::Primitive(primitive) if is_number(primitive) => {
+            format!("{}::from_le_bytes({}.fuzz_bytes({}))", primitive.as_sym(), recv, seed)
+        }
+        Type::Generic(_) if type_.is_self_type() => format!("{}.clone()", recv),
+        Type::Path { path } => {
+            let args = path.generics().unwrap_or_default();
+            match try_type_name_from_did(path.def_id(), cache)?.as_str() {
+                "std::string::String" => {
+                    format!("String::from_utf8_lossy(&{}.data).into_owned()", recv)
+                }
+                "std::vec::Vec" if args.first() == Some(&&Type::Primitive(PrimitiveType::U8)) => {
+                    format!("{}.data.clone()", recv)
+                }
+                "std::option::Option" => format!(
+                    "if {}.fuzz_byte({}) & 1 == 1 {{ Some({}) }} else {{ None }}",
+                    recv,
+                    seed,
+                    fuzz_value(args.first()?, recv, seed + 1, cache)?
+                ),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// implement a required method, the return value is derived from the helper's bytes
+fn render_method(
+    item: &clean::Item,
+    func: &clean::Function,
+    seed: usize,
+    cache: &Cache,
+    tcx: TyCtxt<'_>,
+) -> Option<String> {
+    let name = item.name?;
+    let header = item.fn_header(tcx)?;
+    if header.is_async() {
+        return None;
+    }
+
+    let mut lifetimes = Vec::new();
+    for param in func.generics.params.iter() {
+        match param.kind {
+            GenericParamDefKind::Lifetime { .. } => lifetimes.push(param.name.to_string()),
+            // a generic method can not be written without knowing its bounds
+            _ => return None,
+        }
+    }
+
+    let mut receiver = None;
+    let mut params = Vec::new();
+    for (i, arg) in func.decl.inputs.values.iter().enumerate() {
+        match arg.to_self() {
+            Some(SelfTy::SelfValue) => {
+                params.push("self".to_string());
+                receiver = Some("self");
+            }
+            Some(SelfTy::SelfBorrowed(lifetime, mutability)) => {
+                let lifetime = lifetime.map_or(String::new(), |lifetime| format!("{} ", lifetime.0));
+                let mut_tag = if mutability == Mutability::Mut { "mut " } else { "" };
+                params.push(format!("&{}{}self", lifetime, mut_tag));
+                receiver = Some("self");
+            }
+            Some(SelfTy::SelfExplicit(_)) => return None,
+            None => {
+                if !is_support_type(&arg.type_) {
+                    return None;
+                }
+                params.push(format!("_arg{}: {}", i, _type_name(&arg.type_, Some(cache))));
+            }
+        }
+    }
+
+    let output = &func.decl.output;
+    let body = if output.is_unit() {
+        String::new()
+    } else {
+        fuzz_value(output, receiver.unwrap_or("Self::default()"), seed, cache)?
+    };
+    let ret = if output.is_unit() {
+        String::new()
+    } else {
+        if !is_support_type(output) {
+            return None;
+        }
+        format!(" -> {}", _type_name(output, Some(cache)))
+    };
+    let lifetimes =
+        if lifetimes.is_empty() { String::new() } else { format!("<{}>", lifetimes.join(", ")) };
+    Some(format!(
+        "    {}fn {}{}({}){} {{\n        {}\n    }}\n",
+        if header.is_unsafe() { "unsafe " } else { "" },
+        name,
+        lifetimes,
+        params.join(", "),
+        ret,
+        body
+    ))
+}
+
+/// A type emitted into the driver, which implements the traits that no reachable type implements.
+pub(crate) struct HelperType {
+    pub(crate) traits: Vec<Path>,    // bounds the helper is registered for
+    local_impls: Vec<String>,        // impl blocks of crate traits
+    checked: FxHashMap<DefId, bool>, // trait => can be implemented by the helper
+    enabled: bool,                   // whether the constructor is added to the graph
+}
+
+impl HelperType {
+    pub(crate) fn new() -> Self {
+        HelperType {
+            traits: Vec::new(),
+            local_impls: Vec::new(),
+            checked: FxHashMap::default(),
+            enabled: false,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// marker traits are decided structurally, they need no registered impl
+    pub(crate) fn is_marker(&self, trait_: &Path, cache: &Cache) -> bool {
+        try_type_name_from_did(trait_.def_id(), cache)
+            .map_or(false, |name| MARKER_TRAITS.contains(&name.as_str()))
+    }
+
+    /// whether the helper can implement trait_. Impls of crate traits are rendered here.
+    pub(crate) fn support(&mut self, trait_: &Path, cache: &Cache, tcx: TyCtxt<'_>) -> bool {
+        let did = trait_.def_id();
+        if let Some(&supported) = self.checked.get(&did) {
+            return supported;
+        }
+        self.checked.insert(did, false);
+
+        let supported = match try_type_name_from_did(did, cache) {
+            Some(name) if MARKER_TRAITS.contains(&name.as_str()) => true,
+            Some(name) if STD_TRAITS.contains(&name.as_str()) => !has_generic_args(trait_),
+            Some(_) if did.is_local() => {
+                if let Some(impl_) = self.render_local_impl(did, cache, tcx) {
+                    self.local_impls.push(impl_);
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        };
+        if !supported {
+            println!("[Helper] {} can not be implemented by helper", print_path(trait_, Some(cache)));
+        }
+        self.checked.insert(did, supported);
+        supported
+    }
+
+    /// Implement the required items of a crate trait, provided methods keep their defaults.
+    fn render_local_impl(&mut self, did: DefId, cache: &Cache, tcx: TyCtxt<'_>) -> Option<String> {
+        let trait_ = cache.traits.get(&did)?;
+        if matches!(trait_.safety(tcx), Safety::Unsafe) || trait_.is_auto(tcx) {
+            return None;
+        }
+        if trait_.generics.params.iter().any(|param| param.name != kw::SelfUpper) {
+            return None;
+        }
+        for bound in trait_.bounds.iter() {
+            if let GenericBound::TraitBound(poly, modifier) = bound {
+                if !matches!(modifier, TraitBoundModifier::Maybe)
+                    && !self.support(&poly.trait_, cache, tcx)
+                {
+                    return None;
+                }
+            }
+        }
+
+        let mut methods = Vec::new();
+        for (no, item) in trait_.items.iter().enumerate() {
+            match *item.kind {
+                ItemKind::TyMethodItem(ref func) => {
+                    methods.push(render_method(item, func, no, cache, tcx)?);
+                }
+                ItemKind::TyAssocTypeItem(..) | ItemKind::TyAssocConstItem(..) => return None,
+                _ => {}
+            }
+        }
+        Some(format!(
+            "impl {} for {} {{\n{}}}\n",
+            try_type_name_from_did(did, cache)?,
+            HELPER_TYPE_NAME,
+            methods.join("\n")
+        ))
+    }
+
+    /// `FuzzHelper::new(&[u8]) -> FuzzHelper`
+    pub(crate) fn constructor(&self) -> ApiFunction {
+        ApiFunction {
+            name: "new".to_string(),
+            full_path: HELPER_TYPE_NAME.to_string(),
+            inputs: vec![Type::BorrowedRef {
+                lifetime: None,
+                mutability: Mutability::Not,
+                type_: Box::new(Type::Slice(Box::new(Type::Primitive(PrimitiveType::U8)))),
+            }],
+            output: Some(helper_type()),
+            self_: Some(helper_type()),
+            trait_: None,
+            _unsafe_tag: ApiUnsafety::Normal,
+            trivial_safety: false,
+            panics_doc: None,
+            mono: false,
+            local: false,
+            rpg_local: false,
+        }
+    }
+
+    /// source of the helper type in driver
+    pub(crate) fn definition(&self) -> String {
+        let mut res = HELPER_DEFINITION.to_string();
+        for impl_ in self.local_impls.iter() {
+            res.push('\n');
+            res.push_str(impl_);
+        }
+        res.push('\n');
+        res
+    }
+}
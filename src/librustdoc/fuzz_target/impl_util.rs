@@ -23,20 +23,23 @@
     self, has_type_parameter, is_unsupported_fuzzable, replace_lifetime,
 };
 use crate::fuzz_target::api_util::{_type_name, replace_type_lifetime};
+use crate::fuzz_target::documented_panic;
 use crate::fuzz_target::fuzzable_type::fuzzable_call_type;
 use crate::fuzz_target::generic_function::GenericFunction;
 use crate::fuzz_target::generic_param_map::GenericParamMap;
 use crate::fuzz_target::prelude_type;
 use crate::fuzz_target::statistic;
+use crate::fuzz_target::unsafe_policy;
 use crate::fuzz_target::trait_impl::TraitImpl;
 use crate::html::format::join_with_double_colon;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
-use rustc_middle::ty::Visibility;
+use rustc_middle::ty::{self, Visibility};
 use rustc_hir::def::CtorKind;
 use rustc_hir::def_id::DefId;
 use rustc_span::Symbol;
 
 use super::api_util::print_path_segment;
+use super::api_util::scan_type_with;
 #[derive(Debug, Clone)]
 pub(crate) struct FullNameMap {
     pub(crate) map: FxHashMap<DefId, (String, ItemType)>,
@@ -73,14 +76,20 @@ fn is_prelude_api(name: &str) -> bool {
     }
 }
 
-fn get_ignore_generic_from_impl(impl_: &Impl) -> Option<String> {
-    match &impl_.kind {
-        ImplKind::Blanket(ref type_) => match **type_ {
-            Type::Generic(ref sym) => Some(sym.to_string()),
-            _ => unreachable!(),
-        },
-        _ => None,
+/// Generic params of a blanket impl that are fixed by its `for_` type.
+/// The blanket type can be a bare `T` as well as a wrapper such as `&T` or `Box<T>`.
+fn get_ignore_generic_from_impl(impl_: &Impl) -> Vec<String> {
+    let mut res = Vec::new();
+    if let ImplKind::Blanket(ref type_) = impl_.kind {
+        let mut collect = |ty: &Type| -> bool {
+            if let Type::Generic(ref sym) = ty {
+                res.push(sym.to_string());
+            }
+            true
+        };
+        scan_type_with(type_, &mut collect);
     }
+    res
 }
 
 pub(crate) fn extract_full_name_from_cache(
@@ -190,13 +199,14 @@ fn extract_trait_impl(impl_: &formats::Impl, api_graph: &mut ApiGraph<'_>) {
         // api_graph.add_type(ty_did, impl_for.clone());
         if let Some(ref trait_path) = impl_.trait_ {
             let mut generic_map = GenericParamMap::new();
-            generic_map.add_generics(&impl_.generics, None);
+            generic_map.add_generics(&impl_.generics, &[]);
             let trait_impl = TraitImpl::new(
                 trait_path.clone(),
                 impl_.for_.clone(),
                 blanket_type,
                 generic_map,
                 impl_did,
+                matches!(impl_.polarity, ty::ImplPolarity::Negative),
             );
             api_graph.trait_impl_map.add_type_trait_impl(ty_did, trait_impl);
             //api_graph.add_type_trait(ty_did, trait_did);
@@ -308,7 +318,12 @@ pub(crate) fn analyse_impl(impl_: &formats::Impl, api_graph: &mut ApiGraph<'_>)
     }
 
     if fuzzable_call_type(&self_type, &api_graph.full_name_map, api_graph.cache()).is_fuzzable()
-        && !is_unsupported_fuzzable(&self_type, &api_graph.full_name_map, api_graph.cache())
+        && !is_unsupported_fuzzable(
+            &self_type,
+            &api_graph.full_name_map,
+            api_graph.cache(),
+            api_graph.input_layout,
+        )
     {
         println!("{} is fuzzable", _type_name(&self_type, Some(api_graph.cache())));
         api_graph.type_context.borrow_mut().add_canonical_types(&self_type, api_graph.cache());
@@ -461,6 +476,10 @@ pub(crate) fn analyse_impl_inner_item(
 
             // this different from method_name for re-export item
 
+            let trivial_safety = api_unsafety._is_unsafe()
+                && unsafe_policy::is_trivial_safety_contract(&item.doc_value(), &inputs);
+            let panics_doc =
+                documented_panic::panics_doc(api_graph.tcx(), &item, &function.decl.inputs);
             let api_function = ApiFunction {
                 name: item.name.as_ref().unwrap().to_string(),
                 full_path: type_full_name,
@@ -473,6 +492,8 @@ pub(crate) fn analyse_impl_inner_item(
                 inputs,
                 output,
                 _unsafe_tag: api_unsafety,
+                trivial_safety,
+                panics_doc,
                 mono: false,
                 local: is_local_impl,
                 rpg_local: impl_for_def_id.map_or(false, |did| did.is_local()) && impl_.trait_.as_ref().map_or(true, |path| path.def_id().is_local()) && !is_default
@@ -492,8 +513,8 @@ pub(crate) fn analyse_impl_inner_item(
                 statistic::inc("UNSAFE");
             }
             let mut generic_function = GenericFunction::from(api_function);
-            generic_function.add_generics(&function.generics, None);
-            generic_function.add_generics(&impl_.generics, get_ignore_generic_from_impl(impl_));
+            generic_function.add_generics(&function.generics, &[]);
+            generic_function.add_generics(&impl_.generics, &get_ignore_generic_from_impl(impl_));
             generic_function.set_self_type(&self_type);            
             // if API has any type parameter declaration, it is a generic API
             if generic_function.generic_map.is_empty() {
@@ -1,12 +1,12 @@
 mod afl_util;
 mod api_function;
-mod api_graph;
+pub(crate) mod api_graph;
 mod api_sequence;
 mod api_util;
 mod call_type;
 mod file_util;
 pub mod fuzz_target_renderer;
-mod fuzzable_type;
+pub(crate) mod fuzzable_type;
 mod generic_function;
 mod impl_util;
 mod mod_visibility;
@@ -19,3 +19,24 @@
 mod trait_impl;
 mod generic_solution;
 mod impl_id;
+mod unsolvable_report;
+mod generic_hint;
+mod helper_type;
+mod evil_type;
+mod alloc_type;
+mod coverage_feedback;
+mod crash_minimizer;
+mod runtime_crate;
+mod op_loop;
+pub(crate) mod unsafe_policy;
+mod differential;
+mod trait_law;
+mod round_trip;
+mod documented_panic;
+pub(crate) mod cargo_fuzz;
+mod driver_workspace;
+mod multiplex;
+mod arbitrary_producer;
+mod regression;
+mod seed_corpus;
+pub(crate) mod named_value;
@@ -0,0 +1,60 @@
+use crate::fuzz_target::api_graph::ApiGraph;
+use crate::fuzz_target::api_sequence::ApiSequence;
+use crate::fuzz_target::cargo_fuzz::FuzzEngine;
+use crate::fuzz_target::replay_util;
+
+/// input bytes picking the sequence, a second one is needed past 256 sequences
+pub(crate) fn selector_len(sequences: usize) -> usize {
+    if sequences <= 256 { 1 } else { 2 }
+}
+
+/// Driver running one of the sequences on every input. The first bytes select it, modulo the
+/// number of sequences so that every input runs one, and the rest is decoded in the layout of
+/// the selected sequence. The replay form reads the input from a crash file instead.
+pub(crate) fn multiplexed_file(
+    api_graph: &ApiGraph<'_>,
+    sequences: &[&ApiSequence],
+    replay: bool,
+) -> String {
+    let mut res = ApiSequence::driver_prelude(api_graph, sequences);
+    if replay {
+        res = res.replace(api_graph.engine.header(), "");
+    }
+    for (index, sequence) in sequences.iter().enumerate() {
+        res.push_str(&sequence.to_well_written_function(api_graph, index, 0));
+        res.push('\n');
+        res.push_str(&format!("fn fuzz_sequence{}(data: &[u8]) {{\n", index));
+        res.push_str(&sequence.afl_closure_body(api_graph, 0, index));
+        res.push_str("}\n\n");
+    }
+    let selector_len = selector_len(sequences.len());
+    let selector = if selector_len == 1 {
+        "selector[0] as usize".to_string()
+    } else {
+        "u16::from_le_bytes([selector[0], selector[1]]) as usize".to_string()
+    };
+    res.push_str(&format!(
+        "fn fuzz_dispatch(data: &[u8]) {{\n    if data.len() < {selector_len} {{return;}}\n    let (selector, data) = data.split_at({selector_len});\n    match {selector} % {sequences} {{\n",
+        selector_len = selector_len,
+        selector = selector,
+        sequences = sequences.len()
+    ));
+    for index in 0..sequences.len() {
+        res.push_str(&format!("        {index} => fuzz_sequence{index}(data),\n", index = index));
+    }
+    res.push_str("        _ => unreachable!(),\n    }\n}\n\n");
+    if replay {
+        res.push_str(replay_util::_read_crash_file_data());
+        res.push('\n');
+        res.push_str(
+            "fn main() {\n    let _content = _read_data();\n    fuzz_dispatch(&_content);\n}\n",
+        );
+    } else if api_graph.engine == FuzzEngine::Libfuzzer {
+        res.push_str("fuzz_target!(|data: &[u8]| {\n    fuzz_dispatch(data);\n});\n");
+    } else {
+        res.push_str(
+            "fn main() {\n    fuzz!(|data: &[u8]| {\n        fuzz_dispatch(data);\n    });\n}\n",
+        );
+    }
+    res
+}
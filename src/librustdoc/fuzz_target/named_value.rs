@@ -0,0 +1,14 @@
+/// Enums whose values are picked by name, from the command line or from a trait path
+pub(crate) trait NamedValue: Copy + 'static {
+    /// every value with its name
+    const NAMES: &'static [(&'static str, Self)];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES.iter().find(|(n, _)| *n == name).map(|(_, value)| *value)
+    }
+
+    /// the names, joined for messages listing the choices
+    fn names() -> String {
+        Self::NAMES.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")
+    }
+}
@@ -0,0 +1,202 @@
+use crate::clean::Type;
+use crate::formats::cache::Cache;
+use crate::fuzz_target::afl_util::_AflHelpers;
+use crate::fuzz_target::api_graph::{ApiGraph, ApiType};
+use crate::fuzz_target::api_sequence::{_generate_indent, ApiSequence, ParamType};
+use crate::fuzz_target::api_util::{type_bucket, TypeBucket};
+use crate::fuzz_target::fuzzable_type::InputLayout;
+use rustc_data_structures::fx::FxHashSet;
+
+/// operations one loop chooses from, a selector byte picks one of them
+static MAX_OPERATIONS: usize = 32;
+/// op loop drivers written next to the chosen sequences
+static MAX_OP_LOOP_DRIVERS: usize = 20;
+
+/// Stateful drivers for the receivers built by the chosen sequences. Every prefix of a chosen
+/// sequence ending in a call that returns a struct or enum builds a receiver once, then the
+/// driver loops over the input: a byte selects one of the functions borrowing the receiver, the
+/// following bytes are its arguments.
+pub(crate) fn op_loop_sequences(
+    api_graph: &ApiGraph<'_>,
+    chosen_sequences: &Vec<ApiSequence>,
+) -> Vec<ApiSequence> {
+    let cache = api_graph.cache();
+    // buckets of the types every function borrows
+    let borrowed_buckets = api_graph
+        .api_functions
+        .iter()
+        .map(|api_function| {
+            api_function
+                .inputs
+                .iter()
+                .filter(|input| matches!(input, Type::BorrowedRef { .. }))
+                .map(|input| type_bucket(input, &api_graph.full_name_map, cache))
+                .collect::<FxHashSet<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut res = Vec::new();
+    let mut constructions = FxHashSet::default();
+    for sequence in chosen_sequences {
+        for receiver in 0..sequence.len() {
+            if res.len() >= MAX_OP_LOOP_DRIVERS {
+                return res;
+            }
+            let calls = sequence.functions[..=receiver]
+                .iter()
+                .map(|api_call| api_call.func.1)
+                .collect::<Vec<_>>();
+            if !constructions.insert(calls.clone()) {
+                continue;
+            }
+            if let Some(op_loop) = op_loop_sequence(api_graph, &calls, &borrowed_buckets) {
+                res.push(op_loop);
+            }
+        }
+    }
+    res
+}
+
+/// the calls building the receiver followed by one call for every operation, `None` if the
+/// receiver has no operations
+fn op_loop_sequence(
+    api_graph: &ApiGraph<'_>,
+    calls: &Vec<usize>,
+    borrowed_buckets: &Vec<FxHashSet<TypeBucket>>,
+) -> Option<ApiSequence> {
+    let mut sequence = ApiSequence::new();
+    for function in calls {
+        sequence = api_graph.is_fun_satisfied(&ApiType::BareFunction, *function, &sequence)?;
+    }
+    let receiver = sequence.len() - 1;
+    let output = api_graph.api_functions[*calls.last()?].output.as_ref()?;
+    let bucket = type_bucket(output, &api_graph.full_name_map, api_graph.cache());
+    if !matches!(bucket, TypeBucket::Path(_)) {
+        return None;
+    }
+    let construction_len = sequence.len();
+    for (function, api_function) in api_graph.api_functions.iter().enumerate() {
+        if sequence.len() - construction_len >= MAX_OPERATIONS {
+            break;
+        }
+        if !borrowed_buckets[function].contains(&bucket) {
+            continue;
+        }
+        let Some(next) = api_graph.is_fun_satisfied(&ApiType::BareFunction, function, &sequence)
+        else {
+            continue;
+        };
+        // run again and again, so the operation borrows the receiver, moves nothing and takes
+        // its other arguments from the construction
+        let api_call = next.functions.last().unwrap();
+        let borrows_receiver = api_call.params.iter().enumerate().any(|(position, param)| {
+            param.0 == ParamType::_FunctionReturn
+                && param.1 == receiver
+                && matches!(api_function.inputs[position], Type::BorrowedRef { .. })
+        });
+        let constructed = api_call
+            .params
+            .iter()
+            .all(|param| param.0 != ParamType::_FunctionReturn || param.1 < construction_len);
+        if borrows_receiver
+            && constructed
+            && next._moved == sequence._moved
+            && next._is_decodable(InputLayout::Sequential)
+        {
+            sequence = next;
+        }
+    }
+    if sequence.len() == construction_len {
+        return None;
+    }
+    sequence.op_loop = Some(construction_len);
+    Some(sequence)
+}
+
+/// Body of the test function of an op loop: the construction, then a loop running the operation
+/// a selector byte picks until the input is used up.
+pub(crate) fn op_loop_body(
+    sequence: &ApiSequence,
+    api_graph: &ApiGraph<'_>,
+    cache: &Cache,
+    outer_indent: usize,
+    param_prefix: &str,
+    local_param_prefix: &str,
+    construction_len: usize,
+) -> String {
+    let indent_size = outer_indent + 4;
+    let indent = _generate_indent(indent_size);
+    let dead_code = sequence._dead_code(api_graph);
+    let statements = |i: usize, indent_size: usize| {
+        call_statements(
+            sequence,
+            api_graph,
+            cache,
+            i,
+            &dead_code,
+            indent_size,
+            param_prefix,
+            local_param_prefix,
+        )
+    };
+    let mut res = String::new();
+    for i in 0..construction_len {
+        res.push_str(&statements(i, indent_size));
+    }
+    res.push_str(&format!("{indent}while !_fdp.is_empty() {{\n", indent = indent));
+    res.push_str(&format!(
+        "{indent}    match _fdp.consume_u8() as usize % {operations} {{\n",
+        indent = indent,
+        operations = sequence.len() - construction_len
+    ));
+    for i in construction_len..sequence.len() {
+        let pattern =
+            if i + 1 == sequence.len() { "_".to_string() } else { (i - construction_len).to_string() };
+        res.push_str(&format!("{indent}        {pattern} => {{\n", indent = indent, pattern = pattern));
+        res.push_str(&statements(i, indent_size + 12));
+        res.push_str(&format!("{indent}        }}\n", indent = indent));
+    }
+    res.push_str(&format!("{indent}    }}\n{indent}}}\n", indent = indent));
+    res
+}
+
+/// the fuzzable params of the `i`th call read from the input, followed by the call
+fn call_statements(
+    sequence: &ApiSequence,
+    api_graph: &ApiGraph<'_>,
+    cache: &Cache,
+    i: usize,
+    dead_code: &[bool],
+    indent_size: usize,
+    param_prefix: &str,
+    local_param_prefix: &str,
+) -> String {
+    let indent = _generate_indent(indent_size);
+    let mut res = String::new();
+    for (param_type, index, _) in &sequence.functions[i].params {
+        if *param_type != ParamType::_FuzzableType {
+            continue;
+        }
+        let fuzzable_param = &sequence.fuzzable_params[*index];
+        let rhs = _AflHelpers::_new_from_fuzzable(fuzzable_param)._generate_param_initial_rhs(
+            0,
+            0,
+            0,
+            0,
+            &String::new(),
+            fuzzable_param,
+            Some(("_fdp", InputLayout::Sequential)),
+        );
+        let mut_tag = if sequence._is_fuzzable_need_mut_tag(*index) { "mut " } else { "" };
+        res.push_str(&format!("{indent}let {mut_tag}{param_prefix}{index} = {rhs};\n"));
+    }
+    res.push_str(&sequence._generate_call_string(
+        api_graph,
+        cache,
+        i,
+        dead_code,
+        &indent,
+        param_prefix,
+        local_param_prefix,
+    ));
+    res
+}
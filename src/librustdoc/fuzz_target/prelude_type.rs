@@ -198,6 +198,10 @@ pub(crate) fn _to_helper_function(&self) -> &'static str {
             _PreludeHelper::_OptionHelper => _unwrap_option_function(),
         }
     }
+
+    pub(crate) fn _all() -> Vec<_PreludeHelper> {
+        vec![_PreludeHelper::_ResultHelper, _PreludeHelper::_OptionHelper]
+    }
 }
 
 fn _unwrap_result_function() -> &'static str {
@@ -0,0 +1,187 @@
+use crate::fuzz_target::alloc_type;
+use crate::fuzz_target::api_graph::ApiGraph;
+use crate::fuzz_target::api_sequence::ApiSequence;
+use crate::fuzz_target::fuzzable_type::FuzzableType;
+
+/// Spells a decoded value out as a Rust expression of its type. Floats that have no literal
+/// are written as their constants.
+static LITERAL_DEFINITION: &'static str = r#"trait FuzzLiteral {
+    fn fuzz_literal(&self) -> String;
+}
+
+macro_rules! fuzz_literal_display {
+    ($($ty:ty),*) => {
+        $(
+            impl FuzzLiteral for $ty {
+                fn fuzz_literal(&self) -> String {
+                    format!("{}", self)
+                }
+            }
+        )*
+    };
+}
+
+fuzz_literal_display!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, bool);
+
+macro_rules! fuzz_literal_float {
+    ($($ty:ident),*) => {
+        $(
+            impl FuzzLiteral for $ty {
+                fn fuzz_literal(&self) -> String {
+                    if self.is_nan() {
+                        format!("{}::NAN", stringify!($ty))
+                    } else if self.is_infinite() && *self > 0.0 {
+                        format!("{}::INFINITY", stringify!($ty))
+                    } else if self.is_infinite() {
+                        format!("{}::NEG_INFINITY", stringify!($ty))
+                    } else {
+                        format!("{:?}", self)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+fuzz_literal_float!(f32, f64);
+
+impl FuzzLiteral for char {
+    fn fuzz_literal(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+"#;
+
+/// Reads the crash input, decodes it like the driver and prints the regression test with the
+/// arguments filled in
+static MAIN_DEFINITION: &'static str = r#"fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 2 {
+        eprintln!("usage: {} <crash input>", args[0]);
+        std::process::exit(2);
+    }
+    let data = std::fs::read(&args[1]).unwrap();
+    let mut arguments = Vec::new();
+    fuzz_regression_arguments(&data, &mut arguments);
+    if arguments.len() != FUZZ_ARGUMENT_NUMBER {
+        eprintln!("{} is not an input of the driver", args[1]);
+        std::process::exit(1);
+    }
+    print!("{}", FUZZ_REGRESSION_TEST.replace("FUZZ_ARGUMENTS", &arguments.join(", ")));
+}
+"#;
+
+/// Why the driver of `sequence` cannot become a plain test, if it cannot
+pub(crate) fn unsupported(
+    api_graph: &ApiGraph<'_>,
+    sequence: &ApiSequence,
+) -> Option<&'static str> {
+    if sequence.op_loop.is_some() {
+        Some("is an op loop driver")
+    } else if api_graph.global_allocator {
+        Some("fails allocations on a schedule read from the input")
+    } else {
+        None
+    }
+}
+
+/// Source of a program that decodes a crash input of the driver of `sequence` and prints a
+/// test file for the `tests` directory of the crate: the test function of the driver with the
+/// calls spelled out, called by a `#[test]` with the decoded arguments as literals. The test
+/// needs neither a fuzzer nor the decoding helpers.
+pub(crate) fn regression_file(
+    api_graph: &ApiGraph<'_>,
+    sequence: &ApiSequence,
+    driver: &str,
+    test_index: usize,
+) -> String {
+    let prelude = ApiSequence::driver_prelude(api_graph, &[sequence]);
+    // run in a plain process, afl is not needed
+    let mut res = prelude.replace(api_graph.engine.header(), "");
+    res.push_str(LITERAL_DEFINITION);
+    res.push('\n');
+    res.push_str("fn fuzz_regression_arguments(data: &[u8], fuzz_arguments: &mut Vec<String>) {\n");
+    res.push_str(&sequence.decode_statements(api_graph, 4));
+    for (index, fuzzable_param) in sequence.fuzzable_params.iter().enumerate() {
+        let literal = literal_expression(fuzzable_param, &format!("&_param{}", index));
+        res.push_str(&format!("    fuzz_arguments.push({});\n", literal));
+    }
+    res.push_str("}\n\n");
+    res.push_str(&format!(
+        "static FUZZ_ARGUMENT_NUMBER: usize = {};\n\nstatic FUZZ_REGRESSION_TEST: &str = {:?};\n\n",
+        sequence.fuzzable_params.len(),
+        test_file(api_graph, sequence, driver, test_index)
+    ));
+    res.push_str(MAIN_DEFINITION);
+    res
+}
+
+/// The printed test file, `FUZZ_ARGUMENTS` stands for the decoded arguments
+fn test_file(
+    api_graph: &ApiGraph<'_>,
+    sequence: &ApiSequence,
+    driver: &str,
+    test_index: usize,
+) -> String {
+    let mut res = format!("// regression test written from a crash input of {}\n", driver);
+    // the allocator type is the only driver-local type needing nightly
+    if sequence.uses_type(api_graph, alloc_type::alloc_did()) {
+        res.push_str("#![feature(allocator_api)]\n#![feature(alloc_error_hook)]\n");
+    }
+    res.push('\n');
+    let mut prelude_helpers = sequence.prelude_helpers();
+    prelude_helpers.sort();
+    for prelude_helper in prelude_helpers {
+        res.push_str(prelude_helper);
+        res.push('\n');
+    }
+    res.push_str(&ApiSequence::driver_definitions(api_graph, &[sequence]));
+    res.push_str(&sequence.to_well_written_function(api_graph, test_index, 0));
+    res.push_str(&format!(
+        "\n#[test]\nfn regression{}() {{\n    test_function{}(FUZZ_ARGUMENTS);\n}}\n",
+        &driver["test".len()..],
+        test_index
+    ));
+    res
+}
+
+/// Expression printing the value behind the reference `value` as a literal of its fuzzable type
+fn literal_expression(fuzzable_param: &FuzzableType, value: &str) -> String {
+    match fuzzable_param {
+        FuzzableType::NoFuzzable => unreachable!(),
+        FuzzableType::Primitive(_) => format!("FuzzLiteral::fuzz_literal({})", value),
+        FuzzableType::RefStr => format!("format!(\"{{:?}}\", {})", value),
+        FuzzableType::RefSlice(inner) => format!(
+            "format!(\"&[{{}}]\", {}.iter().map(|fuzz_item| {}).collect::<Vec<_>>().join(\", \"))",
+            value,
+            literal_expression(inner, "fuzz_item")
+        ),
+        FuzzableType::Tuple(inners) if inners.is_empty() => "\"()\".to_string()".to_string(),
+        FuzzableType::Struct(name, _, fields) if fields.is_empty() => {
+            format!("{:?}.to_string()", format!("{} {{}}", name))
+        }
+        FuzzableType::Tuple(inners) => {
+            let items = inners
+                .iter()
+                .enumerate()
+                .map(|(index, inner)| {
+                    literal_expression(inner, &format!("&({}).{}", value, index))
+                })
+                .collect::<Vec<_>>();
+            format!("format!(\"({{}},)\", [{}].join(\", \"))", items.join(", "))
+        }
+        FuzzableType::Struct(name, _, fields) => {
+            let fields = fields
+                .iter()
+                .map(|(field, inner)| {
+                    format!(
+                        "format!(\"{}: {{}}\", {})",
+                        field,
+                        literal_expression(inner, &format!("&({}).{}", value, field))
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("format!(\"{} {{{{ {{}} }}}}\", [{}].join(\", \"))", name, fields.join(", "))
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use crate::clean::{PrimitiveType, Type};
+use crate::formats::cache::Cache;
+use crate::fuzz_target::api_graph::{ApiGraph, ApiType};
+use crate::fuzz_target::api_sequence::{_generate_indent, ApiCall, ApiSequence, ParamType};
+use crate::fuzz_target::api_util::{self, _type_name, try_type_name_from_did};
+use crate::fuzz_target::call_type::CallType;
+use crate::fuzz_target::prelude_type::PreludeType;
+use crate::fuzz_target::trait_impl::{path_type_args, TraitImpl};
+use crate::fuzz_target::trait_law::shortest_producers;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::Mutability;
+use rustc_span::def_id::DefId;
+use rustc_span::symbol::kw;
+
+#[cfg(test)]
+mod tests;
+
+/// round trip drivers written next to the chosen sequences
+static MAX_ROUND_TRIP_DRIVERS: usize = 20;
+
+/// Prefixes of the name of an encoding function and of the function inverting it, the rest of
+/// both names has to be the same, e.g. `to_bytes` and `from_bytes`
+static MIRRORED_PREFIXES: &'static [(&'static str, &'static str)] = &[
+    ("to_", "from_"),
+    ("to_", "try_from_"),
+    ("as_", "from_"),
+    ("encode", "decode"),
+    ("serialize", "deserialize"),
+    ("compress", "decompress"),
+];
+
+/// How the value is written and read back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RoundTrip {
+    /// `FromStr` parses what `Display` printed
+    Display,
+    /// `serde_json` reads what it wrote with `Serialize` back with `Deserialize`
+    Serde,
+    /// the last call decodes what the call before encoded, returning the type itself
+    Codec,
+    /// same, returning an `Option`
+    CodecOption,
+    /// same, returning a `Result`
+    CodecResult,
+}
+
+/// The call producing the value written and read back, and the name of its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RoundTripCheck {
+    pub(crate) value: usize,
+    pub(crate) type_name: String,
+    pub(crate) round_trip: RoundTrip,
+}
+
+/// How the decoder borrows the encoded value when it takes the borrowed form of it, e.g.
+/// `from_bytes(&[u8])` after `to_bytes() -> Vec<u8>` or `decode(&str)` after `encode() -> String`.
+/// The graph reads such params from the fuzz input, so the call is built here.
+fn borrowed_encoding(encoded: &Type, decoder_input: &Type, cache: &Cache) -> Option<CallType> {
+    let Type::BorrowedRef { mutability: Mutability::Not, type_: borrowed, .. } = decoder_input
+    else {
+        return None;
+    };
+    let Type::Path { path } = encoded else {
+        return None;
+    };
+    let name = try_type_name_from_did(path.def_id(), cache)?;
+    let owns_borrowed = match (&**borrowed, name.as_str()) {
+        (Type::Primitive(PrimitiveType::Str), "std::string::String") => true,
+        (Type::Slice(element), "std::vec::Vec") => path_type_args(path).next() == Some(&**element),
+        _ => false,
+    };
+    // `&*encoded`
+    owns_borrowed.then(|| {
+        CallType::_BorrowedRef(Box::new(CallType::_Deref(Box::new(CallType::_DirectCall))))
+    })
+}
+
+/// whether the names are those of a function and its inverse
+fn is_mirrored(encode: &str, decode: &str) -> bool {
+    MIRRORED_PREFIXES.iter().any(|(encode_prefix, decode_prefix)| {
+        match (encode.strip_prefix(encode_prefix), decode.strip_prefix(decode_prefix)) {
+            (Some(encoded), Some(decoded)) => encoded == decoded,
+            _ => false,
+        }
+    })
+}
+
+/// Drivers asserting that local types comparable with `PartialEq` survive being written and
+/// read back: through `Display` and `FromStr`, through serde, or through a pair of functions
+/// with mirrored names (`to_bytes` and `from_bytes`, `encode` and `decode`, ...) where the
+/// first borrows the value and the second returns the type again. The shortest sequence
+/// returning the type builds the value.
+pub(crate) fn round_trip_sequences(api_graph: &ApiGraph<'_>) -> Vec<ApiSequence> {
+    // the driver types live in the local crate too, but are not the crate's to check
+    let impls_of = |did: DefId| {
+        api_graph
+            .trait_impl_map
+            .inner
+            .get(&did)
+            .filter(|_| api_util::is_crate_type(did))
+            .into_iter()
+            .flatten()
+            .filter(|trait_impl| {
+                !trait_impl.negative
+                    && trait_impl.for_.generics().map_or(true, |generics| generics.is_empty())
+            })
+    };
+    let trait_name = |trait_impl: &TraitImpl| {
+        api_graph.full_name_map.get_full_name(trait_impl.trait_.def_id()).cloned()
+    };
+    let traits_of = |did: DefId| impls_of(did).filter_map(trait_name).collect::<FxHashSet<_>>();
+    // `PartialEq<Other>` can not compare the value read back with the one written
+    let comparable = |did: DefId| {
+        impls_of(did).any(|trait_impl| {
+            trait_name(trait_impl).as_deref() == Some("core::cmp::PartialEq")
+                && compares_with_self(trait_impl, did)
+        })
+    };
+    let mut res = Vec::new();
+    for (did, producer) in shortest_producers(api_graph, comparable) {
+        let traits = traits_of(did);
+        let value = producer.len() - 1;
+        let output = api_graph.api_functions[producer.last_api_func_index().unwrap()]
+            .output
+            .as_ref()
+            .unwrap();
+        let check = |round_trip| RoundTripCheck {
+            value,
+            type_name: _type_name(output, Some(api_graph.cache())),
+            round_trip,
+        };
+        let mut round_trips = Vec::new();
+        if traits.contains("core::fmt::Display") && traits.contains("core::str::traits::FromStr") {
+            round_trips.push((producer.clone(), check(RoundTrip::Display)));
+        }
+        if traits.contains("serde::ser::Serialize") && traits.contains("serde::de::Deserialize") {
+            round_trips.push((producer.clone(), check(RoundTrip::Serde)));
+        }
+        for (sequence, round_trip) in codec_sequences(api_graph, did, producer) {
+            round_trips.push((sequence, check(round_trip)));
+        }
+        for (mut sequence, round_trip) in round_trips {
+            if res.len() >= MAX_ROUND_TRIP_DRIVERS {
+                return res;
+            }
+            sequence.round_trip = Some(round_trip);
+            res.push(sequence);
+        }
+    }
+    res
+}
+
+/// whether the `PartialEq` impl compares the type with itself, `Rhs` defaults to `Self`
+fn compares_with_self(trait_impl: &TraitImpl, did: DefId) -> bool {
+    match path_type_args(&trait_impl.trait_).collect::<Vec<_>>().as_slice() {
+        [] => true,
+        [Type::Path { path }] => path.def_id() == did,
+        [Type::Generic(name)] => *name == kw::SelfUpper,
+        _ => false,
+    }
+}
+
+/// the producer followed by an encoding function borrowing the value and the decoding function
+/// taking what it returned, or the borrowed form of it
+fn codec_sequences(
+    api_graph: &ApiGraph<'_>,
+    did: DefId,
+    producer: &ApiSequence,
+) -> Vec<(ApiSequence, RoundTrip)> {
+    let cache = api_graph.cache();
+    let value = producer.len() - 1;
+    let is_type = |type_: &Type| matches!(type_, Type::Path { path } if path.def_id() == did);
+    let decoders = api_graph
+        .api_functions
+        .iter()
+        .enumerate()
+        .filter_map(|(decoder, api_function)| {
+            let output = api_function.output.as_ref()?;
+            let (round_trip, decoded) =
+                match PreludeType::from_type(output, &api_graph.full_name_map, cache) {
+                    Some(prelude @ PreludeType::PreludeOption(..)) => {
+                        (RoundTrip::CodecOption, prelude._get_final_type())
+                    }
+                    Some(prelude @ PreludeType::PreludeResult { .. }) => {
+                        (RoundTrip::CodecResult, prelude._get_final_type())
+                    }
+                    None => (RoundTrip::Codec, output.clone()),
+                };
+            is_type(&decoded).then_some((decoder, round_trip))
+        })
+        .collect::<Vec<_>>();
+    // both calls take their input from the call before, the value stays alive
+    let takes_former = |sequence: &ApiSequence, former: usize| {
+        sequence.functions.last().unwrap().params.iter().any(|param| {
+            param.0 == ParamType::_FunctionReturn && param.1 == former
+        })
+    };
+    let mut res = Vec::new();
+    for (encoder, api_function) in api_graph.api_functions.iter().enumerate() {
+        let borrows_value = match api_function.inputs.as_slice() {
+            [Type::BorrowedRef { type_, .. }] => is_type(type_),
+            _ => false,
+        };
+        if !borrows_value || api_function.output.is_none() {
+            continue;
+        }
+        for (decoder, round_trip) in &decoders {
+            if !is_mirrored(&api_function.name, &api_graph.api_functions[*decoder].name) {
+                continue;
+            }
+            let Some(encoded) =
+                api_graph.is_fun_satisfied(&ApiType::BareFunction, encoder, producer)
+            else {
+                continue;
+            };
+            let decoder_function = &api_graph.api_functions[*decoder];
+            let borrow = match (api_function.output.as_ref(), decoder_function.inputs.as_slice()) {
+                (Some(output), [input]) if !decoder_function._unsafe_tag._is_unsafe() => {
+                    borrowed_encoding(output, input, cache)
+                }
+                _ => None,
+            };
+            let decoded = if let Some(call_type) = borrow {
+                let mut decoded = encoded.clone();
+                if let Some(ref trait_) = decoder_function.trait_ {
+                    decoded.add_trait(trait_.def_id(cache).unwrap());
+                }
+                let mut call = ApiCall::_new(*decoder);
+                call._add_param(ParamType::_FunctionReturn, encoded.len() - 1, call_type);
+                decoded.add_fn(call, decoder_function.is_mono());
+                decoded
+            } else {
+                let Some(decoded) =
+                    api_graph.is_fun_satisfied(&ApiType::BareFunction, *decoder, &encoded)
+                else {
+                    continue;
+                };
+                decoded
+            };
+            if takes_former(&encoded, value)
+                && takes_former(&decoded, encoded.len() - 1)
+                && !decoded.is_moved(value)
+                && decoded._is_decodable(api_graph.input_layout)
+            {
+                res.push((decoded, *round_trip));
+            }
+        }
+    }
+    res
+}
+
+/// Assertion that the value read back equals the value written
+pub(crate) fn round_trip_statements(
+    check: &RoundTripCheck,
+    decoded: usize,
+    local_param_prefix: &str,
+    indent_size: usize,
+) -> String {
+    let indent = _generate_indent(indent_size);
+    let value = format!("{}{}", local_param_prefix, check.value);
+    let decoded = format!("{}{}", local_param_prefix, decoded);
+    match check.round_trip {
+        RoundTrip::Display => format!(
+            "{indent}let fuzz_text = std::string::ToString::to_string(&{value});\n{indent}let fuzz_parsed = <{type_name} as std::str::FromStr>::from_str(&fuzz_text);\n{indent}assert!(fuzz_parsed.as_ref().ok() == Some(&{value}), \"FromStr does not read back what Display printed\");\n",
+            indent = indent,
+            value = value,
+            type_name = check.type_name
+        ),
+        RoundTrip::Serde => format!(
+            "{indent}if let Ok(fuzz_json) = serde_json::to_string(&{value}) {{\n{indent}    let fuzz_parsed = serde_json::from_str::<{type_name}>(&fuzz_json);\n{indent}    assert!(fuzz_parsed.as_ref().ok() == Some(&{value}), \"Deserialize does not read back what Serialize wrote\");\n{indent}}}\n",
+            indent = indent,
+            value = value,
+            type_name = check.type_name
+        ),
+        RoundTrip::Codec => format!(
+            "{indent}assert!({decoded} == {value}, \"decoding does not invert encoding\");\n",
+            indent = indent,
+            decoded = decoded,
+            value = value
+        ),
+        RoundTrip::CodecOption => format!(
+            "{indent}assert!({decoded}.as_ref() == Some(&{value}), \"decoding does not invert encoding\");\n",
+            indent = indent,
+            decoded = decoded,
+            value = value
+        ),
+        RoundTrip::CodecResult => format!(
+            "{indent}assert!({decoded}.as_ref().ok() == Some(&{value}), \"decoding does not invert encoding\");\n",
+            indent = indent,
+            decoded = decoded,
+            value = value
+        ),
+    }
+}
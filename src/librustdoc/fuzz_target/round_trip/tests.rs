@@ -0,0 +1,79 @@
+use super::{borrowed_encoding, is_mirrored};
+use crate::clean::{GenericArg, GenericArgs, Path, PathSegment, PrimitiveType, Type};
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::fuzz_target::call_type::CallType;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex};
+use rustc_hir::Mutability;
+use rustc_span::create_default_session_globals_then;
+use rustc_span::Symbol;
+use thin_vec::{thin_vec, ThinVec};
+
+#[test]
+fn test_is_mirrored() {
+    assert!(is_mirrored("to_bytes", "from_bytes"));
+    assert!(is_mirrored("to_bytes", "try_from_bytes"));
+    assert!(is_mirrored("as_str", "from_str"));
+    assert!(is_mirrored("encode", "decode"));
+    assert!(is_mirrored("encode_utf8", "decode_utf8"));
+    assert!(is_mirrored("serialize", "deserialize"));
+    assert!(is_mirrored("compress", "decompress"));
+}
+
+#[test]
+fn test_is_not_mirrored() {
+    assert!(!is_mirrored("to_bytes", "from_str"));
+    assert!(!is_mirrored("from_bytes", "to_bytes"));
+    assert!(!is_mirrored("decode", "encode"));
+    assert!(!is_mirrored("encode_utf8", "decode"));
+    assert!(!is_mirrored("to_string", "parse"));
+}
+
+fn std_type(cache: &mut Cache, index: u32, name: &str, args: Vec<Type>) -> Type {
+    let did = DefId { krate: CrateNum::from_u32(1), index: DefIndex::from_u32(index) };
+    let syms = name.split("::").map(Symbol::intern).collect::<Vec<_>>();
+    cache.external_paths.insert(did, (syms, ItemType::Struct));
+    let args = args.into_iter().map(GenericArg::Type).collect::<Vec<_>>().into_boxed_slice();
+    Type::Path {
+        path: Path {
+            res: Res::Def(DefKind::Struct, did),
+            segments: thin_vec![PathSegment {
+                name: Symbol::intern(name.rsplit("::").next().unwrap()),
+                args: GenericArgs::AngleBracketed { args, constraints: ThinVec::new() },
+            }],
+        },
+    }
+}
+
+fn borrowed(type_: Type, mutability: Mutability) -> Type {
+    Type::BorrowedRef { lifetime: None, mutability, type_: Box::new(type_) }
+}
+
+#[test]
+fn test_borrowed_encoding() {
+    create_default_session_globals_then(|| {
+        let mut cache = Cache::new(false, false);
+        let u8_ = Type::Primitive(PrimitiveType::U8);
+        let vec_u8 = std_type(&mut cache, 0, "alloc::vec::Vec", vec![u8_.clone()]);
+        let string = std_type(&mut cache, 1, "alloc::string::String", vec![]);
+        let bytes = borrowed(Type::Slice(Box::new(u8_)), Mutability::Not);
+        let str_ = borrowed(Type::Primitive(PrimitiveType::Str), Mutability::Not);
+        let deref =
+            CallType::_BorrowedRef(Box::new(CallType::_Deref(Box::new(CallType::_DirectCall))));
+        // `to_bytes(&self) -> Vec<u8>` and `from_bytes(&[u8])`
+        assert_eq!(borrowed_encoding(&vec_u8, &bytes, &cache), Some(deref.clone()));
+        // `encode(&self) -> String` and `decode(&str)`
+        assert_eq!(borrowed_encoding(&string, &str_, &cache), Some(deref));
+
+        let words =
+            borrowed(Type::Slice(Box::new(Type::Primitive(PrimitiveType::U16))), Mutability::Not);
+        assert_eq!(borrowed_encoding(&vec_u8, &words, &cache), None);
+        assert_eq!(borrowed_encoding(&string, &bytes, &cache), None);
+        let bytes_mut =
+            borrowed(Type::Slice(Box::new(Type::Primitive(PrimitiveType::U8))), Mutability::Mut);
+        assert_eq!(borrowed_encoding(&vec_u8, &bytes_mut, &cache), None);
+        // the owned form is passed as it is
+        assert_eq!(borrowed_encoding(&vec_u8, &vec_u8, &cache), None);
+    });
+}
@@ -0,0 +1,195 @@
+use crate::fuzz_target::afl_util;
+use crate::fuzz_target::prelude_type::_PreludeHelper;
+use std::fs;
+use std::path::PathBuf;
+
+pub(crate) static RUNTIME_CRATE_NAME: &'static str = "rumono_runtime";
+
+static RUNTIME_CRATE_VERSION: &'static str = "0.1.0";
+
+/// Reads the params of a driver one after another, in the layout of `afl_closure_body`: the
+/// fixed length params from the front, the dynamic length ones from equal slots of the rest,
+/// the last slot taking the remaining bytes.
+///
+/// In the sequential layout there is a single cursor instead. Dynamic length values are preceded
+/// by a length byte counting their elements, and whatever runs past the end of the input reads
+/// as zeroes. A `&str` is the valid UTF-8 prefix of its bytes and an invalid `char` is U+FFFD,
+/// so every input decodes, while the split layout exits on them.
+static PROVIDER_DEFINITION: &'static str = r#"pub struct FuzzedDataProvider<'a> {
+    data: &'a [u8],
+    fixed_index: usize,
+    fixed_len: usize,
+    dynamic_index: usize,
+    dynamic_num: usize,
+    dynamic_length: usize,
+    sequential: bool,
+}
+
+macro_rules! consume_fixed {
+    ($($name:ident => $to:ident: $ty:ty, $len:expr;)*) => {
+        $(
+            pub fn $name(&mut self) -> $ty {
+                if self.sequential {
+                    let mut bytes = [0u8; 16];
+                    let available = self.data.len().saturating_sub(self.fixed_index).min($len);
+                    bytes[..available]
+                        .copy_from_slice(&self.data[self.fixed_index..self.fixed_index + available]);
+                    self.fixed_index += available;
+                    return $to(&bytes, 0);
+                }
+                let value = $to(self.data, self.fixed_index);
+                self.fixed_index += $len;
+                value
+            }
+        )*
+    };
+}
+
+impl<'a> FuzzedDataProvider<'a> {
+    /// `data` must hold at least the fixed part, as checked by the driver
+    pub fn new(data: &'a [u8], fixed_len: usize, dynamic_num: usize) -> Self {
+        let dynamic_length =
+            if dynamic_num == 0 { 0 } else { (data.len() - fixed_len) / dynamic_num };
+        FuzzedDataProvider {
+            data,
+            fixed_index: 0,
+            fixed_len,
+            dynamic_index: 0,
+            dynamic_num,
+            dynamic_length,
+            sequential: false,
+        }
+    }
+
+    /// reader of the sequential layout, any `data` will do
+    pub fn sequential(data: &'a [u8]) -> Self {
+        FuzzedDataProvider {
+            data,
+            fixed_index: 0,
+            fixed_len: 0,
+            dynamic_index: 0,
+            dynamic_num: 0,
+            dynamic_length: 0,
+            sequential: true,
+        }
+    }
+
+    consume_fixed! {
+        consume_u8 => _to_u8: u8, 1;
+        consume_i8 => _to_i8: i8, 1;
+        consume_u16 => _to_u16: u16, 2;
+        consume_i16 => _to_i16: i16, 2;
+        consume_u32 => _to_u32: u32, 4;
+        consume_i32 => _to_i32: i32, 4;
+        consume_u64 => _to_u64: u64, 8;
+        consume_i64 => _to_i64: i64, 8;
+        consume_u128 => _to_u128: u128, 16;
+        consume_i128 => _to_i128: i128, 16;
+        consume_usize => _to_usize: usize, 8;
+        consume_isize => _to_isize: isize, 8;
+        consume_f32 => _to_f32: f32, 4;
+        consume_f64 => _to_f64: f64, 8;
+        consume_bool => _to_bool: bool, 1;
+    }
+
+    pub fn consume_char(&mut self) -> char {
+        if self.sequential {
+            return char::from_u32(self.consume_u32()).unwrap_or(char::REPLACEMENT_CHARACTER);
+        }
+        let value = _to_char(self.data, self.fixed_index);
+        self.fixed_index += 4;
+        value
+    }
+
+    pub fn consume_str(&mut self) -> &'a str {
+        let (start, end) = self.next_slot(1);
+        if self.sequential {
+            let bytes = &self.data[start..end];
+            let valid = match std::str::from_utf8(bytes) {
+                Ok(s) => return s,
+                Err(e) => e.valid_up_to(),
+            };
+            return std::str::from_utf8(&bytes[..valid]).unwrap();
+        }
+        _to_str(self.data, start, end)
+    }
+
+    pub fn consume_slice<T>(&mut self) -> &'a [T] {
+        let (start, end) = self.next_slot(std::mem::size_of::<T>());
+        _to_slice::<T>(self.data, start, end)
+    }
+
+    /// whether the sequential layout has read the whole input
+    pub fn is_empty(&self) -> bool {
+        self.fixed_index >= self.data.len()
+    }
+
+    /// elements that can not be borrowed from the input are decoded one by one, sequential
+    /// layout only
+    pub fn consume_vec<T>(&mut self, mut f: impl FnMut(&mut Self) -> T) -> Vec<T> {
+        let len = self.consume_len();
+        (0..len).map(|_| f(self)).collect()
+    }
+
+    fn consume_len(&mut self) -> usize {
+        self.consume_u8() as usize
+    }
+
+    fn next_slot(&mut self, element_size: usize) -> (usize, usize) {
+        if self.sequential {
+            let len = self.consume_len() * element_size;
+            let start = self.fixed_index;
+            let end = (start + len).min(self.data.len());
+            self.fixed_index = end;
+            return (start, end);
+        }
+        let start = self.fixed_len + self.dynamic_index * self.dynamic_length;
+        self.dynamic_index += 1;
+        let end = if self.dynamic_index == self.dynamic_num {
+            self.data.len()
+        } else {
+            start + self.dynamic_length
+        };
+        (start, end)
+    }
+}
+"#;
+
+/// `src/lib.rs` of the runtime crate: the helper functions pasted into drivers otherwise, made
+/// public, and the reader built on them
+pub(crate) fn lib_source() -> String {
+    let mut res = String::new();
+    res.push_str("//! Input decoding shared by the fuzz drivers generated by RuMono.\n");
+    res.push_str("#![allow(dead_code)]\n\n");
+    let helper_functions = afl_util::_all_helper_functions()
+        .into_iter()
+        .chain(_PreludeHelper::_all().iter().map(|helper| helper._to_helper_function()));
+    for helper_function in helper_functions {
+        res.push_str("pub ");
+        res.push_str(helper_function);
+        res.push('\n');
+    }
+    res.push_str(PROVIDER_DEFINITION);
+    res
+}
+
+pub(crate) fn manifest() -> String {
+    format!(
+        "[package]\nname = \"{}\"\nversion = \"{}\"\nedition = \"2021\"\n",
+        RUNTIME_CRATE_NAME, RUNTIME_CRATE_VERSION
+    )
+}
+
+/// write the crate into `dir/rumono_runtime`, drivers depend on it by path
+pub(crate) fn write_runtime_crate(dir: &PathBuf) {
+    let crate_dir = dir.join(RUNTIME_CRATE_NAME);
+    let src_dir = crate_dir.join("src");
+    println!("runtime_crate_path: {crate_dir:?}");
+    println!(
+        "[Runtime] driver projects outside the generated workspace need `{} = {{ path = {:?} }}`",
+        RUNTIME_CRATE_NAME, crate_dir
+    );
+    fs::create_dir_all(&src_dir).unwrap();
+    fs::write(crate_dir.join("Cargo.toml"), manifest()).unwrap();
+    fs::write(src_dir.join("lib.rs"), lib_source()).unwrap();
+}
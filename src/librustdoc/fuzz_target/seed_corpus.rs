@@ -0,0 +1,482 @@
+use crate::clean::PrimitiveType;
+use crate::fuzz_target::api_graph::ApiGraph;
+use crate::fuzz_target::api_sequence::{ApiSequence, ParamType};
+use crate::fuzz_target::fuzzable_type::{FuzzableType, InputLayout};
+use crate::fuzz_target::multiplex;
+use crate::TyCtxt;
+use rustc_ast::LitKind;
+use rustc_hir as hir;
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_middle::hir::nested_filter;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod tests;
+
+/// seeds written for one driver at most
+static MAX_SEEDS: usize = 16;
+/// seeds of every sequence in the multiplexed driver
+static MULTIPLEXED_SEEDS: usize = 2;
+/// literals of each kind kept, the ones closest to zero or shortest first
+static MAX_LITERALS: usize = 32;
+/// longer string literals are left out, the length byte of the sequential layout must hold them
+static MAX_LITERAL_LEN: usize = 64;
+
+/// Literals in the function bodies of the crate, the values its code is likely to compare the
+/// input against
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CrateLiterals {
+    ints: Vec<i128>,
+    floats: Vec<f64>,
+    chars: Vec<char>,
+    strs: Vec<String>,
+    bytes: Vec<Vec<u8>>,
+}
+
+struct LiteralCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    ints: BTreeSet<i128>,
+    floats: BTreeSet<u64>, // bits, f64 is not Ord
+    chars: BTreeSet<char>,
+    strs: BTreeSet<String>,
+    bytes: BTreeSet<Vec<u8>>,
+}
+
+impl<'tcx> LiteralCollector<'tcx> {
+    fn insert(&mut self, kind: &LitKind, negative: bool) {
+        match *kind {
+            LitKind::Int(value, _) => {
+                if let Ok(value) = i128::try_from(value.get()) {
+                    self.ints.insert(if negative { -value } else { value });
+                }
+            }
+            LitKind::Float(symbol, _) => {
+                if let Ok(value) = symbol.as_str().replace('_', "").parse::<f64>() {
+                    self.floats.insert((if negative { -value } else { value }).to_bits());
+                }
+            }
+            LitKind::Byte(value) => {
+                self.ints.insert(value as i128);
+            }
+            LitKind::Char(value) => {
+                self.chars.insert(value);
+            }
+            LitKind::Str(symbol, _) if symbol.as_str().len() <= MAX_LITERAL_LEN => {
+                self.strs.insert(symbol.to_string());
+            }
+            LitKind::ByteStr(ref value, _) if value.len() <= MAX_LITERAL_LEN => {
+                self.bytes.insert(value.to_vec());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for LiteralCollector<'tcx> {
+    type NestedFilter = nested_filter::OnlyBodies;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        // literals of macros such as the pieces of format strings say nothing about the input
+        if !expr.span.from_expansion() {
+            match expr.kind {
+                hir::ExprKind::Lit(lit) => self.insert(&lit.node, false),
+                hir::ExprKind::Unary(hir::UnOp::Neg, inner) => {
+                    if let hir::ExprKind::Lit(lit) = inner.kind {
+                        self.insert(&lit.node, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
+
+/// collect the literals of every body in the crate
+pub(crate) fn crate_literals(tcx: TyCtxt<'_>) -> CrateLiterals {
+    let mut collector = LiteralCollector {
+        tcx,
+        ints: BTreeSet::new(),
+        floats: BTreeSet::new(),
+        chars: BTreeSet::new(),
+        strs: BTreeSet::new(),
+        bytes: BTreeSet::new(),
+    };
+    tcx.hir().visit_all_item_likes_in_crate(&mut collector);
+    let mut ints = collector.ints.into_iter().collect::<Vec<_>>();
+    ints.sort_by_key(|value| (value.unsigned_abs(), *value));
+    let mut floats = collector.floats.into_iter().map(f64::from_bits).collect::<Vec<_>>();
+    floats.sort_by(|a, b| a.abs().total_cmp(&b.abs()).then(a.total_cmp(b)));
+    let mut strs = collector.strs.into_iter().collect::<Vec<_>>();
+    strs.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+    let mut bytes = collector.bytes.into_iter().collect::<Vec<_>>();
+    bytes.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+    let mut literals = CrateLiterals {
+        ints,
+        floats,
+        chars: collector.chars.into_iter().collect(),
+        strs,
+        bytes,
+    };
+    literals.ints.truncate(MAX_LITERALS);
+    literals.floats.truncate(MAX_LITERALS);
+    literals.chars.truncate(MAX_LITERALS);
+    literals.strs.truncate(MAX_LITERALS);
+    literals.bytes.truncate(MAX_LITERALS);
+    println!(
+        "[Seed] {} int, {} float, {} char, {} str and {} byte string literals",
+        literals.ints.len(),
+        literals.floats.len(),
+        literals.chars.len(),
+        literals.strs.len(),
+        literals.bytes.len()
+    );
+    literals
+}
+
+/// Inputs of the driver of `sequence` that get past its decoding: of a length the driver
+/// accepts, with valid UTF-8 in every `&str` and valid `char`s. Every leaf of the fuzzable
+/// params takes boundary values and literals of the crate in turn, the first seed is the one of
+/// zeroes and empty strings.
+pub(crate) fn sequence_seeds(api_graph: &ApiGraph<'_>, sequence: &ApiSequence) -> Vec<Vec<u8>> {
+    let literals = &api_graph.literals;
+    let mut res = Vec::new();
+    for k in 0..MAX_SEEDS {
+        let seed = if let Some(construction_len) = sequence.op_loop {
+            op_loop_seed(sequence, construction_len, k, literals)
+        } else if api_graph.input_layout == InputLayout::Sequential {
+            let mut seed = Vec::new();
+            for fuzzable_param in &sequence.fuzzable_params {
+                encode_sequential(fuzzable_param, k, literals, &mut seed);
+            }
+            seed
+        } else {
+            split_seed(sequence, k, literals)
+        };
+        if !res.contains(&seed) {
+            res.push(seed);
+        }
+    }
+    res
+}
+
+/// the first seeds of every sequence of the multiplexed driver, behind the bytes selecting it
+pub(crate) fn multiplexed_seeds(
+    api_graph: &ApiGraph<'_>,
+    sequences: &[&ApiSequence],
+) -> Vec<Vec<u8>> {
+    let mut res = Vec::new();
+    for (index, sequence) in sequences.iter().enumerate() {
+        let selector = if multiplex::selector_len(sequences.len()) == 1 {
+            vec![index as u8]
+        } else {
+            (index as u16).to_le_bytes().to_vec()
+        };
+        for seed in sequence_seeds(api_graph, sequence).into_iter().take(MULTIPLEXED_SEEDS) {
+            let mut input = selector.clone();
+            input.extend(seed);
+            res.push(input);
+        }
+    }
+    res
+}
+
+/// write the seeds of every driver into `dir/<driver>/seed<n>`
+pub(crate) fn write_seeds(dir: &PathBuf, seeds: &[(String, Vec<Vec<u8>>)]) {
+    println!("seed_path: {dir:?}");
+    for (driver, driver_seeds) in seeds {
+        let driver_dir = dir.join(driver);
+        fs::create_dir_all(&driver_dir).unwrap();
+        for (index, seed) in driver_seeds.iter().enumerate() {
+            fs::write(driver_dir.join(format!("seed{}", index)), seed).unwrap();
+        }
+    }
+}
+
+/// A `&str` or slice in the split layout
+struct Slot {
+    content: Vec<u8>,
+    padding: u8,    // strings are padded with spaces to stay readable, slices with zeroes
+    min_len: usize, // the least length the driver checks for
+    align: usize,   // `align_to` skips the bytes before the first aligned element
+}
+
+/// The fixed parts in the order the driver reads them, then one slot for every `&str` or slice.
+/// The slots are padded to a common length, the driver splits the rest of the input evenly.
+/// Slice elements are placed as if the input started at an aligned address, which the buffers
+/// of the fuzzers are.
+fn split_seed(sequence: &ApiSequence, k: usize, literals: &CrateLiterals) -> Vec<u8> {
+    let mut res = Vec::new();
+    let mut slots = Vec::new();
+    for fuzzable_param in &sequence.fuzzable_params {
+        encode_split(fuzzable_param, k, literals, &mut res, &mut slots);
+    }
+    if slots.is_empty() {
+        return res;
+    }
+    let mut slot_len = slots
+        .iter()
+        .map(|slot| (slot.content.len() + slot.align - 1).max(slot.min_len))
+        .max()
+        .unwrap();
+    while res.len() + slots.len() * slot_len < sequence._fuzzables_min_length() {
+        slot_len = slot_len + 1;
+    }
+    for slot in slots {
+        let start = res.len();
+        res.resize(start + (slot.align - start % slot.align) % slot.align, slot.padding);
+        res.extend(slot.content);
+        res.resize(start + slot_len, slot.padding);
+    }
+    res
+}
+
+/// append the fixed part of `fuzzable` to `fixed` and its dynamic parts to `slots`
+fn encode_split(
+    fuzzable: &FuzzableType,
+    k: usize,
+    literals: &CrateLiterals,
+    fixed: &mut Vec<u8>,
+    slots: &mut Vec<Slot>,
+) {
+    let start = fixed.len();
+    match fuzzable {
+        FuzzableType::NoFuzzable => {}
+        FuzzableType::Primitive(primitive) => {
+            fixed.extend(pick(&primitive_candidates(primitive, literals), k));
+        }
+        FuzzableType::RefStr => slots.push(Slot {
+            content: pick(&str_candidates(literals), k),
+            padding: b' ',
+            min_len: 1,
+            align: 1,
+        }),
+        FuzzableType::RefSlice(inner) => {
+            // elements of other types are cast from zeroes
+            let (content, align) = match **inner {
+                FuzzableType::Primitive(ref primitive) => {
+                    (pick(&slice_candidates(primitive, literals), k).1, inner._min_length())
+                }
+                _ => (Vec::new(), 1),
+            };
+            slots.push(Slot { content, padding: 0, min_len: fuzzable._min_length(), align });
+        }
+        FuzzableType::Tuple(inners) => {
+            for inner in inners {
+                encode_split(inner, k, literals, fixed, slots);
+            }
+        }
+        FuzzableType::Struct(_, _, fields) => {
+            for (_, inner) in fields {
+                encode_split(inner, k, literals, fixed, slots);
+            }
+        }
+    }
+    // the driver moves on by the fixed part length, which may count unread bytes
+    fixed.resize(start + fuzzable._fixed_part_length(), 0);
+}
+
+/// append `fuzzable` in the sequential layout of the runtime crate
+fn encode_sequential(
+    fuzzable: &FuzzableType,
+    k: usize,
+    literals: &CrateLiterals,
+    res: &mut Vec<u8>,
+) {
+    match fuzzable {
+        FuzzableType::NoFuzzable => {}
+        FuzzableType::Primitive(primitive) => {
+            res.extend(pick(&primitive_candidates(primitive, literals), k));
+        }
+        FuzzableType::RefStr => {
+            let content = pick(&str_candidates(literals), k);
+            res.push(content.len() as u8);
+            res.extend(content);
+        }
+        FuzzableType::RefSlice(inner) => match **inner {
+            FuzzableType::Primitive(ref primitive) => {
+                let (elements, content) = pick(&slice_candidates(primitive, literals), k);
+                res.push(elements as u8);
+                res.extend(content);
+            }
+            _ => {
+                let elements = k % 3;
+                res.push(elements as u8);
+                for element in 0..elements {
+                    encode_sequential(inner, k + element, literals, res);
+                }
+            }
+        },
+        FuzzableType::Tuple(inners) => {
+            for inner in inners {
+                encode_sequential(inner, k, literals, res);
+            }
+        }
+        // unit structs are built without reading the input
+        FuzzableType::Struct(_, 1, _) => {}
+        FuzzableType::Struct(_, _, fields) => {
+            for (_, inner) in fields {
+                encode_sequential(inner, k, literals, res);
+            }
+        }
+    }
+}
+
+/// the arguments of the construction, then every operation once, each behind its selector byte
+fn op_loop_seed(
+    sequence: &ApiSequence,
+    construction_len: usize,
+    k: usize,
+    literals: &CrateLiterals,
+) -> Vec<u8> {
+    let mut res = Vec::new();
+    let call_arguments = |i: usize, res: &mut Vec<u8>| {
+        for (param_type, index, _) in &sequence.functions[i].params {
+            if *param_type == ParamType::_FuzzableType {
+                encode_sequential(&sequence.fuzzable_params[*index], k, literals, res);
+            }
+        }
+    };
+    for i in 0..construction_len {
+        call_arguments(i, &mut res);
+    }
+    for i in construction_len..sequence.len() {
+        res.push((i - construction_len) as u8);
+        call_arguments(i, &mut res);
+    }
+    res
+}
+
+fn push_new(candidates: &mut Vec<Vec<u8>>, candidate: Vec<u8>) {
+    if !candidates.contains(&candidate) {
+        candidates.push(candidate);
+    }
+}
+
+fn pick<T: Clone>(candidates: &[T], k: usize) -> T {
+    candidates[k % candidates.len()].clone()
+}
+
+/// byte length and signedness of the integer types
+fn int_layout(primitive: &PrimitiveType) -> Option<(usize, bool)> {
+    match primitive {
+        PrimitiveType::U8 => Some((1, false)),
+        PrimitiveType::I8 => Some((1, true)),
+        PrimitiveType::U16 => Some((2, false)),
+        PrimitiveType::I16 => Some((2, true)),
+        PrimitiveType::U32 => Some((4, false)),
+        PrimitiveType::I32 => Some((4, true)),
+        PrimitiveType::U64 | PrimitiveType::Usize => Some((8, false)),
+        PrimitiveType::I64 | PrimitiveType::Isize => Some((8, true)),
+        PrimitiveType::U128 => Some((16, false)),
+        PrimitiveType::I128 => Some((16, true)),
+        _ => None,
+    }
+}
+
+/// Encodings of the values tried for a primitive, as the driver decodes them: integers and
+/// `char`s big-endian, floats little-endian, an even byte for `true`
+fn primitive_candidates(primitive: &PrimitiveType, literals: &CrateLiterals) -> Vec<Vec<u8>> {
+    let mut res = Vec::new();
+    if let Some((size, signed)) = int_layout(primitive) {
+        let (min, max) = match (size, signed) {
+            (16, true) => (i128::MIN, i128::MAX),
+            (16, false) => (0, i128::MAX),
+            (_, true) => (-(1 << (8 * size - 1)), (1 << (8 * size - 1)) - 1),
+            (_, false) => (0, (1 << (8 * size)) - 1),
+        };
+        let boundaries = [0, 1, max, min, -1, max - 1, min + 1];
+        for value in boundaries.iter().chain(literals.ints.iter()) {
+            if *value >= min && *value <= max {
+                push_new(&mut res, (*value as u128).to_be_bytes()[16 - size..].to_vec());
+            }
+        }
+        return res;
+    }
+    match primitive {
+        PrimitiveType::Bool => {
+            push_new(&mut res, vec![0]);
+            push_new(&mut res, vec![1]);
+        }
+        PrimitiveType::Char => {
+            for value in ['a', '\0', char::MAX].iter().chain(literals.chars.iter()) {
+                push_new(&mut res, (*value as u32).to_be_bytes().to_vec());
+            }
+        }
+        PrimitiveType::F32 | PrimitiveType::F64 => {
+            let boundaries = [0.0, 1.0, -1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+            for value in boundaries.iter().chain(literals.floats.iter()) {
+                if *primitive == PrimitiveType::F32 {
+                    push_new(&mut res, (*value as f32).to_le_bytes().to_vec());
+                } else {
+                    push_new(&mut res, value.to_le_bytes().to_vec());
+                }
+            }
+        }
+        _ => push_new(&mut res, Vec::new()),
+    }
+    res
+}
+
+/// the empty string, short ASCII and non-ASCII ones, then the string literals of the crate
+fn str_candidates(literals: &CrateLiterals) -> Vec<Vec<u8>> {
+    let mut res = vec![Vec::new(), b"a".to_vec(), "\u{e9}\u{4e2d}".as_bytes().to_vec()];
+    for value in &literals.strs {
+        push_new(&mut res, value.as_bytes().to_vec());
+    }
+    res
+}
+
+/// Element numbers and contents of the slices tried. Slices are borrowed from the input, so
+/// their elements are in native byte order, and `bool`s must be 0 or 1. Byte slices take the
+/// byte and string literals of the crate, other slices a growing prefix of the values tried
+/// for their elements.
+fn slice_candidates(
+    primitive: &PrimitiveType,
+    literals: &CrateLiterals,
+) -> Vec<(usize, Vec<u8>)> {
+    let mut res = vec![(0, Vec::new())];
+    if matches!(primitive, PrimitiveType::U8 | PrimitiveType::I8) {
+        res.push((3, vec![0, 1, 0xff]));
+        let contents = literals.bytes.iter().cloned();
+        for content in contents.chain(literals.strs.iter().map(|value| value.as_bytes().to_vec())) {
+            if !res.iter().any(|(_, existing)| *existing == content) {
+                res.push((content.len(), content));
+            }
+        }
+        return res;
+    }
+    let elements = primitive_candidates(primitive, literals)
+        .into_iter()
+        .map(|element| native_element(primitive, element))
+        .collect::<Vec<_>>();
+    for len in 1..=elements.len() {
+        res.push((len, elements[..len].concat()));
+    }
+    res
+}
+
+/// the driver encoding of a primitive turned into the bytes of its value in memory
+fn native_element(primitive: &PrimitiveType, mut element: Vec<u8>) -> Vec<u8> {
+    match primitive {
+        PrimitiveType::Bool => vec![(element[0] % 2 == 0) as u8],
+        PrimitiveType::F32 | PrimitiveType::F64 => {
+            if cfg!(target_endian = "big") {
+                element.reverse();
+            }
+            element
+        }
+        _ => {
+            if cfg!(target_endian = "little") {
+                element.reverse();
+            }
+            element
+        }
+    }
+}
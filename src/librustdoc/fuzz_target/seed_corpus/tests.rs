@@ -0,0 +1,90 @@
+use super::{native_element, primitive_candidates, slice_candidates, CrateLiterals};
+use crate::clean::PrimitiveType;
+
+/// `_to_i16` and the other signed decoders of the driver: the unsigned big-endian value
+/// reinterpreted
+fn decode_signed(bytes: &[u8]) -> i128 {
+    match bytes.len() {
+        1 => bytes[0] as i8 as i128,
+        2 => u16::from_be_bytes(bytes.try_into().unwrap()) as i16 as i128,
+        4 => u32::from_be_bytes(bytes.try_into().unwrap()) as i32 as i128,
+        8 => u64::from_be_bytes(bytes.try_into().unwrap()) as i64 as i128,
+        16 => u128::from_be_bytes(bytes.try_into().unwrap()) as i128,
+        len => panic!("no integer of {len} bytes"),
+    }
+}
+
+#[test]
+fn test_signed_candidates_round_trip() {
+    let literals = CrateLiterals { ints: vec![-300, 70000], ..Default::default() };
+    let cases = [
+        (PrimitiveType::I8, i8::MIN as i128, i8::MAX as i128),
+        (PrimitiveType::I16, i16::MIN as i128, i16::MAX as i128),
+        (PrimitiveType::I32, i32::MIN as i128, i32::MAX as i128),
+        (PrimitiveType::I64, i64::MIN as i128, i64::MAX as i128),
+        (PrimitiveType::I128, i128::MIN, i128::MAX),
+    ];
+    for (primitive, min, max) in cases {
+        let decoded = primitive_candidates(&primitive, &literals)
+            .iter()
+            .map(|candidate| decode_signed(candidate))
+            .collect::<Vec<_>>();
+        let mut expected = vec![0, 1, max, min, -1, max - 1, min + 1];
+        expected.extend([-300, 70000].into_iter().filter(|value| (min..=max).contains(value)));
+        assert_eq!(decoded, expected, "{primitive:?}");
+    }
+}
+
+#[test]
+fn test_unsigned_candidates_round_trip() {
+    let literals = CrateLiterals { ints: vec![-1, 255, 256], ..Default::default() };
+    let decoded = primitive_candidates(&PrimitiveType::U16, &literals)
+        .iter()
+        .map(|candidate| u16::from_be_bytes(candidate[..].try_into().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, vec![0, 1, u16::MAX, u16::MAX - 1, 255, 256]);
+}
+
+#[test]
+fn test_byte_slice_candidates() {
+    let literals = CrateLiterals {
+        strs: vec!["ab".to_string()],
+        bytes: vec![b"\x7fELF".to_vec(), vec![0, 1, 0xff]],
+        ..Default::default()
+    };
+    assert_eq!(
+        slice_candidates(&PrimitiveType::U8, &literals),
+        vec![(0, vec![]), (3, vec![0, 1, 0xff]), (4, b"\x7fELF".to_vec()), (2, b"ab".to_vec())]
+    );
+}
+
+#[test]
+fn test_slice_candidates_native() {
+    let candidates = slice_candidates(&PrimitiveType::U16, &CrateLiterals::default());
+    let values = [0, 1, u16::MAX, u16::MAX - 1];
+    assert_eq!(candidates.len(), values.len() + 1);
+    for (len, content) in candidates {
+        // borrowed from the input, the elements are in the byte order of the target
+        let elements = content
+            .chunks(2)
+            .map(|element| u16::from_ne_bytes(element.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(elements, values[..len]);
+    }
+}
+
+#[test]
+fn test_native_element() {
+    assert_eq!(native_element(&PrimitiveType::Bool, vec![0]), vec![1]);
+    assert_eq!(native_element(&PrimitiveType::Bool, vec![3]), vec![0]);
+    assert_eq!(
+        native_element(&PrimitiveType::I32, (-2i32).to_be_bytes().to_vec()),
+        (-2i32).to_ne_bytes().to_vec()
+    );
+    assert_eq!(
+        native_element(&PrimitiveType::F64, 1.5f64.to_le_bytes().to_vec()),
+        1.5f64.to_ne_bytes().to_vec()
+    );
+    let element = native_element(&PrimitiveType::Char, vec![0, 0, 0, b'a']);
+    assert_eq!(element, ('a' as u32).to_ne_bytes());
+}
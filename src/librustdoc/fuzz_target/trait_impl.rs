@@ -6,11 +6,11 @@
 // use crate::clean::TypeBindingKind;
 use crate::clean::AssocItemConstraintKind;
 // use crate::clean::Visibility;
-use crate::clean::{GenericArg, GenericArgs};
+use crate::clean::{GenericArg, GenericArgs, PathSegment};
 use crate::formats::cache::Cache;
 use crate::fuzz_target::api_function::ApiFunction;
 use crate::fuzz_target::api_sequence::{ApiCall, ApiSequence, ParamType};
-use crate::fuzz_target::api_util::{is_generic_type,_type_name};
+use crate::fuzz_target::api_util::{is_generic_type, try_type_name_from_did, _type_name};
 use crate::fuzz_target::call_type::CallType;
 use crate::fuzz_target::fuzz_target_renderer::FuzzTargetContext;
 use crate::fuzz_target::fuzzable_type;
@@ -32,20 +32,23 @@
 use rand::{self, Rng};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::ty::Visibility;
+use rustc_hir::def::{DefKind, Res};
 use rustc_hir::def_id::DefId;
 use rustc_hir::{self, Mutability};
+use rustc_span::Symbol;
+use thin_vec::{thin_vec, ThinVec};
 use std::cmp::{max, min};
 use std::{cell::RefCell, rc::Rc};
 use std::slice::Iter;
 use super::api_util::print_path_segment;
 use super::api_util::scan_type_with;
 
+#[cfg(test)]
+mod tests;
+
 fn is_impl_in_std(type_: &Type, trait_: &Type, cache: &Cache) -> bool {
     match _type_name(trait_, Some(cache)).as_str() {
-        "std::marker::Sized" => match _type_name(type_, Some(cache)).as_str() {
-            "str" => return false,
-            _ => return true,
-        },
+        "std::marker::Sized" | "core::marker::Sized" => is_sized(type_),
         "std::alloc::Allocator" => {
             match _type_name(type_, Some(cache)).as_str() {
                 "std::alloc::Global" => return true,
@@ -116,11 +119,49 @@ fn is_impl_in_std(type_: &Type, trait_: &Type, cache: &Cache) -> bool {
             }
             false
         }
-        "core::marker::Sized" => true,
         _ => false,
     }
 }
 
+/// Traits that rustc decides structurally rather than by looking for an impl
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkerTrait {
+    Send,
+    Sync,
+    Unpin,
+    Copy,
+}
+
+impl MarkerTrait {
+    fn from_trait(trait_: &Type, cache: &Cache) -> Option<MarkerTrait> {
+        match _type_name(trait_, Some(cache)).as_str() {
+            "std::marker::Send" | "core::marker::Send" => Some(MarkerTrait::Send),
+            "std::marker::Sync" | "core::marker::Sync" => Some(MarkerTrait::Sync),
+            "std::marker::Unpin" | "core::marker::Unpin" => Some(MarkerTrait::Unpin),
+            "std::marker::Copy" | "core::marker::Copy" => Some(MarkerTrait::Copy),
+            _ => None,
+        }
+    }
+
+    fn is_auto(&self) -> bool {
+        !matches!(self, MarkerTrait::Copy)
+    }
+}
+
+pub(crate) fn path_type_args(path: &Path) -> impl Iterator<Item = &Type> {
+    path.segments.last().into_iter().flat_map(|segment| match segment.args {
+        GenericArgs::AngleBracketed { ref args, .. } => args
+            .iter()
+            .filter_map(|arg| if let GenericArg::Type(ty) = arg { Some(ty) } else { None })
+            .collect::<Vec<_>>(),
+        GenericArgs::Parenthesized { .. } => Vec::new(),
+    })
+}
+
+fn is_sized(type_: &Type) -> bool {
+    !matches!(type_, Type::Primitive(PrimitiveType::Str) | Type::Slice(_) | Type::DynTrait(..))
+}
+
 #[derive(Debug)]
 pub(crate) struct TraitImpl {
     pub(crate) trait_: Path,
@@ -129,6 +170,7 @@ pub(crate) struct TraitImpl {
     pub(crate) generic_map: GenericParamMap,
     pub(crate) impl_id: DefId,
     pub(crate) assoc_items: Vec<(QPathData, Type)>,
+    pub(crate) negative: bool, // impl !Trait for Type
 }
 
 impl TraitImpl {
@@ -138,8 +180,17 @@ pub(crate) fn new(
         blanket_type: Option<Type>,
         generic_map: GenericParamMap,
         impl_id: DefId,
+        negative: bool,
     ) -> TraitImpl {
-        TraitImpl { trait_, for_, impl_id, blanket_type, generic_map, assoc_items: Vec::new() }
+        TraitImpl {
+            trait_,
+            for_,
+            impl_id,
+            blanket_type,
+            generic_map,
+            assoc_items: Vec::new(),
+            negative,
+        }
     }
 
     fn check_assoc_item_type(&self, name: &str, type_: &Type) -> bool {
@@ -183,32 +234,81 @@ pub fn check_assoc_items(&self, trait_: &Path) -> bool {
     }
 }
 
-static EMPTY_IMPLS: Vec<TraitImpl> = Vec::new();
+/// Impls are stored under the def id of their `for_` type. `&Foo` resolves to the def id of
+/// `Foo`, while `impl<T> Trait for &T` is stored under the `reference` primitive, so a
+/// reference type has to look into both buckets.
+fn impl_lookup_dids(type_: &Type, cache: &Cache) -> Vec<DefId> {
+    let mut res = vec![type_.def_id(cache).expect(&format!("did fail: {type_:?}"))];
+    if let Type::BorrowedRef { .. } = type_ {
+        if let Some(did) = Type::Primitive(PrimitiveType::Reference).def_id(cache) {
+            if !res.contains(&did) {
+                res.push(did);
+            }
+        }
+    }
+    res
+}
 
-pub(crate) struct TypeTraitCache(pub FxHashMap<(Type, Type), Option<ImplId>>); // (Type, Bound) => ImplId)
+pub(crate) struct TypeTraitCache {
+    results: FxHashMap<(Type, Type), Option<ImplId>>, // (Type, Bound) => ImplId)
+    /// the checks in progress, outermost first
+    in_progress: Vec<(Type, Type)>,
+    /// the outermost check in progress whose assumed result was read, results depending on it
+    /// are provisional until it resolves
+    cycle_root: Option<usize>,
+}
 
 impl TypeTraitCache {
     pub(crate) fn new() -> TypeTraitCache {
-        TypeTraitCache(FxHashMap::default())
+        TypeTraitCache { results: FxHashMap::default(), in_progress: Vec::new(), cycle_root: None }
     }
 
     pub(crate) fn get(&self, type_: &Type, trait_: &Type) -> Option<&Option<ImplId>> {
-        self.0.get(&(type_.clone(), trait_.clone()))
+        self.results.get(&(type_.clone(), trait_.clone()))
+    }
+
+    /// whether the check of type_ against trait_ is in progress, which records the cycle
+    fn hit_cycle(&mut self, type_: &Type, trait_: &Type) -> bool {
+        let Some(depth) = self.in_progress.iter().position(|(ty, tr)| ty == type_ && tr == trait_)
+        else {
+            return false;
+        };
+        self.cycle_root = Some(self.cycle_root.map_or(depth, |root| root.min(depth)));
+        true
     }
 
-    pub(crate) fn set(&mut self, type_: Type, trait_: Type, value: Option<ImplId>) {
-        self.0.insert((type_, trait_), value);
+    fn enter(&mut self, type_: &Type, trait_: &Type) {
+        self.in_progress.push((type_.clone(), trait_.clone()));
+    }
+
+    /// finish the innermost check, its result is only cached if no cycle left it provisional
+    fn exit(&mut self, value: Option<ImplId>) {
+        let (type_, trait_) = self.in_progress.pop().unwrap();
+        let depth = self.in_progress.len();
+        match self.cycle_root {
+            Some(root) if root < depth => return,
+            Some(_) => self.cycle_root = None,
+            None => {}
+        }
+        self.results.insert((type_, trait_), value);
     }
 }
 
 pub(crate) struct TraitImplMap {
     pub(crate) inner: FxHashMap<DefId, Vec<TraitImpl>>,
     pub(crate) concrete: Vec<Type>, //all concrete type from impl
+    pub(crate) sync_trait: Option<Type>,
+    pub(crate) send_trait: Option<Type>,
 }
 
 impl TraitImplMap {
     pub(crate) fn new() -> TraitImplMap {
-        TraitImplMap { inner: FxHashMap::default(), concrete: Vec::new() }
+        TraitImplMap {
+            inner: FxHashMap::default(),
+            concrete: Vec::new(),
+            sync_trait: None,
+            send_trait: None,
+        }
     }
 
     pub(crate) fn init_concrete(&mut self) {
@@ -229,120 +329,298 @@ pub(crate) fn add_type_trait_impl(&mut self, ty_did: DefId, trait_impl: TraitImp
         self.inner.entry(ty_did).or_default().push(trait_impl);
     }
 
-    fn get_type_impls(&self, type_: &Type, cache: &Cache) -> &Vec<TraitImpl> {
-        let did = type_.def_id(cache).expect(&format!("did fail: {type_:?}"));
-        // TODO: identify variant, e.g. &mut T, T may have different trait impl. So do Vec<T>, Vec<i32>.
-        if let Some(res) = self.inner.get(&did) { res } else { &EMPTY_IMPLS }
+    fn get_type_impls(&self, type_: &Type, cache: &Cache) -> Vec<&TraitImpl> {
+        // Variants such as `&mut T`, `Vec<T>` and `Vec<i32>` share a bucket, match_type tells them apart.
+        let mut res = Vec::new();
+        for did in impl_lookup_dids(type_, cache) {
+            if let Some(impls) = self.inner.get(&did) {
+                res.extend(impls.iter());
+            }
+        }
+        res
     }
 
-    /// return the exact impl_id set for type in given trait bounds
-    /// if return None, it means this type do not satisfy bounds
-    pub(crate) fn extract_type_impls_with_bounds(
+    /// build the paths of `Sync` and `Send`, which are needed to decide whether `&T` is `Send`
+    /// and `Mutex<T>` is `Sync`
+    pub(crate) fn init_marker_traits(&mut self, cache: &Cache) {
+        for (did, (syms, _)) in cache.external_paths.iter() {
+            let name = match join_with_double_colon(syms).as_str() {
+                "core::marker::Sync" => "Sync",
+                "core::marker::Send" => "Send",
+                _ => continue,
+            };
+            let trait_ = Some(Type::Path {
+                path: Path {
+                    res: Res::Def(DefKind::Trait, *did),
+                    segments: thin_vec![PathSegment {
+                        name: Symbol::intern(name),
+                        args: GenericArgs::AngleBracketed {
+                            args: Box::new([]),
+                            constraints: ThinVec::new(),
+                        },
+                    }],
+                },
+            });
+            if name == "Sync" {
+                self.sync_trait = trait_;
+            } else {
+                self.send_trait = trait_;
+            }
+        }
+    }
+
+    /// whether any impl (positive or negative) of trait_ is recorded for the definition of type_
+    fn has_impl_of(&self, type_: &Type, trait_: &Type) -> bool {
+        let (Type::Path { path: type_path }, Type::Path { path: trait_path }) = (type_, trait_)
+        else {
+            return false;
+        };
+        self.inner.get(&type_path.def_id()).map_or(false, |impls| {
+            impls.iter().any(|trait_impl| trait_impl.trait_.def_id() == trait_path.def_id())
+        })
+    }
+
+    /// check whether type_ have implement of this trait_
+    fn extract_trait_id(
         &self,
         type_: &Type,
-        bounds: &Vec<Path>,
+        trait_: &Type,
         type_trait_cache: &mut TypeTraitCache,
         cache: &Cache,
-    ) -> Option<FxHashSet<ImplId>> {
-        let mut res = FxHashSet::default();
-        let trait_impls = self.get_type_impls(type_, cache); //trait, generic, impl_id
+    ) -> Option<ImplId> {
+        if let Some(id) = type_trait_cache.get(type_, trait_) {
+            // is type_ implement trait_?
+            return *id;
+        }
+
+        let marker = MarkerTrait::from_trait(trait_, cache);
+        // if recursively check happened this can stop dead loop.
+        // Auto traits are coinductive: like rustc, a cycle proves them, e.g. `struct List(Option<Box<List>>)` is Send.
+        if type_trait_cache.hit_cycle(type_, trait_) {
+            return match marker {
+                Some(marker) if marker.is_auto() => Some(ImplId::Unknown),
+                _ => None,
+            };
+        }
+        type_trait_cache.enter(type_, trait_);
 
-        // check whether type_ have implement of this trait_
-        let mut extract_trait_id = |trait_: &Type| -> Option<ImplId> {
-            if let Some(id) = type_trait_cache.get(type_, trait_) { // is type_ implement trait_?
-                return *id;
+        let res = match marker {
+            // impls synthesized by rustdoc (auto impls, negative impls, derived Copy) are the most precise
+            Some(marker) if !self.has_impl_of(type_, trait_) => {
+                if self.check_marker_trait(type_, trait_, marker, type_trait_cache, cache) {
+                    Some(ImplId::Unknown)
+                } else {
+                    None
+                }
             }
+            _ => self.search_type_impls(type_, trait_, type_trait_cache, cache),
+        };
+        type_trait_cache.exit(res);
+        res
+    }
 
-            // if recursively check happened this can stop dead loop by returning None
-            type_trait_cache.set(type_.clone(), trait_.clone(), None); 
-
-            // check all impls for type_
-            for trait_impl in trait_impls {
-                // println!("Check trait impl {:?}", trait_impl);
-                let impl_trait = Type::Path { path: trait_impl.trait_.clone() };
-                
-                if let Some(sol_for_trait) =
-                    match_type(&trait_, &impl_trait, &trait_impl.generic_map.generic_defs)
-                {
-                    // println!("Check Trait Succ");
-
-                    // if impl is blanket, we do not check type
-                    if let Some(ref blanket_generic) = trait_impl.blanket_type {
-                        let mut solution = sol_for_trait;
-                        match blanket_generic {
-                            Type::Generic(sym) => {
-                                let ind = get_param_index(
-                                    sym.as_str(),
-                                    &trait_impl.generic_map.generic_defs,
-                                );
-                                solution[ind] = type_.clone();
-                            }
-                            _ => unimplemented!(),
-                        };
-                        if solution.is_empty()
-                            || trait_impl
-                                .generic_map
-                                .check_solution(&solution, self, type_trait_cache, cache)
-                                .is_some()
-                        {
-                            type_trait_cache.set(
-                                type_.clone(),
-                                trait_.clone(),
-                                Some(ImplId::Id(trait_impl.impl_id)),
-                            );
-                            return Some(ImplId::Id(trait_impl.impl_id));
-                        }
+    /// find an impl of trait_ whose for type (or blanket type) matches type_
+    fn search_type_impls(
+        &self,
+        type_: &Type,
+        trait_: &Type,
+        type_trait_cache: &mut TypeTraitCache,
+        cache: &Cache,
+    ) -> Option<ImplId> {
+        if type_.def_id(cache).is_none() {
+            return None;
+        }
+        // check all impls for type_
+        for trait_impl in self.get_type_impls(type_, cache) {
+            // println!("Check trait impl {:?}", trait_impl);
+            let impl_trait = Type::Path { path: trait_impl.trait_.clone() };
 
-                        return None;
-                    }
+            let Some(sol_for_trait) =
+                match_type(&trait_, &impl_trait, &trait_impl.generic_map.generic_defs)
+            else {
+                continue;
+            };
+            // impls that record their assoc items must agree with `Trait<Item = ..>`
+            if let Type::Path { path: ref trait_path } = trait_ {
+                if !trait_impl.assoc_items.is_empty() && !trait_impl.check_assoc_items(trait_path) {
+                    continue;
+                }
+            }
+            // println!("Check Trait Succ");
 
-                    // if impl is not blanket, we need merge solution
-                    if let Some(sol_for_type) =
-                        match_type(type_, &trait_impl.for_, &trait_impl.generic_map.generic_defs)
-                    {
-                        /* println!(
-                            "[TraitImpl] {} match {}, {} match {}",
-                            _type_name(&trait_, None),
-                            _type_name(&impl_trait, None),
-                            _type_name(type_, None),
-                            _type_name(&trait_impl.for_, None)
-                        ); */
-
-                        if let Some(mut solution) = merge_solution(
-                            &sol_for_type,
-                            &sol_for_trait,
-                            &trait_impl.generic_map.generic_defs,
-                        ) {
-                            println!(
-                                "[TraitImpl] Recursively check: do we have impl {} for {}?",
-                                _type_name(&impl_trait, Some(cache)),
-                                _type_name(&trait_impl.for_, Some(cache))
-                            );
-                            println!(
-                                "[TraitImpl] solution: {}, generic_defs: {:?}",
-                                solution_string(&solution),
-                                trait_impl.generic_map.generic_defs
-                            );
-
-                            if solution.is_empty()
-                                || trait_impl
-                                    .generic_map
-                                    .check_solution(&solution, self, type_trait_cache, cache)
-                                    .is_some()
-                            {
-                                type_trait_cache.set(
-                                    type_.clone(),
-                                    trait_.clone(),
-                                    Some(ImplId::Id(trait_impl.impl_id)),
-                                );
-                                return Some(ImplId::Id(trait_impl.impl_id));
-                            }
+            let sol_for_type = if let Some(ref blanket_type) = trait_impl.blanket_type {
+                // if impl is blanket, match type against the blanket type (`T`, `&T`, `Box<T>`, ...)
+                match_type(type_, blanket_type, &trait_impl.generic_map.generic_defs)
+            } else {
+                // if impl is not blanket, match type against the for type
+                match_type(type_, &trait_impl.for_, &trait_impl.generic_map.generic_defs)
+            };
+            let Some(sol_for_type) = sol_for_type else {
+                continue;
+            };
+
+            /* println!(
+                "[TraitImpl] {} match {}, {} match {}",
+                _type_name(&trait_, None),
+                _type_name(&impl_trait, None),
+                _type_name(type_, None),
+                _type_name(&trait_impl.for_, None)
+            ); */
+
+            let Some(solution) = merge_solution(
+                &sol_for_type,
+                &sol_for_trait,
+                &trait_impl.generic_map.generic_defs,
+            ) else {
+                continue;
+            };
+            println!(
+                "[TraitImpl] Recursively check: do we have impl {} for {}?",
+                _type_name(&impl_trait, Some(cache)),
+                _type_name(&trait_impl.for_, Some(cache))
+            );
+            println!(
+                "[TraitImpl] solution: {}, generic_defs: {:?}",
+                solution_string(&solution),
+                trait_impl.generic_map.generic_defs
+            );
+
+            if solution.is_empty()
+                || trait_impl
+                    .generic_map
+                    .check_solution(&solution, self, type_trait_cache, cache)
+                    .is_some()
+            {
+                if trait_impl.negative {
+                    println!(
+                        "[TraitImpl] {} has negative impl of {}",
+                        _type_name(type_, Some(cache)),
+                        _type_name(trait_, Some(cache))
+                    );
+                    return None;
+                }
+                return Some(ImplId::Id(trait_impl.impl_id));
+            }
+        }
+        None
+    }
+
+    fn all_hold<'a>(
+        &self,
+        types: impl IntoIterator<Item = &'a Type>,
+        trait_: &Type,
+        type_trait_cache: &mut TypeTraitCache,
+        cache: &Cache,
+    ) -> bool {
+        types
+            .into_iter()
+            .all(|ty| self.extract_trait_id(ty, trait_, type_trait_cache, cache).is_some())
+    }
+
+    /// Structural reasoning for marker traits of types that have no recorded impl.
+    /// An auto trait holds if all components hold it. As we do not know the fields of
+    /// external types, their type arguments are taken as fields.
+    fn check_marker_trait(
+        &self,
+        type_: &Type,
+        trait_: &Type,
+        marker: MarkerTrait,
+        type_trait_cache: &mut TypeTraitCache,
+        cache: &Cache,
+    ) -> bool {
+        match type_ {
+            Type::Primitive(primitive) => {
+                marker != MarkerTrait::Copy || !matches!(primitive, PrimitiveType::Str)
+            }
+            Type::RawPointer(..) => matches!(marker, MarkerTrait::Unpin | MarkerTrait::Copy),
+            Type::BareFunction(..) => true,
+            Type::BorrowedRef { mutability, type_: inner, .. } => match (marker, mutability) {
+                (MarkerTrait::Unpin, _) => true,
+                (MarkerTrait::Copy, Mutability::Not) => true,
+                (MarkerTrait::Copy, Mutability::Mut) => false,
+                // impl<T: Sync + ?Sized> Send for &T
+                (MarkerTrait::Send, Mutability::Not) => {
+                    let sync_trait = self.sync_trait.as_ref().unwrap_or(trait_);
+                    self.all_hold([&**inner], sync_trait, type_trait_cache, cache)
+                }
+                _ => self.all_hold([&**inner], trait_, type_trait_cache, cache),
+            },
+            Type::Slice(inner) => {
+                marker != MarkerTrait::Copy
+                    && self.all_hold([&**inner], trait_, type_trait_cache, cache)
+            }
+            Type::Array(inner, _) => {
+                self.all_hold([&**inner], trait_, type_trait_cache, cache)
+            }
+            Type::Tuple(types) => self.all_hold(types, trait_, type_trait_cache, cache),
+            Type::DynTrait(bounds, _) => {
+                let Type::Path { path: trait_path } = trait_ else {
+                    return false;
+                };
+                bounds.iter().any(|bound| bound.trait_.def_id() == trait_path.def_id())
+            }
+            Type::Path { path } => {
+                let name = try_type_name_from_did(path.def_id(), cache).unwrap_or_default();
+                if !marker.is_auto() {
+                    // Copy is never structural for a nominal type, except for std types with an impl
+                    return match name.as_str() {
+                        // impl<T: ?Sized> Copy for PhantomData<T>
+                        "std::marker::PhantomData" => true,
+                        // impl<T: Copy> Copy for Option<T>
+                        "std::option::Option" => {
+                            self.all_hold(path_type_args(path), trait_, type_trait_cache, cache)
                         }
+                        _ => false,
+                    };
+                }
+                match (name.as_str(), marker) {
+                    ("std::rc::Rc" | "std::rc::Weak", MarkerTrait::Send | MarkerTrait::Sync) => false,
+                    (
+                        "std::cell::Cell" | "std::cell::RefCell" | "std::cell::UnsafeCell"
+                        | "std::cell::OnceCell",
+                        MarkerTrait::Sync,
+                    ) => false,
+                    ("std::sync::MutexGuard" | "std::sync::RwLockReadGuard"
+                    | "std::sync::RwLockWriteGuard", MarkerTrait::Send) => false,
+                    // impl<T: ?Sized + Send + Sync> Send/Sync for Arc<T>
+                    ("std::sync::Arc" | "std::sync::Weak", MarkerTrait::Send | MarkerTrait::Sync) => {
+                        let sync_trait = self.sync_trait.as_ref().unwrap_or(trait_);
+                        self.all_hold(path_type_args(path), trait_, type_trait_cache, cache)
+                            && self.all_hold(path_type_args(path), sync_trait, type_trait_cache, cache)
+                    }
+                    // impl<T: ?Sized + Send> Sync for Mutex<T>
+                    ("std::sync::Mutex", MarkerTrait::Sync) => {
+                        let send_trait = self.send_trait.as_ref().unwrap_or(trait_);
+                        self.all_hold(path_type_args(path), send_trait, type_trait_cache, cache)
+                    }
+                    // impl<T: ?Sized + Send + Sync> Sync for RwLock<T>
+                    ("std::sync::RwLock", MarkerTrait::Sync) => {
+                        let send_trait = self.send_trait.as_ref().unwrap_or(trait_);
+                        self.all_hold(path_type_args(path), trait_, type_trait_cache, cache)
+                            && self.all_hold(path_type_args(path), send_trait, type_trait_cache, cache)
                     }
+                    ("std::marker::PhantomPinned", MarkerTrait::Unpin) => false,
+                    // Box, Vec, Pin... are Unpin regardless of their content
+                    ("std::boxed::Box" | "std::vec::Vec" | "std::pin::Pin" | "std::rc::Rc"
+                    | "std::sync::Arc", MarkerTrait::Unpin) => true,
+                    _ => self.all_hold(path_type_args(path), trait_, type_trait_cache, cache),
                 }
             }
-            None
-        };
+            // unresolved generic, associated type, impl Trait...
+            _ => false,
+        }
+    }
+
+    /// return the exact impl_id set for type in given trait bounds
+    /// if return None, it means this type do not satisfy bounds
+    pub(crate) fn extract_type_impls_with_bounds(
+        &self,
+        type_: &Type,
+        bounds: &Vec<Path>,
+        type_trait_cache: &mut TypeTraitCache,
+        cache: &Cache,
+    ) -> Option<FxHashSet<ImplId>> {
+        let mut res = FxHashSet::default();
 
         for trait_ in bounds.iter() {
             let trait_ = Type::Path { path: trait_.clone() };
@@ -353,7 +631,7 @@ pub(crate) fn extract_type_impls_with_bounds(
                 continue;
             }
 
-            if let Some(impl_id) = extract_trait_id(&trait_) {
+            if let Some(impl_id) = self.extract_trait_id(type_, &trait_, type_trait_cache, cache) {
                 res.insert(impl_id);
                 continue;
             }
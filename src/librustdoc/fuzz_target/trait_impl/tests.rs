@@ -0,0 +1,60 @@
+use super::{TraitImplMap, TypeTraitCache};
+use crate::clean::{GenericArg, GenericArgs, Path, PathSegment, PrimitiveType, Type};
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex};
+use rustc_span::create_default_session_globals_then;
+use rustc_span::Symbol;
+use thin_vec::{thin_vec, ThinVec};
+
+fn external_path(
+    cache: &mut Cache,
+    index: u32,
+    kind: DefKind,
+    name: &str,
+    args: Vec<Type>,
+) -> Type {
+    let did = DefId { krate: CrateNum::from_u32(1), index: DefIndex::from_u32(index) };
+    let syms = name.split("::").map(Symbol::intern).collect::<Vec<_>>();
+    let item_type = if kind == DefKind::Trait { ItemType::Trait } else { ItemType::Struct };
+    cache.external_paths.insert(did, (syms, item_type));
+    let args = args.into_iter().map(GenericArg::Type).collect::<Vec<_>>().into_boxed_slice();
+    Type::Path {
+        path: Path {
+            res: Res::Def(kind, did),
+            segments: thin_vec![PathSegment {
+                name: Symbol::intern(name.rsplit("::").next().unwrap()),
+                args: GenericArgs::AngleBracketed { args, constraints: ThinVec::new() },
+            }],
+        },
+    }
+}
+
+#[test]
+fn test_copy_of_phantom_data_and_option() {
+    create_default_session_globals_then(|| {
+        let mut cache = Cache::new(false, false);
+        let copy = external_path(&mut cache, 0, DefKind::Trait, "core::marker::Copy", vec![]);
+        let string = external_path(&mut cache, 1, DefKind::Struct, "alloc::string::String", vec![]);
+        let u8_ = Type::Primitive(PrimitiveType::U8);
+        let mut wrap = |index, name, arg: &Type| {
+            external_path(&mut cache, index, DefKind::Struct, name, vec![arg.clone()])
+        };
+        let phantom_string = wrap(2, "core::marker::PhantomData", &string);
+        let option_string = wrap(3, "core::option::Option", &string);
+        let option_u8 = wrap(3, "core::option::Option", &u8_);
+
+        let trait_impl_map = TraitImplMap::new();
+        let is_copy = |type_: &Type| {
+            let mut type_trait_cache = TypeTraitCache::new();
+            trait_impl_map.extract_trait_id(type_, &copy, &mut type_trait_cache, &cache).is_some()
+        };
+        // impl<T: ?Sized> Copy for PhantomData<T>
+        assert!(is_copy(&phantom_string));
+        // impl<T: Copy> Copy for Option<T>
+        assert!(is_copy(&option_u8));
+        assert!(!is_copy(&option_string));
+        assert!(!is_copy(&string));
+    });
+}
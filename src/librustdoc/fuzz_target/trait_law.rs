@@ -0,0 +1,221 @@
+use crate::clean::Type;
+use crate::fuzz_target::api_graph::ApiGraph;
+use crate::fuzz_target::api_sequence::{_generate_indent, ApiSequence};
+use crate::fuzz_target::api_util;
+use crate::fuzz_target::named_value::NamedValue;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_span::def_id::DefId;
+use rustc_span::symbol::sym;
+
+/// law check drivers written next to the chosen sequences
+static MAX_LAW_CHECK_DRIVERS: usize = 20;
+
+/// A trait whose laws the drivers check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum TraitLaw {
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+}
+
+/// named after the path of the trait
+impl NamedValue for TraitLaw {
+    const NAMES: &'static [(&'static str, TraitLaw)] = &[
+        ("core::cmp::PartialEq", TraitLaw::PartialEq),
+        ("core::cmp::Eq", TraitLaw::Eq),
+        ("core::cmp::PartialOrd", TraitLaw::PartialOrd),
+        ("core::cmp::Ord", TraitLaw::Ord),
+        ("core::hash::Hash", TraitLaw::Hash),
+        ("core::clone::Clone", TraitLaw::Clone),
+    ];
+}
+
+impl TraitLaw {
+    /// every law is checked against equality, the comparison traits imply `PartialEq`
+    fn implies_partial_eq(&self) -> bool {
+        !matches!(self, TraitLaw::Hash | TraitLaw::Clone)
+    }
+}
+
+/// The calls producing three values of one type, and the traits of the type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LawCheck {
+    pub(crate) values: [usize; 3],
+    pub(crate) laws: Vec<TraitLaw>,
+}
+
+/// Drivers checking the laws of `PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash` and `Clone` for
+/// local types implementing any of them by hand. The shortest sequence returning such a type
+/// runs three times, on its own part of the input each, and the laws are checked on every
+/// combination of the three values. Derived impls keep the driver of types that also have a
+/// hand-written one, as the laws tie the traits together.
+pub(crate) fn law_check_sequences(api_graph: &ApiGraph<'_>) -> Vec<ApiSequence> {
+    let tcx = api_graph.cx.tcx;
+    let mut law_types = FxHashMap::default();
+    for (did, trait_impls) in &api_graph.trait_impl_map.inner {
+        // the driver types are no law types, `FuzzEvil` breaks the laws on purpose
+        if !api_util::is_crate_type(*did) {
+            continue;
+        }
+        let mut laws = Vec::new();
+        let mut hand_written = false;
+        for trait_impl in trait_impls {
+            // generic types would need a producer for every instantiation
+            if trait_impl.negative
+                || !matches!(trait_impl.for_, Type::Path { .. })
+                || trait_impl.for_.generics().map_or(false, |generics| !generics.is_empty())
+            {
+                continue;
+            }
+            let Some(law) = api_graph
+                .full_name_map
+                .get_full_name(trait_impl.trait_.def_id())
+                .and_then(|name| TraitLaw::from_name(name))
+            else {
+                continue;
+            };
+            laws.push(law);
+            hand_written |= !tcx.has_attr(trait_impl.impl_id, sym::automatically_derived);
+        }
+        laws.sort();
+        laws.dedup();
+        if hand_written && laws.iter().any(TraitLaw::implies_partial_eq) {
+            law_types.insert(*did, laws);
+        }
+    }
+    let mut res = Vec::new();
+    for (did, producer) in shortest_producers(api_graph, |did| law_types.contains_key(&did)) {
+        if res.len() >= MAX_LAW_CHECK_DRIVERS {
+            break;
+        }
+        let len = producer.len();
+        let mut sequence =
+            producer.merge_another_sequence(producer).merge_another_sequence(producer);
+        if !sequence._is_decodable(api_graph.input_layout) {
+            continue;
+        }
+        let values = [len - 1, 2 * len - 1, 3 * len - 1];
+        sequence.law_check = Some(LawCheck { values, laws: law_types[&did].clone() });
+        res.push(sequence);
+    }
+    res
+}
+
+/// The shortest generated sequence returning a value of every type `wanted` accepts, ordered by
+/// the name of the type
+pub(crate) fn shortest_producers<'a>(
+    api_graph: &'a ApiGraph<'_>,
+    wanted: impl Fn(DefId) -> bool,
+) -> Vec<(DefId, &'a ApiSequence)> {
+    let mut producers: FxHashMap<DefId, &ApiSequence> = FxHashMap::default();
+    for sequence in &api_graph.api_sequences {
+        let Some(last) = sequence.last_api_func_index() else {
+            continue;
+        };
+        let Some(Type::Path { path }) = &api_graph.api_functions[last].output else {
+            continue;
+        };
+        let did = path.def_id();
+        if wanted(did) && producers.get(&did).map_or(true, |former| former.len() > sequence.len())
+        {
+            producers.insert(did, sequence);
+        }
+    }
+    let mut producers = producers.into_iter().collect::<Vec<_>>();
+    producers.sort_by_key(|(did, _)| api_graph.full_name_map.get_full_name(*did).cloned());
+    producers
+}
+
+/// Assertions of the laws, over all combinations of the three values
+pub(crate) fn law_check_statements(
+    law_check: &LawCheck,
+    local_param_prefix: &str,
+    indent_size: usize,
+) -> String {
+    let has = |law| law_check.laws.contains(&law);
+    let partial_eq = law_check.laws.iter().any(TraitLaw::implies_partial_eq);
+    let mut reflexive = Vec::new();
+    let mut pairs = Vec::new();
+    let mut triples = Vec::new();
+    if partial_eq {
+        pairs.push("assert_eq!(x == y, y == x, \"PartialEq is not symmetric\");");
+        pairs.push("assert_eq!(x != y, !(x == y), \"ne disagrees with eq\");");
+        triples.push("if x == y && y == z { assert!(x == z, \"PartialEq is not transitive\"); }");
+    }
+    if has(TraitLaw::Eq) {
+        reflexive.push("assert!(x == x, \"Eq is not reflexive\");");
+    }
+    if has(TraitLaw::PartialOrd) {
+        pairs.push("let fuzz_ordering = std::cmp::PartialOrd::partial_cmp(x, y);");
+        pairs.push(
+            "assert_eq!(fuzz_ordering == Some(std::cmp::Ordering::Equal), x == y, \"PartialOrd disagrees with PartialEq\");",
+        );
+        pairs.push(
+            "assert_eq!(fuzz_ordering, std::cmp::PartialOrd::partial_cmp(y, x).map(std::cmp::Ordering::reverse), \"PartialOrd is not antisymmetric\");",
+        );
+        pairs.push(
+            "assert_eq!(x < y, fuzz_ordering == Some(std::cmp::Ordering::Less), \"lt disagrees with partial_cmp\");",
+        );
+        triples.push("if x < y && y < z { assert!(x < z, \"PartialOrd is not transitive\"); }");
+    }
+    if has(TraitLaw::Ord) {
+        reflexive.push(
+            "assert_eq!(std::cmp::Ord::cmp(x, x), std::cmp::Ordering::Equal, \"Ord is not reflexive\");",
+        );
+        pairs.push("let fuzz_cmp = std::cmp::Ord::cmp(x, y);");
+        pairs.push(
+            "assert_eq!(fuzz_cmp == std::cmp::Ordering::Equal, x == y, \"Ord disagrees with PartialEq\");",
+        );
+        pairs.push(
+            "assert_eq!(Some(fuzz_cmp), std::cmp::PartialOrd::partial_cmp(x, y), \"Ord disagrees with PartialOrd\");",
+        );
+        pairs.push(
+            "assert_eq!(fuzz_cmp, std::cmp::Ord::cmp(y, x).reverse(), \"Ord is not antisymmetric\");",
+        );
+        triples.push(
+            "if std::cmp::Ord::cmp(x, y).is_le() && std::cmp::Ord::cmp(y, z).is_le() { assert!(std::cmp::Ord::cmp(x, z).is_le(), \"Ord is not transitive\"); }",
+        );
+    }
+    // both need equality to compare with
+    if has(TraitLaw::Hash) && partial_eq {
+        pairs.push(
+            "if x == y { assert_eq!(fuzz_hash(x), fuzz_hash(y), \"equal values hash differently\"); }",
+        );
+    }
+    if has(TraitLaw::Clone) && partial_eq {
+        reflexive.push(
+            "if x == x { assert!(&std::clone::Clone::clone(x) == x, \"clone differs from the original\"); }",
+        );
+    }
+    let indent = _generate_indent(indent_size);
+    let values = law_check
+        .values
+        .iter()
+        .map(|value| format!("&{}{}", local_param_prefix, value))
+        .collect::<Vec<_>>();
+    let mut res = format!("{indent}let fuzz_values = [{}];\n", values.join(", "), indent = indent);
+    if pairs.iter().any(|statement| statement.contains("fuzz_hash")) {
+        res.push_str(&format!(
+            "{indent}fn fuzz_hash<T: std::hash::Hash>(value: &T) -> u64 {{\n{indent}    let mut hasher = std::collections::hash_map::DefaultHasher::new();\n{indent}    std::hash::Hash::hash(value, &mut hasher);\n{indent}    std::hash::Hasher::finish(&hasher)\n{indent}}}\n",
+            indent = indent
+        ));
+    }
+    let lines = |statements: &[&str], indent_size: usize| {
+        let indent = _generate_indent(indent_size);
+        statements.iter().map(|statement| format!("{}{}\n", indent, statement)).collect::<String>()
+    };
+    res.push_str(&format!("{indent}for x in fuzz_values {{\n", indent = indent));
+    res.push_str(&lines(&reflexive, indent_size + 4));
+    res.push_str(&format!("{indent}    for y in fuzz_values {{\n", indent = indent));
+    res.push_str(&lines(&pairs, indent_size + 8));
+    if !triples.is_empty() {
+        res.push_str(&format!("{indent}        for z in fuzz_values {{\n", indent = indent));
+        res.push_str(&lines(&triples, indent_size + 12));
+        res.push_str(&format!("{indent}        }}\n", indent = indent));
+    }
+    res.push_str(&format!("{indent}    }}\n{indent}}}\n", indent = indent));
+    res
+}
@@ -0,0 +1,114 @@
+use crate::clean::Type;
+use crate::fuzz_target::api_function::ApiFunction;
+use crate::fuzz_target::api_util::{doc_section, scan_type_with};
+use crate::fuzz_target::named_value::NamedValue;
+
+#[cfg(test)]
+mod tests;
+
+/// How unsafe APIs, and dereferences of raw pointers, enter the generated drivers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnsafePolicy {
+    /// like any other API
+    Include,
+    /// never
+    Exclude,
+    /// only in drivers of their own, named `test_unsafe_{crate}{i}`
+    Isolate,
+    /// only unsafe functions whose `# Safety` section any arguments satisfy
+    DocContract,
+}
+
+/// Words by which a `# Safety` section states an obligation, e.g. "`n` must be less than 10" or
+/// "only call it once"
+static OBLIGATION_WORDS: &'static [&'static str] = &[
+    "must",
+    "should",
+    "shall",
+    "only",
+    "if",
+    "unless",
+    "otherwise",
+    "not",
+    "never",
+    "cannot",
+    "need",
+    "needs",
+];
+
+/// Beginnings of words stating an obligation, e.g. "the caller ensures" or "undefined behavior"
+static OBLIGATION_STEMS: &'static [&'static str] = &[
+    "caller",
+    "ensur",
+    "requir",
+    "undefined",
+    "violat",
+    "assum",
+    "guarante",
+    "expect",
+    "invalid",
+    "precondition",
+    "responsib",
+    "unchecked",
+];
+
+impl NamedValue for UnsafePolicy {
+    const NAMES: &'static [(&'static str, UnsafePolicy)] = &[
+        ("include", UnsafePolicy::Include),
+        ("exclude", UnsafePolicy::Exclude),
+        ("isolate", UnsafePolicy::Isolate),
+        ("doc-contract", UnsafePolicy::DocContract),
+    ];
+}
+
+impl UnsafePolicy {
+    /// whether a sequence may call the unsafe function
+    pub(crate) fn admits_function(&self, api_function: &ApiFunction) -> bool {
+        match self {
+            UnsafePolicy::Include | UnsafePolicy::Isolate => true,
+            UnsafePolicy::Exclude => false,
+            UnsafePolicy::DocContract => api_function.trivial_safety,
+        }
+    }
+
+    /// whether a sequence may dereference a raw pointer, which comes with no contract at all
+    pub(crate) fn admits_unsafe_deref(&self) -> bool {
+        match self {
+            UnsafePolicy::Include | UnsafePolicy::Isolate => true,
+            UnsafePolicy::Exclude | UnsafePolicy::DocContract => false,
+        }
+    }
+}
+
+/// Whether the documented contract of an unsafe function holds for any arguments: there is a
+/// `# Safety` section stating no obligation, with none of `OBLIGATION_WORDS` and no word
+/// beginning with one of `OBLIGATION_STEMS` (e.g. "it is always safe to call"), and no argument
+/// is a raw pointer, which nothing the driver passes could make valid.
+pub(crate) fn is_trivial_safety_contract(doc: &str, inputs: &[Type]) -> bool {
+    let Some(section) = doc_section(doc, "Safety") else {
+        return false;
+    };
+    let section = section.to_lowercase();
+    let words = section.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty());
+    let mut stated = false;
+    for word in words {
+        if OBLIGATION_WORDS.contains(&word)
+            || OBLIGATION_STEMS.iter().any(|stem| word.starts_with(stem))
+        {
+            return false;
+        }
+        stated = true;
+    }
+    // an empty section states nothing, not that nothing is asked
+    if !stated {
+        return false;
+    }
+    let mut raw_pointer = false;
+    for input in inputs {
+        scan_type_with(input, &mut |type_: &Type| {
+            raw_pointer |= matches!(type_, Type::RawPointer(..));
+            !raw_pointer
+        });
+    }
+    !raw_pointer
+}
@@ -0,0 +1,70 @@
+use super::is_trivial_safety_contract;
+use crate::clean::{PrimitiveType, Type};
+use rustc_hir::Mutability;
+
+fn safety_doc(section: &str) -> String {
+    format!("Does something.\n\n# Safety\n\n{}\n\n# Examples\n\n```\nlet x = 1;\n```\n", section)
+}
+
+#[test]
+fn test_std_safety_sections_are_obligations() {
+    let sections = [
+        // Vec::set_len
+        "- `new_len` must be less than or equal to [`capacity()`].\n\
+         - The elements at `old_len..new_len` must be initialized.",
+        // str::from_utf8_unchecked
+        "The bytes passed in must be valid UTF-8.",
+        // slice::get_unchecked
+        "Calling this method with an out-of-bounds index is *[undefined behavior]*\n\
+         even if the resulting reference is not used.",
+        // String::as_mut_vec
+        "This function is unsafe because the returned `&mut Vec` allows writing\n\
+         bytes which are not valid UTF-8. If this constraint is violated, using\n\
+         the original `String` after dropping the `&mut Vec` may violate memory\n\
+         safety, as the rest of the standard library assumes that `String`s are\n\
+         valid UTF-8.",
+        // NonZero::new_unchecked
+        "The value must not be zero.",
+        // char::from_u32_unchecked
+        "This function is unsafe, as it may construct invalid `char` values.\n\n\
+         For a safe version of this function, see the [`from_u32`] function.",
+        // env::set_var
+        "This function is safe to call in a single-threaded program.\n\n\
+         In multi-threaded programs on other operating systems, the only safe option is\n\
+         to not use `set_var` or `remove_var` at all.",
+        // restrictions on the arguments without any of the words of the old blacklist
+        "`n` must be less than 10.",
+        "The slice must be sorted.",
+        "It is sound as long as the callback is not reentrant.",
+    ];
+    for section in sections {
+        assert!(!is_trivial_safety_contract(&safety_doc(section), &[]), "{section}");
+    }
+}
+
+#[test]
+fn test_safety_sections_without_obligation() {
+    let sections = [
+        "This function is unsafe for historical reasons and is always safe to call.",
+        "Calling it is always sound, it is `unsafe` to match the trait it implements.",
+    ];
+    for section in sections {
+        assert!(is_trivial_safety_contract(&safety_doc(section), &[]), "{section}");
+    }
+}
+
+#[test]
+fn test_missing_or_empty_safety_section() {
+    assert!(!is_trivial_safety_contract("Does something.", &[]));
+    assert!(!is_trivial_safety_contract("Does something.\n\n# Safety\n\n# Examples\n", &[]));
+}
+
+#[test]
+fn test_raw_pointer_arguments() {
+    let doc = safety_doc("It is always safe to call.");
+    let byte = Type::Primitive(PrimitiveType::U8);
+    let pointer = Type::RawPointer(Mutability::Mut, Box::new(byte.clone()));
+    assert!(is_trivial_safety_contract(&doc, &[byte.clone()]));
+    assert!(!is_trivial_safety_contract(&doc, &[pointer.clone()]));
+    assert!(!is_trivial_safety_contract(&doc, &[Type::Tuple(vec![byte, pointer])]));
+}
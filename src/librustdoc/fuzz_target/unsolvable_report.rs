@@ -0,0 +1,192 @@
+use rustc_data_structures::fx::FxHashMap;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+static REPORT_JSON_FILE: &'static str = "unsolvable_generic.json";
+static REPORT_SUMMARY_FILE: &'static str = "unsolvable_generic.txt";
+
+/// why the solver could not produce a mono function for a generic api
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum UnsolvableReason {
+    /// bound like `F: Fn(u8)` that the solver does not model
+    UnsupportedBound { bound: String },
+    /// argument type the solver does not model, e.g. `dyn Trait` or a qualified path
+    UnsupportedType { input: usize, type_: String },
+    /// generic that is neither declared by the function nor by its impl
+    UnknownGeneric { name: String },
+    /// generic param that does not occur in any input, so no candidate can be inferred
+    UndeterminedParam { param: String },
+    /// no reachable type can be passed as this input
+    NoProducer { input: usize, type_: String },
+    /// every candidate of the param fails the trait bound
+    UnsatisfiedBound { trait_: String, candidates: Vec<String> },
+    /// user supplied hint does not satisfy the trait bound
+    RejectedHint { type_: String, trait_: String },
+    /// solutions were found but their output exceed `MAX_TYPE_DEPTH`
+    DepthLimit { output: String },
+    /// solver stopped at `MAX_MONO_PER_FUNC`, later instantiations are not searched
+    MonoCap { limit: usize },
+}
+
+impl UnsolvableReason {
+    /// reasons which depend on the reachable types of the current iteration
+    pub(crate) fn is_transient(&self) -> bool {
+        matches!(self, UnsolvableReason::NoProducer { .. })
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            UnsolvableReason::UnsupportedBound { .. } => "unsupported bound",
+            UnsolvableReason::UnsupportedType { .. } => "unsupported type",
+            UnsolvableReason::UnknownGeneric { .. } => "unknown generic",
+            UnsolvableReason::UndeterminedParam { .. } => "undetermined param",
+            UnsolvableReason::NoProducer { .. } => "no producer",
+            UnsolvableReason::UnsatisfiedBound { .. } => "unsatisfied bound",
+            UnsolvableReason::RejectedHint { .. } => "rejected hint",
+            UnsolvableReason::DepthLimit { .. } => "depth limit",
+            UnsolvableReason::MonoCap { .. } => "mono cap",
+        }
+    }
+}
+
+/// record a reason, keep one entry per unsatisfied trait and merge their candidates
+pub(crate) fn add_reason(reasons: &mut Vec<UnsolvableReason>, reason: UnsolvableReason) {
+    if let UnsolvableReason::UnsatisfiedBound { ref trait_, ref candidates } = reason {
+        for old in reasons.iter_mut() {
+            if let UnsolvableReason::UnsatisfiedBound { trait_: old_trait, candidates: old_candidates } =
+                old
+            {
+                if old_trait == trait_ {
+                    for candidate in candidates {
+                        if !old_candidates.contains(candidate) {
+                            old_candidates.push(candidate.clone());
+                        }
+                    }
+                    return;
+                }
+            }
+        }
+    }
+    if !reasons.contains(&reason) {
+        reasons.push(reason);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct UnsolvableEntry {
+    pub(crate) function: String,
+    pub(crate) signature: String,
+    pub(crate) local: bool,
+    pub(crate) reasons: Vec<UnsolvableReason>,
+}
+
+/// generic apis without any reserved mono function, those whose search was cut short and those
+/// with a rejected hint
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct UnsolvableReport {
+    pub(crate) entries: Vec<UnsolvableEntry>,
+    /// solved apis whose search stopped at `MAX_MONO_PER_FUNC`, with the `MonoCap` reason only
+    pub(crate) capped: Vec<UnsolvableEntry>,
+    /// apis with a hint failing the trait bounds, solved or not, with `RejectedHint` reasons only
+    pub(crate) rejected_hints: Vec<UnsolvableEntry>,
+}
+
+impl UnsolvableReport {
+    pub(crate) fn new() -> Self {
+        UnsolvableReport { entries: Vec::new(), capped: Vec::new(), rejected_hints: Vec::new() }
+    }
+
+    pub(crate) fn add_entry(&mut self, entry: UnsolvableEntry) {
+        self.entries.push(entry);
+    }
+
+    pub(crate) fn add_capped(&mut self, entry: UnsolvableEntry) {
+        self.capped.push(entry);
+    }
+
+    pub(crate) fn add_rejected_hints(&mut self, entry: UnsolvableEntry) {
+        self.rejected_hints.push(entry);
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        let mut res = String::new();
+        let mut kind_count = FxHashMap::<&'static str, usize>::default();
+        let mut trait_count = FxHashMap::<&str, usize>::default();
+        for entry in self.entries.iter() {
+            for reason in entry.reasons.iter() {
+                *kind_count.entry(reason.kind()).or_insert(0) += 1;
+                if let UnsolvableReason::UnsatisfiedBound { trait_, .. } = reason {
+                    *trait_count.entry(trait_.as_str()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        res.push_str(&format!("unsolvable generic functions: {}\n", self.entries.len()));
+        res.push_str("== reasons ==\n");
+        let mut kinds: Vec<_> = kind_count.into_iter().collect();
+        kinds.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        for (kind, count) in kinds {
+            res.push_str(&format!("{}: {}\n", kind, count));
+        }
+        res.push_str("== unsatisfied traits ==\n");
+        let mut traits: Vec<_> = trait_count.into_iter().collect();
+        traits.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        for (trait_, count) in traits {
+            res.push_str(&format!("{}: {}\n", trait_, count));
+        }
+        res.push_str("== functions ==\n");
+        for entry in self.entries.iter() {
+            res.push_str(&format!(
+                "{}{}\n",
+                if entry.local { "" } else { "(external) " },
+                entry.signature
+            ));
+            for reason in entry.reasons.iter() {
+                let detail = match reason {
+                    UnsolvableReason::UnsupportedBound { bound } => bound.clone(),
+                    UnsolvableReason::UnsupportedType { input, type_ }
+                    | UnsolvableReason::NoProducer { input, type_ } => {
+                        format!("input#{}: {}", input, type_)
+                    }
+                    UnsolvableReason::UnknownGeneric { name } => name.clone(),
+                    UnsolvableReason::UndeterminedParam { param } => param.clone(),
+                    UnsolvableReason::UnsatisfiedBound { trait_, candidates } => {
+                        format!("{} (tried: {})", trait_, candidates.join(", "))
+                    }
+                    UnsolvableReason::RejectedHint { type_, trait_ } => {
+                        format!("{} does not implement {}", type_, trait_)
+                    }
+                    UnsolvableReason::DepthLimit { output } => output.clone(),
+                    UnsolvableReason::MonoCap { limit } => limit.to_string(),
+                };
+                res.push_str(&format!("    {}: {}\n", reason.kind(), detail));
+            }
+            if entry.reasons.is_empty() {
+                res.push_str("    no candidate left\n");
+            }
+        }
+        res.push_str(&format!("== mono cap reached: {} ==\n", self.capped.len()));
+        for entry in self.capped.iter() {
+            res.push_str(&format!("{}\n", entry.signature));
+        }
+        res.push_str(&format!("== rejected hints: {} ==\n", self.rejected_hints.len()));
+        for entry in self.rejected_hints.iter() {
+            res.push_str(&format!("{}\n", entry.signature));
+            for reason in entry.reasons.iter() {
+                if let UnsolvableReason::RejectedHint { type_, trait_ } = reason {
+                    res.push_str(&format!("    {} does not implement {}\n", type_, trait_));
+                }
+            }
+        }
+        res
+    }
+
+    pub(crate) fn write_files(&self, dir: &PathBuf) {
+        fs::create_dir_all(dir).unwrap();
+        let json = serde_json::to_string_pretty(self).unwrap();
+        fs::write(dir.join(REPORT_JSON_FILE), json).unwrap();
+        fs::write(dir.join(REPORT_SUMMARY_FILE), self.summary()).unwrap();
+    }
+}
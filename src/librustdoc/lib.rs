@@ -728,6 +728,148 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("html-no-source", |o| {
             o.optflag("", "html-no-source", "Disable HTML source code pages generation")
         }),
+        unstable("fuzz-hints", |o| {
+            o.optopt(
+                "",
+                "fuzz-hints",
+                "File mapping generic params of APIs to concrete types, e.g. `krate::decode::T = u8`",
+                "PATH",
+            )
+        }),
+        unstable("fuzz-evil-impls", |o| {
+            o.optflag(
+                "",
+                "fuzz-evil-impls",
+                "Instantiate generic APIs with types whose trait impls misbehave on fuzz input",
+            )
+        }),
+        unstable("fuzz-allocator", |o| {
+            o.optflag(
+                "",
+                "fuzz-allocator",
+                "Instantiate `A: Allocator` params with an allocator failing on fuzz input",
+            )
+        }),
+        unstable("fuzz-global-allocator", |o| {
+            o.optflag(
+                "",
+                "fuzz-global-allocator",
+                "Install a global allocator failing on fuzz input in generated fuzz targets",
+            )
+        }),
+        unstable("fuzz-strategy", |o| {
+            o.optopt(
+                "",
+                "fuzz-strategy",
+                "Graph traversal building API sequences for fuzz targets",
+                "default|bfs|fast-bfs|bfs-end-point|fast-bfs-end-point|random-walk|random-walk-end-point|try-deep-bfs|backward-search",
+            )
+        }),
+        unstable("fuzz-seed", |o| {
+            o.optopt(
+                "",
+                "fuzz-seed",
+                "Seed of the random choices of the fuzz target generator",
+                "N",
+            )
+        }),
+        unstable("fuzz-coverage", |o| {
+            o.optopt(
+                "",
+                "fuzz-coverage",
+                "Coverage of a former fuzzing round, an llvm-cov export or AFL output directory",
+                "PATH",
+            )
+        }),
+        unstable("fuzz-minimize", |o| {
+            o.optopt(
+                "",
+                "fuzz-minimize",
+                "Write a minimizer shrinking the crashes of the named fuzz target",
+                "DRIVER",
+            )
+        }),
+        unstable("fuzz-runtime-crate", |o| {
+            o.optflag(
+                "",
+                "fuzz-runtime-crate",
+                "Decode fuzz input with a runtime crate written next to the fuzz targets",
+            )
+        }),
+        unstable("fuzz-layout", |o| {
+            o.optopt(
+                "",
+                "fuzz-layout",
+                "How fuzz targets lay out their input, `sequential` length-prefixes dynamic values",
+                "split|sequential",
+            )
+        }),
+        unstable("fuzz-op-loop", |o| {
+            o.optflag(
+                "",
+                "fuzz-op-loop",
+                "Also write fuzz targets calling fuzz-selected methods of a receiver in a loop",
+            )
+        }),
+        unstable("fuzz-unsafe", |o| {
+            o.optopt(
+                "",
+                "fuzz-unsafe",
+                "How fuzz targets call unsafe APIs",
+                "include|exclude|isolate|doc-contract",
+            )
+        }),
+        unstable("fuzz-differential", |o| {
+            o.optflag(
+                "",
+                "fuzz-differential",
+                "Also write fuzz targets asserting that mono variants of a generic API agree",
+            )
+        }),
+        unstable("fuzz-trait-laws", |o| {
+            o.optflag(
+                "",
+                "fuzz-trait-laws",
+                "Also write fuzz targets checking the laws of Eq, Ord, Hash and Clone impls",
+            )
+        }),
+        unstable("fuzz-round-trip", |o| {
+            o.optflag(
+                "",
+                "fuzz-round-trip",
+                "Also write fuzz targets asserting that values read back equal the values written",
+            )
+        }),
+        unstable("fuzz-expect-documented-panics", |o| {
+            o.optflag(
+                "",
+                "fuzz-expect-documented-panics",
+                "Let fuzz targets ignore panics of APIs whose doc has a `# Panics` section",
+            )
+        }),
+        unstable("fuzz-engine", |o| {
+            o.optopt(
+                "",
+                "fuzz-engine",
+                "Fuzzer the fuzz targets are written for, `libfuzzer` writes a cargo-fuzz project",
+                "afl|libfuzzer",
+            )
+        }),
+        unstable("fuzz-multiplex", |o| {
+            o.optflag(
+                "",
+                "fuzz-multiplex",
+                "Write one fuzz target running the sequence picked by the first input bytes",
+            )
+        }),
+        unstable("fuzz-regression", |o| {
+            o.optopt(
+                "",
+                "fuzz-regression",
+                "Write a program turning crashes of the named fuzz target into #[test] functions",
+                "DRIVER",
+            )
+        }),
     ]
 }
 